@@ -13,6 +13,28 @@ FEATURES=(
     [4]="properties_as_vector"
     [5]="chrono properties_as_hashmap"
     [6]="chrono properties_as_vector"
+    [7]="encoding"
+    [8]="gzip zstd xz"
+    [9]="zip"
+    [10]="tar gzip"
+    [11]="stacktrace"
+    [12]="stacktrace serde"
+    [13]="cluster"
+    [14]="sqlite"
+    [15]="sqlite chrono properties_as_hashmap"
+    [16]="shard"
+    [17]="gate"
+    [18]="quarantine"
+    [19]="quarantine serde gate"
+    [20]="query"
+    [21]="query properties_as_vector"
+    [22]="html"
+    [23]="html properties_as_hashmap"
+    [24]="sarif"
+    [25]="otlp"
+    [26]="otlp properties_as_vector"
+    [27]="export"
+    [28]="export chrono properties_as_hashmap"
 )
 
 run_doc() {
@@ -23,7 +45,7 @@ run_doc() {
     do
         cargo doc --no-default-features --features "$FEATURE"
     done
-    cargo doc --no-default-features --features="document-features,serde,properties_as_hashmap,properties_as_vector,chrono"
+    cargo doc --no-default-features --features="document-features,serde,properties_as_hashmap,properties_as_vector,chrono,encoding,gzip,zstd,xz,zip,tar,stacktrace,cluster,sqlite,shard,gate,quarantine,query,html,sarif,otlp,export"
 }
 
 run_fmt() {
@@ -0,0 +1,124 @@
+//! Parse every JUnit report found in a zip or tar archive
+//!
+//! Archive entries are selected with a glob pattern matched against their
+//! path within the archive, for example `**/TEST-*.xml`. `*` does not match
+//! path separators, use `**` to match any number of directories.
+
+use crate::{from_reader_with_options, Error, ParseOptions, TestSuites};
+use glob::{MatchOptions, Pattern};
+#[cfg(feature = "tar")]
+use std::io::BufRead;
+use std::io::BufReader;
+#[cfg(feature = "zip")]
+use std::io::{Read, Seek};
+
+/// Path of an archive entry along with the result of parsing it
+pub type ArchiveReport = (String, Result<TestSuites, Error>);
+
+/// Options used to match entry paths against the glob pattern
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Parse every file of the zip archive read from `reader` whose path matches
+/// `pattern`
+///
+/// An error is returned if the pattern is invalid or if the archive can not
+/// be read. Errors while parsing an entry are reported along with its path.
+///
+/// # Example
+/// ```no_run
+/// use junit_parser::ParseOptions;
+/// use std::fs::File;
+///
+/// let file = File::open("test-results.zip").unwrap();
+/// let options = ParseOptions::default();
+/// let reports = junit_parser::archive::zip_reports(file, "**/*.xml", &options).unwrap();
+/// for (path, report) in &reports {
+///     match report {
+///         Ok(t) => println!("{}: {} suites", path, t.suites.len()),
+///         Err(err) => eprintln!("{}: {}", path, err),
+///     }
+/// }
+/// let merged = junit_parser::archive::merge_reports(reports).unwrap();
+/// ```
+#[cfg(feature = "zip")]
+pub fn zip_reports<R: Read + Seek>(
+    reader: R,
+    pattern: &str,
+    options: &ParseOptions,
+) -> Result<Vec<ArchiveReport>, Error> {
+    let pattern = Pattern::new(pattern)?;
+    let mut archive = zip::ZipArchive::new(reader)?;
+    let mut reports = Vec::new();
+    for i in 0..archive.len() {
+        let file = archive.by_index(i)?;
+        if file.is_dir() || !pattern.matches_with(file.name(), MATCH_OPTIONS) {
+            continue;
+        }
+        let path = file.name().to_string();
+        let report = from_reader_with_options(BufReader::new(file), options);
+        reports.push((path, report));
+    }
+    Ok(reports)
+}
+
+/// Parse every file of the tar archive read from `reader` whose path matches
+/// `pattern`
+///
+/// With the `gzip`, `zstd` or `xz` features, compressed archives such as
+/// `.tar.gz` are decompressed transparently.
+///
+/// An error is returned if the pattern is invalid or if the archive can not
+/// be read. Errors while parsing an entry are reported along with its path.
+#[cfg(feature = "tar")]
+pub fn tar_reports<R: BufRead>(
+    reader: R,
+    pattern: &str,
+    options: &ParseOptions,
+) -> Result<Vec<ArchiveReport>, Error> {
+    let pattern = Pattern::new(pattern)?;
+    #[cfg(any(feature = "gzip", feature = "zstd", feature = "xz"))]
+    let reader = crate::compression::decompressing_reader(reader)?;
+    let mut archive = tar::Archive::new(reader);
+    let mut reports = Vec::new();
+    for entry in archive.entries()? {
+        let entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry.path()?.to_string_lossy().to_string();
+        if !pattern.matches_with(&path, MATCH_OPTIONS) {
+            continue;
+        }
+        let report = from_reader_with_options(BufReader::new(entry), options);
+        reports.push((path, report));
+    }
+    Ok(reports)
+}
+
+/// Merge the reports of an archive into a single [`TestSuites`]
+///
+/// Each [`TestSuite`](crate::TestSuite), along with its test cases, has its
+/// [`source_file`](crate::TestSuite::source_file) set to the path of the
+/// entry it was read from, and the totals of the test suites are summed up.
+/// The first error while parsing an entry is returned.
+pub fn merge_reports<I: IntoIterator<Item = ArchiveReport>>(
+    reports: I,
+) -> Result<TestSuites, Error> {
+    let mut merged = TestSuites::default();
+    for (path, report) in reports {
+        for mut suite in report?.suites {
+            merged.time += suite.time;
+            merged.tests += suite.tests;
+            merged.errors += suite.errors;
+            merged.failures += suite.failures;
+            merged.skipped += suite.skipped;
+            suite.set_source_file(&path);
+            merged.suites.push(suite);
+        }
+    }
+    Ok(merged)
+}
@@ -0,0 +1,169 @@
+//! Replay a report as Azure Pipelines logging commands
+//!
+//! Each failing test case, or test case in error, gives an error issue and
+//! each test case passing after reruns a warning one, located from the `file`
+//! and `line` attributes of the test case:
+//!
+//! ```text
+//! ##vso[task.logissue type=error;sourcepath=src/cart.py;linenumber=12;code=AssertionError]test_cart: expected 10, got 11
+//! ```
+//!
+//! Azure Pipelines has no logging command per test case, [`publish`] gives
+//! the command publishing report files to its test tab, and [`complete`] the
+//! one setting the result of the task from the outcome of the report. The
+//! latter ends the task when printed, it is only meant for a step dedicated
+//! to the report.
+//!
+//! # Example
+//!
+//! ```
+//! use junit_parser::azure::{complete, issues};
+//! use std::io::Cursor;
+//!
+//! let xml = r#"<testsuite><testcase name="a" file="src/a.py" line="3"><failure message="boom"/></testcase></testsuite>"#;
+//! let report = junit_parser::from_reader(Cursor::new(xml)).unwrap();
+//! assert_eq!(
+//!     issues(&report)[0].to_string(),
+//!     "##vso[task.logissue type=error;sourcepath=src/a.py;linenumber=3]a: boom"
+//! );
+//! assert_eq!(
+//!     complete(&report).to_string(),
+//!     "##vso[task.complete result=Failed]1 test cases failed"
+//! );
+//! ```
+
+use crate::{TestCase, TestStatus, TestSuites};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A logging command, formatted with [`fmt::Display`]
+pub struct LoggingCommand {
+    /// Area and action of the command, such as `task.logissue`
+    pub command: String,
+    /// Properties of the command, in order
+    pub properties: Vec<(String, String)>,
+    /// Message of the command
+    pub message: String,
+}
+
+impl LoggingCommand {
+    /// New command `command` with `properties` and `message`
+    fn new(command: &str, properties: Vec<(&str, String)>, message: String) -> Self {
+        Self {
+            command: command.to_string(),
+            properties: properties
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect(),
+            message,
+        }
+    }
+}
+
+/// Escape `s` as the message of a logging command
+fn escape_message(s: &str) -> String {
+    s.replace('%', "%AZP25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape `s` as the value of a property of a logging command
+fn escape_property(s: &str) -> String {
+    escape_message(s).replace(';', "%3B").replace(']', "%5D")
+}
+
+impl fmt::Display for LoggingCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "##vso[{}", self.command)?;
+        for (i, (key, value)) in self.properties.iter().enumerate() {
+            let sep = if i == 0 { ' ' } else { ';' };
+            write!(f, "{}{}={}", sep, key, escape_property(value))?;
+        }
+        write!(f, "]{}", escape_message(&self.message))
+    }
+}
+
+/// Issue of the test case `tc`, if it failed or passed after reruns
+fn issue(tc: &TestCase) -> Option<LoggingCommand> {
+    let (level, code, message) = match &tc.status {
+        TestStatus::Failure(f) => ("error", &f.failure_type, f.message.clone()),
+        TestStatus::Error(e) => ("error", &e.error_type, e.message.clone()),
+        TestStatus::Success if !tc.reruns.is_empty() => {
+            let message = format!("passed after {} failed attempts", tc.reruns.len());
+            ("warning", &tc.reruns[0].rerun_type, message)
+        }
+        _ => return None,
+    };
+    let mut properties = vec![("type", level.to_string())];
+    if let Some(file) = &tc.file {
+        properties.push(("sourcepath", file.clone()));
+        if let Some(line) = tc.line {
+            properties.push(("linenumber", line.to_string()));
+        }
+    }
+    if !code.is_empty() {
+        properties.push(("code", code.clone()));
+    }
+    let message = if message.is_empty() {
+        tc.name.clone()
+    } else {
+        format!("{}: {}", tc.name, message)
+    };
+    Some(LoggingCommand::new("task.logissue", properties, message))
+}
+
+/// Issues of the failing test cases, the test cases in error and the flaky
+/// test cases of `suites`
+pub fn issues(suites: &TestSuites) -> Vec<LoggingCommand> {
+    let mut res = Vec::new();
+    for ts in suites.suites.iter() {
+        ts.visit_cases(&mut |_, tc| {
+            if let Some(issue) = issue(tc) {
+                res.push(issue);
+            }
+        });
+    }
+    res
+}
+
+/// Command publishing the JUnit reports `files` to the test tab of the run,
+/// titled `run_title`
+pub fn publish(files: &[&str], run_title: Option<&str>) -> LoggingCommand {
+    let mut properties = vec![("type", "JUnit".to_string())];
+    if let Some(title) = run_title {
+        properties.push(("runTitle", title.to_string()));
+    }
+    LoggingCommand::new("results.publish", properties, files.join(","))
+}
+
+/// Command completing the task as failed if a test case of `suites` failed
+/// or is in error, as succeeded with issues if one passed after reruns, and
+/// as succeeded otherwise
+///
+/// Azure Pipelines ends the task as soon as the command is printed.
+pub fn complete(suites: &TestSuites) -> LoggingCommand {
+    let mut failed = 0;
+    let mut flaky = 0;
+    for ts in suites.suites.iter() {
+        ts.visit_cases(&mut |_, tc| match tc.status {
+            TestStatus::Failure(_) | TestStatus::Error(_) => failed += 1,
+            TestStatus::Success if !tc.reruns.is_empty() => flaky += 1,
+            _ => {}
+        });
+    }
+    let (result, message) = if failed > 0 {
+        ("Failed", format!("{} test cases failed", failed))
+    } else if flaky > 0 {
+        (
+            "SucceededWithIssues",
+            format!("{} test cases passed after reruns", flaky),
+        )
+    } else {
+        ("Succeeded", "All test cases passed".to_string())
+    };
+    LoggingCommand::new(
+        "task.complete",
+        vec![("result", result.to_string())],
+        message,
+    )
+}
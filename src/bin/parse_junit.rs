@@ -1,20 +1,83 @@
 use std::env;
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{BufReader, Write};
+use std::path::Path;
 use std::process;
 
-use junit_parser::from_reader;
+use junit_parser::{from_reader_with_options, ParseOptions};
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-
-    if args.len() != 2 {
-        eprintln!("Usage: {} <junit_file.xml>", args[0]);
-        process::exit(1);
-    }
+fn usage(program: &str) -> ! {
+    eprintln!("Usage: {} <junit_file.xml>", program);
+    eprintln!("       {} flaky <junit_file.xml>...", program);
+    eprintln!(
+        "       {} durations <junit_file.xml> [baseline.xml]",
+        program
+    );
+    eprintln!(
+        "       {} annotate [--strip-prefix <prefix>]... [--max <n>] <junit_file.xml>...",
+        program
+    );
+    eprintln!(
+        "       {} markdown <junit_file.xml> [baseline.xml]",
+        program
+    );
+    eprintln!("       {} tap <junit_file.xml>", program);
+    eprintln!(
+        "       {} teamcity [--flow-id <id>] <junit_file.xml>...",
+        program
+    );
+    eprintln!(
+        "       {} azure [--publish] [--complete-task] <junit_file.xml>...",
+        program
+    );
+    eprintln!(
+        "       {} prometheus [--openmetrics] [--case-durations] [--label <name=value>]... <junit_file.xml>...",
+        program
+    );
+    eprintln!(
+        "       {} write [--profile <gitlab|jenkins>] <junit_file.xml>...",
+        program
+    );
+    #[cfg(feature = "gate")]
+    eprintln!(
+        "       {} gate <rules_file> <junit_file.xml> [baseline.xml]",
+        program
+    );
+    #[cfg(feature = "query")]
+    eprintln!("       {} query <expression> <junit_file.xml>", program);
+    #[cfg(feature = "html")]
+    eprintln!("       {} html <junit_file.xml>", program);
+    #[cfg(feature = "sarif")]
+    eprintln!("       {} sarif <junit_file.xml>...", program);
+    #[cfg(feature = "otlp")]
+    eprintln!("       {} otlp <junit_file.xml> <traces.json>", program);
+    #[cfg(feature = "export")]
+    eprintln!(
+        "       {} export [--jsonl] [--property <name>]... <junit_file.xml>...",
+        program
+    );
+    process::exit(1);
+}
 
-    let filename = &args[1];
+/// Exit with an error if `filename` is compressed in a format this build can
+/// not decompress
+fn check_compression(filename: &str) {
+    let feature = match Path::new(filename).extension().and_then(|e| e.to_str()) {
+        Some("gz") if cfg!(not(feature = "gzip")) => "gzip",
+        Some("zst") if cfg!(not(feature = "zstd")) => "zstd",
+        Some("xz") if cfg!(not(feature = "xz")) => "xz",
+        _ => return,
+    };
+    eprintln!(
+        "Error parsing '{}': decompressing it requires the `{}` feature",
+        filename, feature
+    );
+    process::exit(1);
+}
 
+/// Print the parsed report
+fn dump(filename: &str) {
+    check_compression(filename);
     let file = match File::open(filename) {
         Ok(file) => file,
         Err(err) => {
@@ -24,8 +87,12 @@ fn main() {
     };
 
     let reader = BufReader::new(file);
+    let options = ParseOptions {
+        track_positions: true,
+        ..Default::default()
+    };
 
-    match from_reader(reader) {
+    match from_reader_with_options(reader, &options) {
         Ok(test_suites) => {
             println!("{:#?}", test_suites);
         }
@@ -35,3 +102,466 @@ fn main() {
         }
     }
 }
+
+/// Parse the report at `filename`, exiting on error
+fn parse(filename: &str) -> junit_parser::TestSuites {
+    check_compression(filename);
+    match junit_parser::from_path(filename) {
+        Ok(test_suites) => test_suites,
+        Err(err) => {
+            eprintln!("Error parsing '{}': {}", filename, err);
+            process::exit(1);
+        }
+    }
+}
+
+/// Print the flaky tests found in reports given from the oldest to the newest
+fn flaky(filenames: &[String]) {
+    let history: Vec<_> = filenames.iter().map(|f| parse(f)).collect();
+
+    let report = junit_parser::flaky::flakiness_report(&history);
+    let flaky: Vec<_> = report.iter().filter(|t| t.is_flaky()).collect();
+    if flaky.is_empty() {
+        println!("No flaky test in {} runs", history.len());
+        return;
+    }
+    println!(
+        "{:>7} {:>7} {:>5} {:>6}  name",
+        "flips", "fails", "runs", "reruns"
+    );
+    for t in flaky {
+        print!(
+            "{:>6.1}% {:>6.1}% {:>5} {:>6}  {}",
+            t.flip_rate * 100.,
+            t.failure_rate * 100.,
+            t.runs,
+            t.rerun_runs,
+            t.name
+        );
+        match t.last_failure {
+            Some(i) => println!(" (last failed in {})", filenames[i]),
+            None => println!(),
+        }
+    }
+}
+
+/// Print the slowest tests and the duration percentiles of a report, and the
+/// tests slower than in a baseline report
+fn durations(filename: &str, baseline: Option<&str>) {
+    use junit_parser::durations;
+
+    let report = parse(filename);
+
+    println!("Slowest test cases:");
+    println!("{:>10}  name", "time");
+    for c in durations::slowest_cases(&report, 10) {
+        println!("{:>9.3}s  {}", c.time, c.name);
+    }
+
+    println!();
+    println!("Slowest test suites:");
+    println!("{:>10}  name", "time");
+    for s in durations::slowest_suites(&report, 10) {
+        println!("{:>9.3}s  {}", s.time, s.name);
+    }
+
+    println!();
+    println!("Durations per test suite:");
+    println!(
+        "{:>6} {:>10} {:>10} {:>10} {:>10} {:>10}  suite",
+        "cases", "p50", "p90", "p95", "p99", "max"
+    );
+    for p in durations::suite_percentiles(&report) {
+        println!(
+            "{:>6} {:>9.3}s {:>9.3}s {:>9.3}s {:>9.3}s {:>9.3}s  {}",
+            p.cases, p.p50, p.p90, p.p95, p.p99, p.max, p.suite
+        );
+    }
+
+    if let Some(baseline) = baseline {
+        let thresholds = durations::RegressionThresholds::default();
+        let regressions = durations::regressions(&parse(baseline), &report, &thresholds);
+        println!();
+        println!(
+            "Test cases slower than in '{}' by more than {}% and {}s:",
+            baseline,
+            thresholds.relative * 100.,
+            thresholds.absolute
+        );
+        println!("{:>10} {:>10} {:>8}  name", "baseline", "current", "change");
+        for r in regressions {
+            println!(
+                "{:>9.3}s {:>9.3}s {:>7.0}%  {}",
+                r.baseline,
+                r.current,
+                r.relative_increase() * 100.,
+                r.name
+            );
+        }
+    }
+}
+
+/// Print GitHub Actions annotations for the failures of reports
+///
+/// The `GITHUB_WORKSPACE` prefix is stripped from paths, along with the ones
+/// given with `--strip-prefix`.
+fn annotate(program: &str, args: &[String]) {
+    use junit_parser::github::{annotations, AnnotationOptions};
+
+    let mut options = AnnotationOptions::default();
+    if let Ok(workspace) = env::var("GITHUB_WORKSPACE") {
+        options.rewrites.push((
+            format!("{}/", workspace.trim_end_matches('/')),
+            String::new(),
+        ));
+    }
+    let mut filenames = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--strip-prefix" => match args.next() {
+                Some(prefix) => options.rewrites.push((prefix.clone(), String::new())),
+                None => usage(program),
+            },
+            "--max" => match args.next().and_then(|n| n.parse().ok()) {
+                Some(max) => options.max_annotations = Some(max),
+                None => usage(program),
+            },
+            _ => filenames.push(arg.as_str()),
+        }
+    }
+    if filenames.is_empty() {
+        usage(program);
+    }
+
+    let mut report = junit_parser::TestSuites::default();
+    for filename in filenames {
+        report.suites.extend(parse(filename).suites);
+    }
+    for annotation in annotations(&report, &options) {
+        println!("{}", annotation);
+    }
+}
+
+/// Print a report as Markdown, compared to a baseline report
+fn markdown(filename: &str, baseline: Option<&str>) {
+    use junit_parser::markdown::{render, MarkdownOptions};
+
+    let report = parse(filename);
+    let baseline = baseline.map(parse);
+    print!(
+        "{}",
+        render(&report, baseline.as_ref(), &MarkdownOptions::default())
+    );
+}
+
+/// Print a report as TAP
+fn tap(filename: &str) {
+    use junit_parser::tap::{render, TapOptions};
+
+    print!("{}", render(&parse(filename), &TapOptions::default()));
+}
+
+/// Print reports as TeamCity service messages
+fn teamcity(program: &str, args: &[String]) {
+    use junit_parser::teamcity::{messages, TeamCityOptions};
+
+    let mut options = TeamCityOptions::default();
+    let mut filenames = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--flow-id" => match args.next() {
+                Some(id) => options.flow_id = Some(id.clone()),
+                None => usage(program),
+            },
+            _ => filenames.push(arg.as_str()),
+        }
+    }
+    if filenames.is_empty() {
+        usage(program);
+    }
+
+    for filename in filenames {
+        for message in messages(&parse(filename), &options) {
+            println!("{}", message);
+        }
+    }
+}
+
+/// Print the failures of reports as Azure Pipelines logging commands,
+/// followed by the result of the task
+///
+/// With `--publish`, the reports are also published to the test tab of the
+/// run.
+fn azure(program: &str, args: &[String]) {
+    use junit_parser::azure::{complete, issues, publish};
+
+    let publish_files = args.iter().any(|a| a == "--publish");
+    let complete_task = args.iter().any(|a| a == "--complete-task");
+    let filenames: Vec<&str> = args
+        .iter()
+        .map(String::as_str)
+        .filter(|a| *a != "--publish" && *a != "--complete-task")
+        .collect();
+    if filenames.is_empty() {
+        usage(program);
+    }
+
+    let mut report = junit_parser::TestSuites::default();
+    for filename in filenames.iter() {
+        report.suites.extend(parse(filename).suites);
+    }
+    for issue in issues(&report) {
+        println!("{}", issue);
+    }
+    if publish_files {
+        println!("{}", publish(&filenames, None));
+    }
+    if complete_task {
+        println!("{}", complete(&report));
+    }
+}
+
+/// Print the metrics of reports in the Prometheus text format
+fn prometheus(program: &str, args: &[String]) {
+    use junit_parser::prometheus::{render, Format, PrometheusOptions};
+
+    let mut options = PrometheusOptions::default();
+    let mut filenames = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--openmetrics" => options.format = Format::OpenMetrics,
+            "--case-durations" => options.case_durations = true,
+            "--label" => match args.next().and_then(|l| l.split_once('=')) {
+                Some((name, value)) => options.labels.push((name.to_string(), value.to_string())),
+                None => usage(program),
+            },
+            _ => filenames.push(arg.as_str()),
+        }
+    }
+    if filenames.is_empty() {
+        usage(program);
+    }
+
+    let mut report = junit_parser::TestSuites::default();
+    for filename in filenames {
+        report.suites.extend(parse(filename).suites);
+    }
+    print!("{}", render(&report, &options));
+}
+
+/// Print reports merged as JUnit XML, normalized for a CI system
+fn write(program: &str, args: &[String]) {
+    use junit_parser::writer::{to_writer, Profile, WriteOptions};
+    use std::io::{self, BufWriter};
+
+    let mut options = WriteOptions::default();
+    let mut filenames = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--profile" => match args.next().map(String::as_str) {
+                Some("gitlab") => options.profile = Profile::GitLab,
+                Some("jenkins") => options.profile = Profile::Jenkins,
+                _ => usage(program),
+            },
+            _ => filenames.push(arg.as_str()),
+        }
+    }
+    if filenames.is_empty() {
+        usage(program);
+    }
+
+    let mut report = junit_parser::TestSuites::default();
+    for filename in filenames {
+        let parsed = parse(filename);
+        report.tests += parsed.tests;
+        report.failures += parsed.failures;
+        report.errors += parsed.errors;
+        report.skipped += parsed.skipped;
+        report.time += parsed.time;
+        report.suites.extend(parsed.suites);
+    }
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let res = to_writer(&report, &mut out, &options).and_then(|_| {
+        writeln!(out)?;
+        out.flush().map_err(Into::into)
+    });
+    if let Err(err) = res {
+        eprintln!("Error writing report: {}", err);
+        process::exit(1);
+    }
+}
+
+/// Evaluate the rules of a quality gate against a report, exiting with 2 if
+/// they are not respected
+#[cfg(feature = "gate")]
+fn gate(rules: &str, filename: &str, baseline: Option<&str>) {
+    let rules = match junit_parser::gate::Rules::from_path(rules) {
+        Ok(rules) => rules,
+        Err(err) => {
+            eprintln!("Error reading rules '{}': {}", rules, err);
+            process::exit(1);
+        }
+    };
+    let report = parse(filename);
+    let baseline = baseline.map(parse);
+
+    let verdict = rules.evaluate(&report, baseline.as_ref());
+    for name in verdict.quarantined.iter() {
+        println!("Ignored quarantined failure: {}", name);
+    }
+    for v in verdict.violations.iter() {
+        println!("FAILED {:?}: {}", v.rule, v.reason);
+        for name in v.tests.iter() {
+            println!("    {}", name);
+        }
+    }
+    if verdict.passed {
+        println!("Quality gate passed");
+    } else {
+        println!("Quality gate failed");
+        process::exit(2);
+    }
+}
+
+/// Print the test cases of a report selected by a filter expression
+#[cfg(feature = "query")]
+fn query(expression: &str, filename: &str) {
+    let filter: junit_parser::query::Filter = match expression.parse() {
+        Ok(filter) => filter,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    };
+    let report = parse(filename);
+
+    let selected = junit_parser::query::select(&report, &filter);
+    for (ts, tc) in selected.iter() {
+        println!(
+            "{:<8} {:>9.3}s  {}  {}",
+            tc.status.as_str(),
+            tc.time,
+            ts.name,
+            tc.name
+        );
+    }
+    println!("{} test cases selected", selected.len());
+}
+
+/// Print a report as a self-contained HTML page
+#[cfg(feature = "html")]
+fn html(filename: &str) {
+    use junit_parser::html::{render, HtmlOptions};
+
+    print!("{}", render(&parse(filename), &HtmlOptions::default()));
+}
+
+/// Print the failures of reports as a SARIF log
+#[cfg(feature = "sarif")]
+fn sarif(filenames: &[String]) {
+    use junit_parser::sarif::{to_json, SarifOptions};
+
+    let mut report = junit_parser::TestSuites::default();
+    for filename in filenames {
+        report.suites.extend(parse(filename).suites);
+    }
+    match to_json(&report, &SarifOptions::default()) {
+        Ok(json) => println!("{}", json),
+        Err(err) => {
+            eprintln!("Error writing SARIF: {}", err);
+            process::exit(1);
+        }
+    }
+}
+
+/// Write a report as OpenTelemetry traces to the file `output`
+#[cfg(feature = "otlp")]
+fn otlp(filename: &str, output: &str) {
+    use junit_parser::otlp::{to_json, OtlpOptions};
+
+    let res = to_json(&parse(filename), &OtlpOptions::default())
+        .and_then(|json| std::fs::write(output, json).map_err(Into::into));
+    if let Err(err) = res {
+        eprintln!("Error writing traces to '{}': {}", output, err);
+        process::exit(1);
+    }
+}
+
+/// Print a row per test case of reports, in CSV or JSON Lines
+///
+/// Reports are read one after the other, so that only one is in memory.
+#[cfg(feature = "export")]
+fn export(program: &str, args: &[String]) {
+    use junit_parser::export::{ExportOptions, Exporter, Format};
+    use std::io::{self, BufWriter};
+
+    let mut options = ExportOptions::default();
+    let mut filenames = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--jsonl" => options.format = Format::JsonLines,
+            "--property" => match args.next() {
+                Some(name) => options.properties.push(name.clone()),
+                None => usage(program),
+            },
+            _ => filenames.push(arg.as_str()),
+        }
+    }
+    if filenames.is_empty() {
+        usage(program);
+    }
+
+    let stdout = io::stdout();
+    let res = Exporter::new(BufWriter::new(stdout.lock()), options).and_then(|mut exporter| {
+        for filename in filenames {
+            exporter.write_suites(&parse(filename))?;
+        }
+        exporter.into_inner().map(|_| ())
+    });
+    if let Err(err) = res {
+        eprintln!("Error exporting test cases: {}", err);
+        process::exit(1);
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    match args.get(1).map(String::as_str) {
+        Some("flaky") if args.len() > 2 => flaky(&args[2..]),
+        Some("durations") if args.len() == 3 || args.len() == 4 => {
+            durations(&args[2], args.get(3).map(String::as_str))
+        }
+        Some("annotate") if args.len() > 2 => annotate(&args[0], &args[2..]),
+        Some("markdown") if args.len() == 3 || args.len() == 4 => {
+            markdown(&args[2], args.get(3).map(String::as_str))
+        }
+        Some("tap") if args.len() == 3 => tap(&args[2]),
+        Some("teamcity") if args.len() > 2 => teamcity(&args[0], &args[2..]),
+        Some("azure") if args.len() > 2 => azure(&args[0], &args[2..]),
+        Some("prometheus") if args.len() > 2 => prometheus(&args[0], &args[2..]),
+        Some("write") if args.len() > 2 => write(&args[0], &args[2..]),
+        #[cfg(feature = "gate")]
+        Some("gate") if args.len() == 4 || args.len() == 5 => {
+            gate(&args[2], &args[3], args.get(4).map(String::as_str))
+        }
+        #[cfg(feature = "query")]
+        Some("query") if args.len() == 4 => query(&args[2], &args[3]),
+        #[cfg(feature = "html")]
+        Some("html") if args.len() == 3 => html(&args[2]),
+        #[cfg(feature = "sarif")]
+        Some("sarif") if args.len() > 2 => sarif(&args[2..]),
+        #[cfg(feature = "otlp")]
+        Some("otlp") if args.len() == 4 => otlp(&args[2], &args[3]),
+        #[cfg(feature = "export")]
+        Some("export") if args.len() > 2 => export(&args[0], &args[2..]),
+        Some(filename) if args.len() == 2 => dump(filename),
+        _ => usage(&args[0]),
+    }
+}
@@ -0,0 +1,308 @@
+//! Group the failures and errors of a report by their root cause
+//!
+//! Test cases failing for the same reason share a [`Signature`], made of the
+//! type of the failure, of its message normalized by [`normalize_message`],
+//! and of the top frames of its stack trace. Line numbers are not part of the
+//! signature.
+//!
+//! # Example
+//!
+//! ```
+//! use junit_parser::cluster::{cluster_failures, ClusterOptions};
+//! use std::io::Cursor;
+//!
+//! let xml = r#"
+//! <testsuite>
+//!   <testcase name="a"><error type="IOError" message="connection to 10.0.0.1:5432 refused"/></testcase>
+//!   <testcase name="b"><error type="IOError" message="connection to 10.0.0.2:5432 refused"/></testcase>
+//!   <testcase name="c"><failure type="AssertionError" message="expected 1"/></testcase>
+//! </testsuite>
+//! "#;
+//! let report = junit_parser::from_reader(Cursor::new(xml)).unwrap();
+//! let clusters = cluster_failures(&report, &ClusterOptions::default());
+//! assert_eq!(clusters.len(), 2);
+//! assert_eq!(clusters[0].count, 2);
+//! assert_eq!(clusters[0].signature.message, "connection to <n>.<n>.<n>.<n>:<n> refused");
+//! ```
+
+use crate::stacktrace::{Frame, StackTrace};
+use crate::{TestCase, TestStatus, TestSuite, TestSuites};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+/// Options controlling how failures are grouped, see [`cluster_failures`]
+pub struct ClusterOptions {
+    /// Number of frames of the stack trace of the root cause included in the
+    /// signature
+    pub max_frames: usize,
+    /// Maximum number of examples kept for each cluster
+    pub max_examples: usize,
+}
+
+impl Default for ClusterOptions {
+    fn default() -> Self {
+        Self {
+            max_frames: 3,
+            max_examples: 5,
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Whether a test case failed or ended in error
+pub enum FailureKind {
+    /// The test case has a `<failure />`
+    Failure,
+    /// The test case has an `<error />`
+    Error,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// What test cases of a [`Cluster`] have in common
+pub struct Signature {
+    /// Whether the test cases failed or ended in error
+    pub kind: FailureKind,
+    /// Type of the failure or error
+    pub failure_type: String,
+    /// Message of the failure or error, see [`normalize_message`]
+    pub message: String,
+    /// Top frames of the stack trace of the root cause, without their line
+    /// and column
+    pub frames: Vec<Frame>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A test case of a [`Cluster`]
+pub struct Example {
+    /// Name of the test suite of the test case
+    pub suite: String,
+    /// Name of the test case, see [`TestCase::name`]
+    pub name: String,
+    /// Message of the failure or error, before normalization
+    pub message: String,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Failures and errors sharing the same [`Signature`]
+pub struct Cluster {
+    /// Signature of the cluster
+    pub signature: Signature,
+    /// Number of test cases in the cluster
+    pub count: usize,
+    /// The first test cases of the cluster, up to
+    /// [`ClusterOptions::max_examples`]
+    pub examples: Vec<Example>,
+}
+
+/// Group the failures and errors of `suites` by [`Signature`]
+///
+/// Clusters are sorted from the largest to the smallest one, clusters of the
+/// same size are kept in the order they appear in the report.
+pub fn cluster_failures(suites: &TestSuites, options: &ClusterOptions) -> Vec<Cluster> {
+    let mut clusters: Vec<Cluster> = Vec::new();
+    let mut index: HashMap<Signature, usize> = HashMap::new();
+    let mut add = |suite: &TestSuite, case: &TestCase| {
+        let (signature, message) = match signature(case, options) {
+            Some(s) => s,
+            None => return,
+        };
+        let i = *index.entry(signature.clone()).or_insert_with(|| {
+            clusters.push(Cluster {
+                signature,
+                count: 0,
+                examples: Vec::new(),
+            });
+            clusters.len() - 1
+        });
+        let cluster = &mut clusters[i];
+        cluster.count += 1;
+        if cluster.examples.len() < options.max_examples {
+            cluster.examples.push(Example {
+                suite: suite.name.clone(),
+                name: case.name.clone(),
+                message,
+            });
+        }
+    };
+    for suite in suites.suites.iter() {
+        suite.visit_cases(&mut add);
+    }
+    clusters.sort_by_key(|c| std::cmp::Reverse(c.count));
+    clusters
+}
+
+/// Signature of a failed test case, along with its message
+fn signature(case: &TestCase, options: &ClusterOptions) -> Option<(Signature, String)> {
+    let (kind, failure_type, message, text) = match &case.status {
+        TestStatus::Failure(f) => (FailureKind::Failure, &f.failure_type, &f.message, &f.text),
+        TestStatus::Error(e) => (FailureKind::Error, &e.error_type, &e.message, &e.text),
+        _ => return None,
+    };
+    let trace = StackTrace::parse(text);
+    let message = if !message.is_empty() {
+        message.clone()
+    } else if let Some(m) = trace.as_ref().and_then(|t| t.message.clone()) {
+        m
+    } else {
+        text.lines()
+            .map(str::trim)
+            .find(|l| !l.is_empty())
+            .unwrap_or_default()
+            .to_string()
+    };
+    let frames = trace
+        .as_ref()
+        .map(|t| {
+            t.root_cause()
+                .frames
+                .iter()
+                .take(options.max_frames)
+                .map(|f| Frame {
+                    line: None,
+                    column: None,
+                    ..f.clone()
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let signature = Signature {
+        kind,
+        failure_type: failure_type.clone(),
+        message: normalize_message(&message),
+        frames,
+    };
+    Some((signature, message))
+}
+
+/// Characters delimiting the words of a message
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || "\"'`()<>[]{},;=".contains(c)
+}
+
+/// Mask the parts of a message which vary from a run to another
+///
+/// - paths are replaced by `<path>`
+/// - UUIDs are replaced by `<uuid>`
+/// - hexadecimal numbers, such as addresses or hashes, are replaced by
+///   `<hex>`
+/// - numbers are replaced by `<n>`
+///
+/// ```
+/// use junit_parser::cluster::normalize_message;
+///
+/// assert_eq!(
+///     normalize_message("cannot open /tmp/run-42/db.sqlite: error 13 at 0x7ffd5e8c"),
+///     "cannot open <path>: error <n> at <hex>"
+/// );
+/// ```
+pub fn normalize_message(message: &str) -> String {
+    let mut res = String::with_capacity(message.len());
+    let mut word = String::new();
+    for c in message.chars() {
+        if is_delimiter(c) {
+            normalize_word(&word, &mut res);
+            word.clear();
+            res.push(c);
+        } else {
+            word.push(c);
+        }
+    }
+    normalize_word(&word, &mut res);
+    res
+}
+
+/// Append the normalized `word` to `res`
+fn normalize_word(word: &str, res: &mut String) {
+    if word.is_empty() {
+        return;
+    }
+    if is_path(word) {
+        // Keep the punctuation ending a sentence
+        let path = word.trim_end_matches([':', '.', '!', '?']);
+        res.push_str("<path>");
+        res.push_str(&word[path.len()..]);
+    } else if is_uuid(word) {
+        res.push_str("<uuid>");
+    } else {
+        normalize_alphanumeric(word, res);
+    }
+}
+
+/// Whether `word` looks like a path or an URL
+fn is_path(word: &str) -> bool {
+    let separators = word.matches(['/', '\\']).count();
+    let bytes = word.as_bytes();
+    let drive = bytes.len() > 2 && bytes[0].is_ascii_alphabetic() && &bytes[1..3] == b":\\";
+    separators > 0
+        && (word.starts_with(['/', '\\', '~', '.'])
+            || drive
+            || word.contains("://")
+            || separators > 1)
+}
+
+/// Whether `word` is a UUID, such as `123e4567-e89b-12d3-a456-426614174000`
+fn is_uuid(word: &str) -> bool {
+    word.len() == 36
+        && word.char_indices().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        })
+}
+
+/// Append `word` to `res`, masking its numbers
+fn normalize_alphanumeric(word: &str, res: &mut String) {
+    let mut rest = word;
+    while !rest.is_empty() {
+        let end = rest
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(rest.len());
+        let (token, tail) = rest.split_at(end);
+        normalize_token(token, res);
+        let sep_end = tail
+            .find(|c: char| c.is_ascii_alphanumeric())
+            .unwrap_or(tail.len());
+        res.push_str(&tail[..sep_end]);
+        rest = &tail[sep_end..];
+    }
+}
+
+/// Append the alphanumeric `token` to `res`, masking its numbers
+fn normalize_token(token: &str, res: &mut String) {
+    let is_hex = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_hexdigit());
+    let has_digit = token.bytes().any(|b| b.is_ascii_digit());
+    if token.is_empty() {
+        return;
+    }
+    if token.bytes().all(|b| b.is_ascii_digit()) {
+        res.push_str("<n>");
+    } else if let Some(hex) = token
+        .strip_prefix("0x")
+        .or_else(|| token.strip_prefix("0X"))
+    {
+        if is_hex(hex) {
+            res.push_str("<hex>");
+        } else {
+            res.push_str(token);
+        }
+    } else if token.len() >= 8 && has_digit && is_hex(token) {
+        res.push_str("<hex>");
+    } else {
+        // Mask the numbers within identifiers, as in `worker-3` or `tmp42`
+        let mut in_number = false;
+        for c in token.chars() {
+            if c.is_ascii_digit() {
+                if !in_number {
+                    res.push_str("<n>");
+                }
+                in_number = true;
+            } else {
+                res.push(c);
+                in_number = false;
+            }
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use crate::Error;
+use std::io::{self, BufRead, BufReader, Chain, Cursor, Read};
+
+/// Magic bytes starting a gzip stream
+#[cfg(feature = "gzip")]
+const GZIP_MAGIC: &[u8] = &[0x1F, 0x8B];
+/// Magic bytes starting a zstd frame
+#[cfg(feature = "zstd")]
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xB5, 0x2F, 0xFD];
+/// Magic bytes starting a xz stream
+#[cfg(feature = "xz")]
+const XZ_MAGIC: &[u8] = &[0xFD, b'7', b'z', b'X', b'Z', 0x00];
+
+/// Length of the longest magic bytes
+const MAGIC_LEN: usize = 6;
+
+/// Reader over `B` whose first bytes were read to detect its format
+pub(crate) type Peeked<B> = Chain<Cursor<Vec<u8>>, B>;
+
+/// Reader over a report, decompressing it if needed
+pub(crate) enum MaybeCompressed<B: BufRead> {
+    /// Uncompressed report
+    Plain(B),
+    /// gzip-compressed report
+    #[cfg(feature = "gzip")]
+    Gzip(BufReader<flate2::bufread::MultiGzDecoder<B>>),
+    /// zstd-compressed report
+    #[cfg(feature = "zstd")]
+    Zstd(BufReader<zstd::stream::read::Decoder<'static, B>>),
+    /// xz-compressed report
+    #[cfg(feature = "xz")]
+    Xz(BufReader<xz2::bufread::XzDecoder<B>>),
+}
+
+impl<B: BufRead> Read for MaybeCompressed<B> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            MaybeCompressed::Plain(r) => r.read(buf),
+            #[cfg(feature = "gzip")]
+            MaybeCompressed::Gzip(r) => r.read(buf),
+            #[cfg(feature = "zstd")]
+            MaybeCompressed::Zstd(r) => r.read(buf),
+            #[cfg(feature = "xz")]
+            MaybeCompressed::Xz(r) => r.read(buf),
+        }
+    }
+}
+
+impl<B: BufRead> BufRead for MaybeCompressed<B> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        match self {
+            MaybeCompressed::Plain(r) => r.fill_buf(),
+            #[cfg(feature = "gzip")]
+            MaybeCompressed::Gzip(r) => r.fill_buf(),
+            #[cfg(feature = "zstd")]
+            MaybeCompressed::Zstd(r) => r.fill_buf(),
+            #[cfg(feature = "xz")]
+            MaybeCompressed::Xz(r) => r.fill_buf(),
+        }
+    }
+
+    fn consume(&mut self, amt: usize) {
+        match self {
+            MaybeCompressed::Plain(r) => r.consume(amt),
+            #[cfg(feature = "gzip")]
+            MaybeCompressed::Gzip(r) => r.consume(amt),
+            #[cfg(feature = "zstd")]
+            MaybeCompressed::Zstd(r) => r.consume(amt),
+            #[cfg(feature = "xz")]
+            MaybeCompressed::Xz(r) => r.consume(amt),
+        }
+    }
+}
+
+/// Compression format of a report
+enum Format {
+    /// Not compressed, or unknown compression
+    Plain,
+    /// gzip
+    #[cfg(feature = "gzip")]
+    Gzip,
+    /// zstd
+    #[cfg(feature = "zstd")]
+    Zstd,
+    /// xz
+    #[cfg(feature = "xz")]
+    Xz,
+}
+
+/// Detect the compression format from the magic bytes at the start of `head`
+fn detect_format(head: &[u8]) -> Format {
+    #[cfg(feature = "gzip")]
+    if head.starts_with(GZIP_MAGIC) {
+        return Format::Gzip;
+    }
+    #[cfg(feature = "zstd")]
+    if head.starts_with(ZSTD_MAGIC) {
+        return Format::Zstd;
+    }
+    #[cfg(feature = "xz")]
+    if head.starts_with(XZ_MAGIC) {
+        return Format::Xz;
+    }
+    Format::Plain
+}
+
+/// Detect the compression format of `reader`, returns it with the bytes
+/// consumed to do so
+///
+/// A single call to `fill_buf()` may return fewer than [`MAGIC_LEN`] bytes,
+/// for example when reading from a pipe, they are then consumed until there
+/// are enough of them or the end is reached. Otherwise nothing is consumed, so
+/// that the next `fill_buf()` still returns the whole buffer.
+fn detect<B: BufRead>(reader: &mut B) -> io::Result<(Format, Vec<u8>)> {
+    let buf = reader.fill_buf()?;
+    if buf.len() >= MAGIC_LEN {
+        return Ok((detect_format(buf), Vec::new()));
+    }
+    let mut head = Vec::with_capacity(MAGIC_LEN);
+    while head.len() < MAGIC_LEN {
+        let buf = match reader.fill_buf() {
+            Ok(buf) => buf,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        if buf.is_empty() {
+            break;
+        }
+        let n = buf.len().min(MAGIC_LEN - head.len());
+        head.extend_from_slice(&buf[..n]);
+        reader.consume(n);
+    }
+    Ok((detect_format(&head), head))
+}
+
+/// Wrap `reader` with the decoder matching its magic bytes, if any
+pub(crate) fn decompressing_reader<B: BufRead>(
+    mut reader: B,
+) -> Result<MaybeCompressed<Peeked<B>>, Error> {
+    let (format, head) = detect(&mut reader)?;
+    let reader = Cursor::new(head).chain(reader);
+    let r = match format {
+        Format::Plain => MaybeCompressed::Plain(reader),
+        #[cfg(feature = "gzip")]
+        Format::Gzip => {
+            MaybeCompressed::Gzip(BufReader::new(flate2::bufread::MultiGzDecoder::new(reader)))
+        }
+        #[cfg(feature = "zstd")]
+        Format::Zstd => MaybeCompressed::Zstd(BufReader::new(
+            zstd::stream::read::Decoder::with_buffer(reader)?,
+        )),
+        #[cfg(feature = "xz")]
+        Format::Xz => MaybeCompressed::Xz(BufReader::new(
+            xz2::bufread::XzDecoder::new_multi_decoder(reader),
+        )),
+    };
+    Ok(r)
+}
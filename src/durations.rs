@@ -0,0 +1,241 @@
+//! Analyze the durations of test cases and test suites
+//!
+//! # Example
+//!
+//! ```
+//! use junit_parser::durations::{regressions, slowest_cases, RegressionThresholds};
+//! use std::io::Cursor;
+//!
+//! let baseline = r#"<testsuite name="s"><testcase name="a" time="1"/><testcase name="b" time="2"/></testsuite>"#;
+//! let current = r#"<testsuite name="s"><testcase name="a" time="3"/><testcase name="b" time="2"/></testsuite>"#;
+//! let baseline = junit_parser::from_reader(Cursor::new(baseline)).unwrap();
+//! let current = junit_parser::from_reader(Cursor::new(current)).unwrap();
+//!
+//! let slowest = slowest_cases(&current, 1);
+//! assert_eq!(slowest[0].name, "a");
+//! let regressions = regressions(&baseline, &current, &RegressionThresholds::default());
+//! assert_eq!(regressions.len(), 1);
+//! assert_eq!(regressions[0].name, "a");
+//! assert_eq!(regressions[0].increase(), 2.);
+//! ```
+
+use crate::{TestSuite, TestSuites};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Duration of a test case, see [`slowest_cases`]
+pub struct CaseDuration {
+    /// Name of the test case, see [`TestCase::name`](crate::TestCase::name)
+    pub name: String,
+    /// Name of the test suite of the test case
+    pub suite: String,
+    /// How long the test case took to run
+    pub time: f64,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Duration of a test suite, see [`slowest_suites`]
+pub struct SuiteDuration {
+    /// Name of the test suite
+    pub name: String,
+    /// How long the test suite took to run
+    pub time: f64,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Percentiles of the durations of the test cases of a test suite, see
+/// [`suite_percentiles`]
+pub struct SuitePercentiles {
+    /// Name of the test suite
+    pub suite: String,
+    /// Number of test cases in the test suite
+    pub cases: usize,
+    /// Median duration
+    pub p50: f64,
+    /// 90th percentile of the durations
+    pub p90: f64,
+    /// 95th percentile of the durations
+    pub p95: f64,
+    /// 99th percentile of the durations
+    pub p99: f64,
+    /// Longest duration
+    pub max: f64,
+}
+
+#[derive(Debug, Clone)]
+/// Thresholds a test case must exceed to be reported by [`regressions`]
+pub struct RegressionThresholds {
+    /// Minimum increase, relative to the baseline duration: `0.2` reports
+    /// test cases more than 20% slower
+    pub relative: f64,
+    /// Minimum increase, in seconds
+    pub absolute: f64,
+}
+
+impl Default for RegressionThresholds {
+    fn default() -> Self {
+        Self {
+            relative: 0.2,
+            absolute: 0.1,
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Default)]
+/// A test case slower than in the baseline report, see [`regressions`]
+pub struct Regression {
+    /// Name of the test case, see [`TestCase::name`](crate::TestCase::name)
+    pub name: String,
+    /// Duration in the baseline report
+    pub baseline: f64,
+    /// Duration in the current report
+    pub current: f64,
+}
+
+impl Regression {
+    /// Increase of the duration, in seconds
+    pub fn increase(&self) -> f64 {
+        self.current - self.baseline
+    }
+
+    /// Increase of the duration, relative to the baseline duration
+    pub fn relative_increase(&self) -> f64 {
+        if self.baseline > 0. {
+            self.increase() / self.baseline
+        } else {
+            f64::INFINITY
+        }
+    }
+}
+
+/// Compare durations from the longest one to the shortest one
+fn longest_first(a: f64, b: f64) -> Ordering {
+    b.partial_cmp(&a).unwrap_or(Ordering::Equal)
+}
+
+/// The `n` slowest test cases of `suites`, from the slowest one
+pub fn slowest_cases(suites: &TestSuites, n: usize) -> Vec<CaseDuration> {
+    let mut cases = Vec::new();
+    for ts in suites.suites.iter() {
+        ts.visit_cases(&mut |ts, tc| {
+            cases.push(CaseDuration {
+                name: tc.name.clone(),
+                suite: ts.name.clone(),
+                time: tc.time,
+            })
+        });
+    }
+    cases.sort_by(|a, b| longest_first(a.time, b.time));
+    cases.truncate(n);
+    cases
+}
+
+/// Call `f` on `ts` and on all its nested test suites
+fn visit_suites<'a, F: FnMut(&'a TestSuite)>(ts: &'a TestSuite, f: &mut F) {
+    f(ts);
+    for nested in ts.suites.iter() {
+        visit_suites(nested, f);
+    }
+}
+
+/// The `n` slowest test suites of `suites`, nested ones included, from the
+/// slowest one
+pub fn slowest_suites(suites: &TestSuites, n: usize) -> Vec<SuiteDuration> {
+    let mut res = Vec::new();
+    for ts in suites.suites.iter() {
+        visit_suites(ts, &mut |ts| {
+            res.push(SuiteDuration {
+                name: ts.name.clone(),
+                time: ts.time,
+            })
+        });
+    }
+    res.sort_by(|a, b| longest_first(a.time, b.time));
+    res.truncate(n);
+    res
+}
+
+/// Percentile `p`, between 0 and 100, of `sorted` durations, with the
+/// nearest-rank method
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.;
+    }
+    let rank = (p / 100. * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Percentiles of the durations of the test cases of each test suite of
+/// `suites`, nested ones included
+///
+/// Only the test cases directly in a test suite are taken into account, test
+/// suites without test cases are skipped.
+pub fn suite_percentiles(suites: &TestSuites) -> Vec<SuitePercentiles> {
+    let mut res = Vec::new();
+    for ts in suites.suites.iter() {
+        visit_suites(ts, &mut |ts| {
+            if ts.cases.is_empty() {
+                return;
+            }
+            let mut times: Vec<f64> = ts.cases.iter().map(|tc| tc.time).collect();
+            times.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+            res.push(SuitePercentiles {
+                suite: ts.name.clone(),
+                cases: times.len(),
+                p50: percentile(&times, 50.),
+                p90: percentile(&times, 90.),
+                p95: percentile(&times, 95.),
+                p99: percentile(&times, 99.),
+                max: times[times.len() - 1],
+            });
+        });
+    }
+    res
+}
+
+/// Duration of each test case of `suites`, summing up the durations of test
+/// cases with the same name
+fn case_durations(suites: &TestSuites) -> HashMap<&str, f64> {
+    let mut res = HashMap::new();
+    for ts in suites.suites.iter() {
+        ts.visit_cases(&mut |_, tc| {
+            *res.entry(tc.name.as_str()).or_insert(0.) += tc.time;
+        });
+    }
+    res
+}
+
+/// Test cases of `current` whose duration grew compared to `baseline` by more
+/// than both `thresholds`
+///
+/// Test cases are matched by name, those missing from the baseline are not
+/// reported. Regressions are sorted from the largest increase.
+pub fn regressions(
+    baseline: &TestSuites,
+    current: &TestSuites,
+    thresholds: &RegressionThresholds,
+) -> Vec<Regression> {
+    let baseline = case_durations(baseline);
+    let mut res: Vec<Regression> = case_durations(current)
+        .into_iter()
+        .filter_map(|(name, time)| {
+            let r = Regression {
+                name: name.to_string(),
+                baseline: *baseline.get(name)?,
+                current: time,
+            };
+            if r.increase() > thresholds.absolute && r.relative_increase() > thresholds.relative {
+                Some(r)
+            } else {
+                None
+            }
+        })
+        .collect();
+    res.sort_by(|a, b| longest_first(a.increase(), b.increase()).then_with(|| a.name.cmp(&b.name)));
+    res
+}
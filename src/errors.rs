@@ -3,6 +3,7 @@ use thiserror::Error;
 
 /// Error enumerates all possible errors returned by this library.
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum Error {
     /// Error while parsing XML
     #[error("Error while parsing XML")]
@@ -10,6 +11,9 @@ pub enum Error {
     /// Error while decoding XML
     #[error("Error while decoding XML")]
     EncodingError(#[from] ::quick_xml::encoding::EncodingError),
+    /// Error while reading the input
+    #[error("Error while reading the input")]
+    IOError(#[from] std::io::Error),
     /// Error while converting f64 attribute
     #[error("Error while converting f64 attribute")]
     ParseFloatError(#[from] std::num::ParseFloatError),
@@ -25,8 +29,73 @@ pub enum Error {
     /// Error while parsing: unexpected end of file
     #[error("Unexpected end of XML while parsing a {0} element")]
     UnexpectedEndOfFile(String),
+    /// Error while parsing: `ParseOptions::max_depth` nested test suites exceeded
+    #[error("Maximum nesting depth of {0} test suites exceeded")]
+    DepthLimitExceeded(usize),
+    /// Error while parsing: `ParseOptions::max_cases` test cases exceeded
+    #[error("Maximum number of {0} test cases exceeded")]
+    CasesLimitExceeded(u64),
+    /// Error while parsing: `ParseOptions::max_total_size` bytes exceeded
+    #[error("Maximum size of {0} bytes exceeded")]
+    SizeLimitExceeded(u64),
+    /// Invalid glob pattern
+    #[cfg(any(
+        feature = "zip",
+        feature = "tar",
+        feature = "gate",
+        feature = "quarantine",
+        feature = "query"
+    ))]
+    #[error("Invalid glob pattern")]
+    PatternError(#[from] glob::PatternError),
+    /// Error while reading a zip archive
+    #[cfg(feature = "zip")]
+    #[error("Error while reading a zip archive")]
+    ZipError(#[from] zip::result::ZipError),
+    /// Error while accessing an SQLite database
+    #[cfg(feature = "sqlite")]
+    #[error("Error while accessing the SQLite database")]
+    SqliteError(#[from] rusqlite::Error),
+    /// SQLite database written with a newer schema than the supported one
+    #[cfg(feature = "sqlite")]
+    #[error("Unsupported version {0} of the database schema")]
+    UnsupportedSchemaVersion(i64),
+    /// Error while serializing or deserializing JSON
+    #[cfg(any(
+        feature = "shard",
+        feature = "gate",
+        feature = "sarif",
+        feature = "otlp",
+        feature = "export"
+    ))]
+    #[error("Error while processing JSON")]
+    JsonError(#[from] serde_json::Error),
+    /// Error while deserializing TOML
+    #[cfg(feature = "gate")]
+    #[error("Error while parsing TOML")]
+    TomlError(#[from] toml::de::Error),
+    /// Error while deserializing YAML
+    #[cfg(feature = "gate")]
+    #[error("Error while parsing YAML")]
+    YamlError(#[from] serde_yaml::Error),
+    /// File whose format can not be guessed from its extension
+    #[cfg(feature = "gate")]
+    #[error("Unsupported format of file {0}")]
+    UnsupportedFormat(String),
+    /// Exported property named like another column
+    #[cfg(feature = "export")]
+    #[error("Duplicate column {0}")]
+    DuplicateColumn(String),
+    /// Invalid regular expression
+    #[cfg(any(feature = "gate", feature = "quarantine", feature = "query"))]
+    #[error("Invalid regular expression")]
+    RegexError(#[from] regex::Error),
+    /// Invalid filter expression
+    #[cfg(feature = "query")]
+    #[error("Invalid query: {0}")]
+    InvalidQuery(String),
     /// Chrono ParseError
-    #[cfg(feature = "chrono")]
+    #[cfg(any(feature = "chrono", feature = "quarantine"))]
     #[error("Error while decoding Date/Time")]
     ChronoParseError(#[from] chrono::format::ParseError),
 }
@@ -0,0 +1,284 @@
+//! Export test cases as flat rows, in CSV or JSON Lines
+//!
+//! Each test case gives a row with the columns:
+//!
+//! - `suite`: names of the test suites containing the test case, from the
+//!   top-level one, separated with `/`
+//! - `classname`, `name`, `status`, `time`, `timestamp`, `file` and `line`
+//!   of the test case, `name` being its original name
+//! - `failure_type` and `failure_message`: type and message of the failure,
+//!   error or skipped tag
+//! - `reruns`: number of rerun and flaky entries
+//! - a column per property of [`ExportOptions::properties`], named after the
+//!   property, whose value comes from the test case or else from the closest
+//!   test suite having it. Properties named like another column are rejected
+//!   with [`Error::DuplicateColumn`]
+//!
+//! Rows are written to the writer as they are produced, which is better
+//! buffered. Several reports can be written one after the other with the
+//! same [`Exporter`], without keeping them in memory.
+//!
+//! # Example
+//!
+//! ```
+//! use junit_parser::export::{export, ExportOptions};
+//! use std::io::Cursor;
+//!
+//! let xml = r#"<testsuite name="cart"><testcase name="total" time="0.5"><failure message="expected 10, got 11"/></testcase></testsuite>"#;
+//! let report = junit_parser::from_reader(Cursor::new(xml)).unwrap();
+//! let mut csv = Vec::new();
+//! export(&report, &mut csv, &ExportOptions::default()).unwrap();
+//! assert_eq!(
+//!     String::from_utf8(csv).unwrap(),
+//!     "suite,classname,name,status,time,timestamp,file,line,failure_type,failure_message,reruns\n\
+//!      cart,,total,failure,0.5,,,,,\"expected 10, got 11\",0\n"
+//! );
+//! ```
+
+use crate::{Error, TestCase, TestStatus, TestSuite, TestSuites};
+use serde_json::{json, Value};
+use std::io::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// Format of the rows
+pub enum Format {
+    /// Comma-separated values with a header line, quoted as in RFC 4180
+    #[default]
+    Csv,
+    /// A JSON object per line
+    JsonLines,
+}
+
+#[derive(Debug, Clone, Default)]
+/// Options of the export
+pub struct ExportOptions {
+    /// Format of the rows
+    pub format: Format,
+    /// Names of the properties exported as columns
+    pub properties: Vec<String>,
+}
+
+/// Columns of every row, before the properties
+const COLUMNS: [&str; 11] = [
+    "suite",
+    "classname",
+    "name",
+    "status",
+    "time",
+    "timestamp",
+    "file",
+    "line",
+    "failure_type",
+    "failure_message",
+    "reruns",
+];
+
+/// Value of a cell
+enum Cell<'a> {
+    /// Missing value, empty in CSV
+    Null,
+    /// Borrowed string
+    Str(&'a str),
+    /// Owned string
+    String(String),
+    /// Number with a fractional part
+    Float(f64),
+    /// Integer
+    Int(u64),
+}
+
+impl Cell<'_> {
+    /// Cell of `s`, null if `s` is empty
+    fn non_empty(s: &str) -> Cell<'_> {
+        if s.is_empty() {
+            Cell::Null
+        } else {
+            Cell::Str(s)
+        }
+    }
+
+    /// Cell as a JSON value
+    fn to_json(&self) -> Value {
+        match self {
+            Cell::Null => Value::Null,
+            Cell::Str(s) => json!(s),
+            Cell::String(s) => json!(s),
+            Cell::Float(f) => json!(f),
+            Cell::Int(i) => json!(i),
+        }
+    }
+}
+
+/// Escape `s` as a CSV field, quoted if it contains a separator, a quote or
+/// a line break
+fn escape_csv(s: &str) -> String {
+    if s.contains(|c| matches!(c, ',' | '"' | '\n' | '\r')) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Timestamp of `tc`, in RFC 3339 format if parsed
+#[cfg(feature = "chrono")]
+fn timestamp(tc: &TestCase) -> Cell<'_> {
+    match &tc.timestamp {
+        Some(t) => Cell::String(t.to_rfc3339()),
+        None => Cell::Null,
+    }
+}
+
+/// Timestamp of `tc`, as in the report
+#[cfg(not(feature = "chrono"))]
+fn timestamp(tc: &TestCase) -> Cell<'_> {
+    match &tc.timestamp {
+        Some(t) => Cell::Str(t),
+        None => Cell::Null,
+    }
+}
+
+/// Writer of rows of test cases
+pub struct Exporter<W: Write> {
+    /// Destination of the rows
+    writer: W,
+    /// Format and property columns of the rows
+    options: ExportOptions,
+}
+
+impl<W: Write> Exporter<W> {
+    /// New exporter to `writer`, writing the header of CSV right away
+    ///
+    /// Fails with [`Error::DuplicateColumn`] if a property is named like
+    /// another column.
+    pub fn new(mut writer: W, options: ExportOptions) -> Result<Self, Error> {
+        for (i, name) in options.properties.iter().enumerate() {
+            if COLUMNS.contains(&name.as_str()) || options.properties[..i].contains(name) {
+                return Err(Error::DuplicateColumn(name.clone()));
+            }
+        }
+        if options.format == Format::Csv {
+            let header: Vec<String> = COLUMNS
+                .iter()
+                .map(|c| c.to_string())
+                .chain(options.properties.iter().map(|p| escape_csv(p)))
+                .collect();
+            writeln!(writer, "{}", header.join(","))?;
+        }
+        Ok(Self { writer, options })
+    }
+
+    /// Write a row per test case of `suites`
+    pub fn write_suites(&mut self, suites: &TestSuites) -> Result<(), Error> {
+        for ts in suites.suites.iter() {
+            self.write_suite(ts)?;
+        }
+        Ok(())
+    }
+
+    /// Write a row per test case of `ts` and its nested test suites
+    pub fn write_suite(&mut self, ts: &TestSuite) -> Result<(), Error> {
+        self.suite(&mut Vec::new(), ts)
+    }
+
+    /// Flush the writer and return it
+    pub fn into_inner(mut self) -> Result<W, Error> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    /// Write the rows of `ts`, nested in `parents`
+    fn suite<'a>(
+        &mut self,
+        parents: &mut Vec<&'a TestSuite>,
+        ts: &'a TestSuite,
+    ) -> Result<(), Error> {
+        parents.push(ts);
+        for tc in ts.cases.iter() {
+            self.case(parents, tc)?;
+        }
+        for nested in ts.suites.iter() {
+            self.suite(parents, nested)?;
+        }
+        parents.pop();
+        Ok(())
+    }
+
+    /// Write the row of `tc`, in the test suites `parents`
+    fn case(&mut self, parents: &[&TestSuite], tc: &TestCase) -> Result<(), Error> {
+        let suite: Vec<&str> = parents.iter().map(|ts| ts.name.as_str()).collect();
+        let status = tc.status.as_str();
+        let (failure_type, message) = match &tc.status {
+            TestStatus::Success => ("", ""),
+            TestStatus::Failure(f) => (f.failure_type.as_str(), f.message.as_str()),
+            TestStatus::Error(e) => (e.error_type.as_str(), e.message.as_str()),
+            TestStatus::Skipped(s) => (s.skipped_type.as_str(), s.message.as_str()),
+        };
+        let mut cells = vec![
+            Cell::String(suite.join("/")),
+            tc.classname.as_deref().map_or(Cell::Null, Cell::Str),
+            Cell::Str(&tc.original_name),
+            Cell::Str(status),
+            Cell::Float(tc.time),
+            timestamp(tc),
+            tc.file.as_deref().map_or(Cell::Null, Cell::Str),
+            tc.line.map_or(Cell::Null, Cell::Int),
+            Cell::non_empty(failure_type),
+            Cell::non_empty(message),
+            Cell::Int(tc.reruns.len() as u64),
+        ];
+        for name in self.options.properties.iter() {
+            let value = tc
+                .properties
+                .get(name)
+                .or_else(|| parents.iter().rev().find_map(|ts| ts.properties.get(name)));
+            cells.push(value.map_or(Cell::Null, Cell::Str));
+        }
+
+        match self.options.format {
+            Format::Csv => {
+                let fields: Vec<String> = cells
+                    .iter()
+                    .map(|cell| match cell {
+                        Cell::Null => String::new(),
+                        Cell::Str(s) => escape_csv(s),
+                        Cell::String(s) => escape_csv(s),
+                        Cell::Float(f) => f.to_string(),
+                        Cell::Int(i) => i.to_string(),
+                    })
+                    .collect();
+                writeln!(self.writer, "{}", fields.join(","))?;
+            }
+            Format::JsonLines => {
+                // Written field by field to keep the order of the columns
+                let columns = COLUMNS
+                    .iter()
+                    .copied()
+                    .chain(self.options.properties.iter().map(String::as_str));
+                let fields = columns
+                    .zip(cells.iter())
+                    .map(|(column, cell)| {
+                        Ok(format!(
+                            "{}:{}",
+                            serde_json::to_string(column)?,
+                            serde_json::to_string(&cell.to_json())?
+                        ))
+                    })
+                    .collect::<Result<Vec<String>, Error>>()?;
+                writeln!(self.writer, "{{{}}}", fields.join(","))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Write a row per test case of `suites` to `writer`
+pub fn export<W: Write>(
+    suites: &TestSuites,
+    writer: W,
+    options: &ExportOptions,
+) -> Result<(), Error> {
+    let mut exporter = Exporter::new(writer, options.clone())?;
+    exporter.write_suites(suites)?;
+    exporter.into_inner()?;
+    Ok(())
+}
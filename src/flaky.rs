@@ -0,0 +1,171 @@
+//! Detect flaky tests across a history of reports
+//!
+//! Given the reports of successive runs of the same pipeline, from the oldest
+//! to the newest one, the outcomes of each test case, identified by its
+//! [`TestCase::name`], are compared from one run to the next one. A test
+//! passing then failing, or failing then passing, flips; a test with
+//! [`RerunOrFlaky`](crate::RerunOrFlaky) entries needed retries to get its
+//! outcome.
+//!
+//! # Example
+//!
+//! ```
+//! use std::io::Cursor;
+//!
+//! let runs = [
+//!     r#"<testsuite><testcase name="a"/><testcase name="b"/></testsuite>"#,
+//!     r#"<testsuite><testcase name="a"><failure/></testcase><testcase name="b"/></testsuite>"#,
+//!     r#"<testsuite><testcase name="a"/><testcase name="b"/></testsuite>"#,
+//! ];
+//! let history: Vec<_> = runs
+//!     .iter()
+//!     .map(|xml| junit_parser::from_reader(Cursor::new(xml)).unwrap())
+//!     .collect();
+//! let report = junit_parser::flaky::flakiness_report(&history);
+//! assert_eq!(report[0].name, "a");
+//! assert_eq!(report[0].flips, 2);
+//! assert_eq!(report[0].last_failure, Some(1));
+//! assert!(report[0].is_flaky());
+//! assert!(!report[1].is_flaky());
+//! ```
+
+use crate::{TestCase, TestStatus, TestSuites};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Outcomes of a test case across a history of reports
+pub struct TestFlakiness {
+    /// Name of the test case, see [`TestCase::name`]
+    pub name: String,
+    /// Number of runs in which the test case was executed, not skipped
+    pub runs: usize,
+    /// Number of runs in which the test case passed
+    pub passes: usize,
+    /// Number of runs in which the test case failed or ended in error
+    pub failures: usize,
+    /// Number of runs in which the test case was skipped
+    pub skipped: usize,
+    /// Number of times the outcome changed from a run to the next one
+    /// executing the test case
+    pub flips: usize,
+    /// Ratio of flips to the number of pairs of consecutive runs executing
+    /// the test case
+    pub flip_rate: f64,
+    /// Ratio of failures to the number of runs executing the test case
+    pub failure_rate: f64,
+    /// Index in the history of the last run in which the test case failed or
+    /// ended in error
+    pub last_failure: Option<usize>,
+    /// Number of runs in which the test case had rerun or flaky entries
+    pub rerun_runs: usize,
+}
+
+impl TestFlakiness {
+    /// Whether the test case flipped or needed reruns
+    pub fn is_flaky(&self) -> bool {
+        self.flips > 0 || self.rerun_runs > 0
+    }
+}
+
+/// Outcome of a test case in a run
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    /// Passed
+    Pass,
+    /// Failed or ended in error
+    Fail,
+    /// Skipped
+    Skip,
+}
+
+/// Outcome of a test case, along with whether it had reruns
+fn outcome(tc: &TestCase) -> (Outcome, bool) {
+    let outcome = match tc.status {
+        TestStatus::Success => Outcome::Pass,
+        TestStatus::Failure(_) | TestStatus::Error(_) => Outcome::Fail,
+        TestStatus::Skipped(_) => Outcome::Skip,
+    };
+    (outcome, !tc.reruns.is_empty())
+}
+
+/// Compute the flakiness of every test case found in `history`, ordered from
+/// the oldest run to the newest one
+///
+/// When a test case appears several times in a run, it fails if any of its
+/// occurrences fails.
+///
+/// The test cases are ranked from the flakiest one: by flip rate, then by
+/// number of runs with reruns, then by failure rate, and finally by name.
+pub fn flakiness_report<'a, I: IntoIterator<Item = &'a TestSuites>>(
+    history: I,
+) -> Vec<TestFlakiness> {
+    let mut tests: BTreeMap<String, (TestFlakiness, Option<Outcome>)> = BTreeMap::new();
+    for (i, suites) in history.into_iter().enumerate() {
+        let mut run: BTreeMap<&str, (Outcome, bool)> = BTreeMap::new();
+        for ts in suites.suites.iter() {
+            ts.visit_cases(&mut |_, tc| {
+                let (o, reruns) = outcome(tc);
+                let e = run.entry(tc.name.as_str()).or_insert((o, reruns));
+                if o == Outcome::Fail || e.0 == Outcome::Skip {
+                    e.0 = o;
+                }
+                e.1 |= reruns;
+            });
+        }
+        for (name, (o, reruns)) in run {
+            let (t, previous) = tests.entry(name.to_string()).or_insert_with(|| {
+                let t = TestFlakiness {
+                    name: name.to_string(),
+                    ..Default::default()
+                };
+                (t, None)
+            });
+            if reruns {
+                t.rerun_runs += 1;
+            }
+            match o {
+                Outcome::Skip => {
+                    t.skipped += 1;
+                    continue;
+                }
+                Outcome::Pass => t.passes += 1,
+                Outcome::Fail => {
+                    t.failures += 1;
+                    t.last_failure = Some(i);
+                }
+            }
+            t.runs += 1;
+            if previous.map_or(false, |p| p != o) {
+                t.flips += 1;
+            }
+            *previous = Some(o);
+        }
+    }
+    let mut report: Vec<TestFlakiness> = tests
+        .into_values()
+        .map(|(mut t, _)| {
+            if t.runs > 1 {
+                t.flip_rate = t.flips as f64 / (t.runs - 1) as f64;
+            }
+            if t.runs > 0 {
+                t.failure_rate = t.failures as f64 / t.runs as f64;
+            }
+            t
+        })
+        .collect();
+    // Rates are never NaN
+    report.sort_by(|a, b| {
+        b.flip_rate
+            .partial_cmp(&a.flip_rate)
+            .unwrap_or(Ordering::Equal)
+            .then(b.rerun_runs.cmp(&a.rerun_runs))
+            .then(
+                b.failure_rate
+                    .partial_cmp(&a.failure_rate)
+                    .unwrap_or(Ordering::Equal),
+            )
+    });
+    report
+}
@@ -0,0 +1,255 @@
+//! Evaluate quality gate rules against a report
+//!
+//! The rules are read from a TOML, YAML or JSON file, for example:
+//!
+//! ```toml
+//! max_failures = 0
+//! min_pass_rate = 0.95
+//! no_new_failures = true
+//! max_duration = 600.0
+//! no_skipped_in = ["com.acme.payments.CheckoutTest"]
+//! quarantine = ["com.acme.LegacyTest::flaky_test", "glob:com.acme.legacy.*"]
+//! ```
+//!
+//! Every rule is optional. Failing test cases matching a [`Pattern`] of
+//! `quarantine` are not taken into account by the rules, they are listed in
+//! the [`Verdict::quarantined`] instead.
+//!
+//! # Example
+//!
+//! ```
+//! use junit_parser::gate::{Rule, Rules};
+//! use std::io::Cursor;
+//!
+//! let rules = Rules::from_toml("max_failures = 0").unwrap();
+//! let xml = r#"<testsuite><testcase name="a"><failure/></testcase></testsuite>"#;
+//! let report = junit_parser::from_reader(Cursor::new(xml)).unwrap();
+//! let verdict = rules.evaluate(&report, None);
+//! assert!(!verdict.passed);
+//! assert_eq!(verdict.violations[0].rule, Rule::MaxFailures);
+//! assert_eq!(verdict.violations[0].tests, vec!["a"]);
+//! ```
+
+use crate::pattern::Pattern;
+use crate::{Error, TestStatus, TestSuites};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default, deny_unknown_fields)]
+/// Quality gate rules
+pub struct Rules {
+    /// Maximum number of test cases failing or in error
+    pub max_failures: Option<u64>,
+    /// Minimum ratio, between 0 and 1, of test cases passing among the test
+    /// cases not skipped
+    pub min_pass_rate: Option<f64>,
+    /// Fail when test cases fail or are in error while they did not in the
+    /// baseline report, ignored without a baseline
+    pub no_new_failures: bool,
+    /// Maximum duration of the report, in seconds
+    pub max_duration: Option<f64>,
+    /// Names of the test suites where no test case may be skipped
+    pub no_skipped_in: Vec<String>,
+    /// Patterns of the names of the test cases whose failures and errors are
+    /// ignored, see [`TestCase::name`](crate::TestCase::name)
+    pub quarantine: Vec<Pattern>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+/// A rule of [`Rules`]
+pub enum Rule {
+    /// [`Rules::max_failures`]
+    MaxFailures,
+    /// [`Rules::min_pass_rate`]
+    MinPassRate,
+    /// [`Rules::no_new_failures`]
+    NoNewFailures,
+    /// [`Rules::max_duration`]
+    MaxDuration,
+    /// [`Rules::no_skipped_in`]
+    NoSkippedIn,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+/// A rule which is not respected
+pub struct Violation {
+    /// The rule
+    pub rule: Rule,
+    /// Why the rule is not respected
+    pub reason: String,
+    /// Names of the test cases not respecting the rule, if relevant
+    pub tests: Vec<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Default)]
+/// Result of the evaluation of [`Rules`] against a report
+pub struct Verdict {
+    /// Whether all the rules are respected
+    pub passed: bool,
+    /// The rules which are not respected
+    pub violations: Vec<Violation>,
+    /// Names of the quarantined test cases which failed or were in error
+    pub quarantined: Vec<String>,
+}
+
+/// Outcome of the test cases of a report
+#[derive(Default)]
+struct Counts {
+    /// Number of test cases passing
+    passed: u64,
+    /// Names of the test cases failing or in error, not quarantined
+    failed: Vec<String>,
+    /// Names of the quarantined test cases failing or in error
+    quarantined: Vec<String>,
+    /// Names of the test cases skipped in suites of `no_skipped_in`
+    skipped_in: Vec<String>,
+}
+
+impl Rules {
+    /// Read the rules from the file at `path`, whose format is guessed from
+    /// its extension: `.toml`, `.yaml`, `.yml` or `.json`
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Rules, Error> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Rules::from_toml(&content),
+            Some("yaml" | "yml") => Rules::from_yaml(&content),
+            Some("json") => Rules::from_json(&content),
+            _ => Err(Error::UnsupportedFormat(path.display().to_string())),
+        }
+    }
+
+    /// Read the rules from TOML
+    pub fn from_toml(content: &str) -> Result<Rules, Error> {
+        Ok(toml::from_str(content)?)
+    }
+
+    /// Read the rules from YAML
+    pub fn from_yaml(content: &str) -> Result<Rules, Error> {
+        Ok(serde_yaml::from_str(content)?)
+    }
+
+    /// Read the rules from JSON
+    pub fn from_json(content: &str) -> Result<Rules, Error> {
+        Ok(serde_json::from_str(content)?)
+    }
+
+    /// Count the outcomes of the test cases of `suites`
+    fn count(&self, suites: &TestSuites) -> Counts {
+        let no_skipped_in: HashSet<&str> = self.no_skipped_in.iter().map(String::as_str).collect();
+        let mut counts = Counts::default();
+        for ts in suites.suites.iter() {
+            ts.visit_cases(&mut |ts, tc| match tc.status {
+                TestStatus::Success => counts.passed += 1,
+                TestStatus::Failure(_) | TestStatus::Error(_) => {
+                    if self.quarantine.iter().any(|p| p.matches(&tc.name)) {
+                        counts.quarantined.push(tc.name.clone());
+                    } else {
+                        counts.failed.push(tc.name.clone());
+                    }
+                }
+                TestStatus::Skipped(_) => {
+                    if no_skipped_in.contains(ts.name.as_str()) {
+                        counts.skipped_in.push(tc.name.clone());
+                    }
+                }
+            });
+        }
+        counts
+    }
+
+    /// Evaluate the rules against `suites`, comparing it to the `baseline`
+    /// report if any
+    pub fn evaluate(&self, suites: &TestSuites, baseline: Option<&TestSuites>) -> Verdict {
+        let counts = self.count(suites);
+        let mut violations = Vec::new();
+
+        let failures = counts.failed.len() as u64;
+        if let Some(max) = self.max_failures {
+            if failures > max {
+                violations.push(Violation {
+                    rule: Rule::MaxFailures,
+                    reason: format!("{} test cases failed, at most {} allowed", failures, max),
+                    tests: counts.failed.clone(),
+                });
+            }
+        }
+
+        if let Some(min) = self.min_pass_rate {
+            // Quarantined test cases are neither passing nor failing
+            let executed = counts.passed + failures;
+            if executed > 0 {
+                let rate = counts.passed as f64 / executed as f64;
+                if rate < min {
+                    violations.push(Violation {
+                        rule: Rule::MinPassRate,
+                        reason: format!(
+                            "pass rate of {:.2}% below {:.2}%",
+                            rate * 100.,
+                            min * 100.
+                        ),
+                        tests: Vec::new(),
+                    });
+                }
+            }
+        }
+
+        if let (true, Some(baseline)) = (self.no_new_failures, baseline) {
+            let before: HashSet<String> = self.count(baseline).failed.into_iter().collect();
+            let new: Vec<String> = counts
+                .failed
+                .iter()
+                .filter(|name| !before.contains(*name))
+                .cloned()
+                .collect();
+            if !new.is_empty() {
+                violations.push(Violation {
+                    rule: Rule::NoNewFailures,
+                    reason: format!("{} test cases failed but not in the baseline", new.len()),
+                    tests: new,
+                });
+            }
+        }
+
+        if let Some(max) = self.max_duration {
+            let duration = duration(suites);
+            if duration > max {
+                violations.push(Violation {
+                    rule: Rule::MaxDuration,
+                    reason: format!("took {:.3}s, at most {:.3}s allowed", duration, max),
+                    tests: Vec::new(),
+                });
+            }
+        }
+
+        if !counts.skipped_in.is_empty() {
+            violations.push(Violation {
+                rule: Rule::NoSkippedIn,
+                reason: format!(
+                    "{} test cases skipped in test suites where skipping is not allowed",
+                    counts.skipped_in.len()
+                ),
+                tests: counts.skipped_in,
+            });
+        }
+
+        Verdict {
+            passed: violations.is_empty(),
+            violations,
+            quarantined: counts.quarantined,
+        }
+    }
+}
+
+/// Duration of a report: its `time` attribute if set, or else the sum of
+/// the times of its test suites
+fn duration(suites: &TestSuites) -> f64 {
+    if suites.time > 0. {
+        suites.time
+    } else {
+        suites.suites.iter().map(|ts| ts.time).sum()
+    }
+}
@@ -0,0 +1,268 @@
+//! Annotate failures with GitHub Actions workflow commands
+//!
+//! Each failing test case, or test case in error, gives an `::error`
+//! annotation and each test case passing after reruns a `::warning` one, so
+//! that they are shown inline on the pull request diff:
+//!
+//! ```text
+//! ::error file=src/cart.py,line=12,title=test_cart::expected 10, got 11
+//! ```
+//!
+//! The location of a test case comes from its `file` and `line` attributes
+//! or, with the `stacktrace` feature, from the stack trace of its failure,
+//! which also gives the message of failures without `message` attribute.
+//! Paths are rewritten with [`AnnotationOptions::rewrites`], for example to
+//! make them relative to the repository.
+//!
+//! # Example
+//!
+//! ```
+//! use junit_parser::github::{annotations, AnnotationOptions};
+//! use std::io::Cursor;
+//!
+//! let xml = r#"
+//! <testsuite>
+//!   <testcase name="test_cart" file="/home/runner/work/shop/shop/src/cart.py" line="12">
+//!     <failure message="expected 10, got 11"/>
+//!   </testcase>
+//! </testsuite>"#;
+//! let report = junit_parser::from_reader(Cursor::new(xml)).unwrap();
+//! let options = AnnotationOptions {
+//!     rewrites: vec![("/home/runner/work/shop/shop/".to_string(), String::new())],
+//!     ..Default::default()
+//! };
+//! let annotations = annotations(&report, &options);
+//! assert_eq!(
+//!     annotations[0].to_string(),
+//!     "::error file=src/cart.py,line=12,title=test_cart::expected 10, got 11"
+//! );
+//! ```
+
+use crate::{TestCase, TestStatus, TestSuites};
+use std::fmt;
+
+#[derive(Debug, Clone)]
+/// Options of [`annotations`]
+pub struct AnnotationOptions {
+    /// Prefixes of paths replaced by other prefixes, the first matching one
+    /// applies
+    ///
+    /// An empty replacement strips the prefix, such as the CI workspace.
+    pub rewrites: Vec<(String, String)>,
+    /// Maximum number of annotations, the last one being a notice telling how
+    /// many test cases were left out when there are more, `Some(0)` to
+    /// annotate nothing
+    ///
+    /// GitHub shows at most 50 annotations per job.
+    pub max_annotations: Option<usize>,
+    /// Whether to annotate test cases passing after reruns with warnings
+    pub flaky_warnings: bool,
+}
+
+impl Default for AnnotationOptions {
+    fn default() -> Self {
+        Self {
+            rewrites: Vec::new(),
+            max_annotations: Some(50),
+            flaky_warnings: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Level of an [`Annotation`]
+pub enum Level {
+    /// `::error`
+    Error,
+    /// `::warning`
+    Warning,
+    /// `::notice`
+    Notice,
+}
+
+impl Level {
+    /// Name of the workflow command
+    fn command(&self) -> &'static str {
+        match self {
+            Level::Error => "error",
+            Level::Warning => "warning",
+            Level::Notice => "notice",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A workflow command annotating a file, formatted with [`fmt::Display`]
+pub struct Annotation {
+    /// Level of the annotation
+    pub level: Level,
+    /// Path of the annotated file
+    pub file: Option<String>,
+    /// Annotated line, starting at 1
+    pub line: Option<u64>,
+    /// Title of the annotation
+    pub title: Option<String>,
+    /// Message of the annotation, possibly on several lines
+    pub message: String,
+}
+
+/// Escape `s` as the message of a workflow command
+fn escape_data(s: &str) -> String {
+    s.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape `s` as the value of a property of a workflow command
+fn escape_property(s: &str) -> String {
+    escape_data(s).replace(':', "%3A").replace(',', "%2C")
+}
+
+impl fmt::Display for Annotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut properties = Vec::new();
+        if let Some(file) = &self.file {
+            properties.push(format!("file={}", escape_property(file)));
+        }
+        if let Some(line) = self.line {
+            properties.push(format!("line={}", line));
+        }
+        if let Some(title) = &self.title {
+            properties.push(format!("title={}", escape_property(title)));
+        }
+        write!(f, "::{}", self.level.command())?;
+        if !properties.is_empty() {
+            write!(f, " {}", properties.join(","))?;
+        }
+        write!(f, "::{}", escape_data(&self.message))
+    }
+}
+
+/// `path` rewritten with the first matching rewrite of `options`
+fn rewrite(path: &str, options: &AnnotationOptions) -> String {
+    for (prefix, replacement) in options.rewrites.iter() {
+        if let Some(rest) = path.strip_prefix(prefix.as_str()) {
+            return format!("{}{}", replacement, rest);
+        }
+    }
+    path.to_string()
+}
+
+/// Location of the failure of `tc`, from the stack trace of its failure
+#[cfg(feature = "stacktrace")]
+fn trace_location(tc: &TestCase) -> Option<(String, u64)> {
+    let trace = match &tc.status {
+        TestStatus::Failure(f) => f.parse_stack_trace()?,
+        TestStatus::Error(e) => e.parse_stack_trace()?,
+        _ => return None,
+    };
+    let file_name = tc
+        .file
+        .as_deref()
+        .map(|f| f.rsplit(|c| c == '/' || c == '\\').next().unwrap_or(f));
+    let frames: Vec<(&str, u64)> = trace
+        .frames
+        .iter()
+        .filter_map(|frame| Some((frame.file.as_deref()?, frame.line?)))
+        .collect();
+    // Prefer the frame in the file of the test case
+    let (file, line) = frames
+        .iter()
+        .find(|(file, _)| file_name.map_or(false, |name| file.ends_with(name)))
+        .or_else(|| frames.first())?;
+    let file = match &tc.file {
+        Some(tc_file) if tc_file.ends_with(file) => tc_file.as_str(),
+        _ => file,
+    };
+    Some((file.to_string(), *line))
+}
+
+/// Location of the failure of `tc`, from the stack trace of its failure
+#[cfg(not(feature = "stacktrace"))]
+fn trace_location(_tc: &TestCase) -> Option<(String, u64)> {
+    None
+}
+
+/// File and line of the test case `tc`, if known
+fn location(tc: &TestCase) -> (Option<String>, Option<u64>) {
+    if let (Some(file), Some(line)) = (&tc.file, tc.line) {
+        return (Some(file.clone()), Some(line));
+    }
+    match trace_location(tc) {
+        Some((file, line)) => (Some(file), Some(line)),
+        None => (tc.file.clone(), tc.line),
+    }
+}
+
+/// `message`, or else the message of the stack trace in `text`, or else
+/// the first line of `text`, or else `default`
+fn message(message: &str, text: &str, default: &str) -> String {
+    if !message.is_empty() {
+        return message.to_string();
+    }
+    #[cfg(feature = "stacktrace")]
+    if let Some(message) = crate::stacktrace::StackTrace::parse(text).and_then(|t| t.message) {
+        return message;
+    }
+    match text.lines().map(str::trim).find(|l| !l.is_empty()) {
+        Some(line) => line.to_string(),
+        None => default.to_string(),
+    }
+}
+
+/// Annotation of the test case `tc`, if it failed or passed after reruns
+fn annotation(tc: &TestCase, options: &AnnotationOptions) -> Option<Annotation> {
+    let (level, message) = match &tc.status {
+        TestStatus::Failure(f) => (Level::Error, message(&f.message, &f.text, "failed")),
+        TestStatus::Error(e) => (Level::Error, message(&e.message, &e.text, "error")),
+        TestStatus::Success if options.flaky_warnings && !tc.reruns.is_empty() => {
+            let rerun = &tc.reruns[0];
+            let reason = message(&rerun.message, &rerun.text, "failed");
+            let message = format!(
+                "passed after {} failed attempts, first one: {}",
+                tc.reruns.len(),
+                reason
+            );
+            (Level::Warning, message)
+        }
+        _ => return None,
+    };
+    let (file, line) = location(tc);
+    Some(Annotation {
+        level,
+        file: file.map(|f| rewrite(&f, options)),
+        line,
+        title: Some(tc.name.clone()),
+        message,
+    })
+}
+
+/// Annotations of the failing test cases, the test cases in error and the
+/// flaky test cases of `suites`
+pub fn annotations(suites: &TestSuites, options: &AnnotationOptions) -> Vec<Annotation> {
+    let mut res = Vec::new();
+    for ts in suites.suites.iter() {
+        ts.visit_cases(&mut |_, tc| {
+            if let Some(a) = annotation(tc, options) {
+                res.push(a);
+            }
+        });
+    }
+    if let Some(max) = options.max_annotations {
+        if max == 0 {
+            res.clear();
+        } else if res.len() > max {
+            let kept = max - 1;
+            let omitted = res.len() - kept;
+            res.truncate(kept);
+            res.push(Annotation {
+                level: Level::Notice,
+                file: None,
+                line: None,
+                title: None,
+                message: format!("{} more test cases not annotated", omitted),
+            });
+        }
+    }
+    res
+}
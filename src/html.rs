@@ -0,0 +1,336 @@
+//! Render a report as a self-contained HTML page
+//!
+//! The page inlines its styles and scripts, it can be opened from the disk
+//! or published as a CI artifact without any network access. It shows:
+//!
+//! - the totals of the report
+//! - buttons filtering the test cases by status, and a search box matching
+//!   their names
+//! - the tree of test suites, which can be collapsed, with their properties
+//! - a table of test cases per test suite, sortable by name, status or
+//!   duration
+//! - expandable panes with the body of failures, errors and skipped tags, the
+//!   `system-out` and `system-err` outputs, the properties and the reruns of
+//!   each test case
+//!
+//! # Example
+//!
+//! ```
+//! use junit_parser::html::{render, HtmlOptions};
+//! use std::io::Cursor;
+//!
+//! let xml = r#"<testsuite name="s"><testcase name="a"><failure message="a &lt; b"/></testcase></testsuite>"#;
+//! let report = junit_parser::from_reader(Cursor::new(xml)).unwrap();
+//! let html = render(&report, &HtmlOptions::default());
+//! assert!(html.starts_with("<!DOCTYPE html>"));
+//! assert!(html.contains("a &lt; b"));
+//! ```
+
+use crate::{RerunOrFlakyKind, TestCase, TestStatus, TestSuite, TestSuites};
+use quick_xml::escape::escape;
+use std::fmt::Write;
+
+#[derive(Debug, Clone)]
+/// Options of the HTML renderer, see [`render`]
+pub struct HtmlOptions {
+    /// Title of the page
+    pub title: String,
+}
+
+impl Default for HtmlOptions {
+    fn default() -> Self {
+        Self {
+            title: "Test results".to_string(),
+        }
+    }
+}
+
+/// Styles of the page
+const STYLE: &str = r#"
+body { font-family: system-ui, sans-serif; margin: 1.5em; color: #222; }
+h1 { font-size: 1.5em; }
+.totals span { margin-right: 1.5em; }
+.toolbar { margin: 1em 0; display: flex; gap: .5em; flex-wrap: wrap; align-items: center; }
+.toolbar button { border: 1px solid #aaa; border-radius: 4px; background: #eee; padding: .3em .8em; cursor: pointer; }
+.toolbar button.off { opacity: .4; }
+.toolbar input { padding: .3em; min-width: 20em; }
+details.suite { margin: .3em 0 .3em 1em; }
+details.suite > summary { cursor: pointer; font-weight: bold; }
+.counts { font-weight: normal; color: #555; margin-left: 1em; }
+table { border-collapse: collapse; margin: .5em 0 .5em 1em; }
+th, td { text-align: left; padding: .2em .6em; border-bottom: 1px solid #ddd; vertical-align: top; }
+th.sort { cursor: pointer; user-select: none; }
+td.time { text-align: right; font-variant-numeric: tabular-nums; }
+.success { color: #1a7f37; }
+.failure, .error { color: #cf222e; }
+.skipped { color: #9a6700; }
+pre { background: #f6f8fa; padding: .5em; overflow: auto; max-height: 30em; margin: .3em 0; }
+details.pane { margin: .2em 0; }
+details.pane > summary { cursor: pointer; color: #555; }
+.hidden { display: none; }
+"#;
+
+/// Scripts of the page: filters, search and sorting
+const SCRIPT: &str = r#"
+(function () {
+  var hidden = {};
+  var search = document.getElementById("search");
+  function update() {
+    var text = search.value.toLowerCase();
+    document.querySelectorAll("tbody.case").forEach(function (c) {
+      var shown = !hidden[c.dataset.status] && c.dataset.name.toLowerCase().indexOf(text) >= 0;
+      c.classList.toggle("hidden", !shown);
+    });
+    var suites = Array.prototype.slice.call(document.querySelectorAll("details.suite")).reverse();
+    suites.forEach(function (s) {
+      var shown = s.querySelector("tbody.case:not(.hidden)") !== null;
+      s.classList.toggle("hidden", !shown);
+    });
+  }
+  document.querySelectorAll("button[data-status]").forEach(function (b) {
+    b.addEventListener("click", function () {
+      hidden[b.dataset.status] = !hidden[b.dataset.status];
+      b.classList.toggle("off", hidden[b.dataset.status]);
+      update();
+    });
+  });
+  search.addEventListener("input", update);
+  document.querySelectorAll("th.sort").forEach(function (th) {
+    th.addEventListener("click", function () {
+      var table = th.closest("table");
+      var key = th.dataset.key;
+      var desc = th.dataset.desc !== "true";
+      th.dataset.desc = desc;
+      var cases = Array.prototype.slice.call(table.querySelectorAll("tbody.case"));
+      cases.sort(function (a, b) {
+        var x = a.dataset[key], y = b.dataset[key];
+        var r = key === "time" ? parseFloat(x) - parseFloat(y) : x.localeCompare(y);
+        return desc ? -r : r;
+      });
+      cases.forEach(function (c) { table.appendChild(c); });
+    });
+  });
+})();
+"#;
+
+/// Name of the status of `tc`, also used as CSS class
+fn status(tc: &TestCase) -> &'static str {
+    tc.status.as_str()
+}
+
+/// Write an expandable pane titled `title` showing `text`, if not empty
+fn pane(out: &mut String, title: &str, text: &str) {
+    if text.trim().is_empty() {
+        return;
+    }
+    let _ = write!(
+        out,
+        "<details class=\"pane\"><summary>{}</summary><pre>{}</pre></details>",
+        escape(title),
+        escape(text)
+    );
+}
+
+/// Write a table of `properties`, if any
+fn properties_table(out: &mut String, properties: &[(&str, &str)]) {
+    if properties.is_empty() {
+        return;
+    }
+    out.push_str("<details class=\"pane\"><summary>Properties</summary><table>");
+    for (name, value) in properties {
+        let _ = write!(
+            out,
+            "<tr><th>{}</th><td>{}</td></tr>",
+            escape(*name),
+            escape(*value)
+        );
+    }
+    out.push_str("</table></details>");
+}
+
+/// Write the rows of the test case `tc`
+fn case(out: &mut String, tc: &TestCase) {
+    let status = status(tc);
+    let _ = write!(
+        out,
+        "<tbody class=\"case\" data-name=\"{name}\" data-status=\"{status}\" data-time=\"{time}\">\
+         <tr><td>{name}</td><td>{class}</td><td class=\"{status}\">{status}</td><td class=\"time\">{time:.3}s</td></tr>",
+        name = escape(&tc.name),
+        class = escape(tc.classname.as_deref().unwrap_or("")),
+        status = status,
+        time = tc.time,
+    );
+
+    let mut details = String::new();
+    let (message, text) = match &tc.status {
+        TestStatus::Success => ("", ""),
+        TestStatus::Failure(f) => (f.message.as_str(), f.text.as_str()),
+        TestStatus::Error(e) => (e.message.as_str(), e.text.as_str()),
+        TestStatus::Skipped(s) => (s.message.as_str(), s.text.as_str()),
+    };
+    if !message.is_empty() {
+        let _ = write!(
+            details,
+            "<div class=\"{}\">{}</div>",
+            status,
+            escape(message)
+        );
+    }
+    pane(&mut details, "Details", text);
+    pane(
+        &mut details,
+        "Standard output",
+        tc.system_out.as_deref().unwrap_or(""),
+    );
+    pane(
+        &mut details,
+        "Standard error",
+        tc.system_err.as_deref().unwrap_or(""),
+    );
+    properties_table(&mut details, &tc.properties.entries());
+    if !tc.reruns.is_empty() {
+        let _ = write!(
+            details,
+            "<details class=\"pane\"><summary>Reruns ({})</summary><table>\
+             <tr><th>Kind</th><th>Type</th><th>Message</th><th>Time</th></tr>",
+            tc.reruns.len()
+        );
+        for rerun in tc.reruns.iter() {
+            let kind = match rerun.kind {
+                RerunOrFlakyKind::FlakyFailure => "flaky failure",
+                RerunOrFlakyKind::FlakyError => "flaky error",
+                RerunOrFlakyKind::RerunFailure => "rerun failure",
+                RerunOrFlakyKind::RerunError => "rerun error",
+            };
+            let _ = write!(
+                details,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td class=\"time\">{:.3}s</td></tr><tr><td colspan=\"4\">",
+                kind,
+                escape(&rerun.rerun_type),
+                escape(&rerun.message),
+                rerun.time
+            );
+            pane(&mut details, "Details", &rerun.text);
+            pane(
+                &mut details,
+                "Stack trace",
+                rerun.stack_trace.as_deref().unwrap_or(""),
+            );
+            pane(
+                &mut details,
+                "Standard output",
+                rerun.system_out.as_deref().unwrap_or(""),
+            );
+            pane(
+                &mut details,
+                "Standard error",
+                rerun.system_err.as_deref().unwrap_or(""),
+            );
+            details.push_str("</td></tr>");
+        }
+        details.push_str("</table></details>");
+    }
+    if !details.is_empty() {
+        let _ = write!(out, "<tr><td colspan=\"4\">{}</td></tr>", details);
+    }
+    out.push_str("</tbody>");
+}
+
+/// Number of test cases of `ts` per status, nested test suites included
+fn counts(ts: &TestSuite) -> [usize; 4] {
+    let mut counts = [0; 4];
+    ts.visit_cases(&mut |_, tc| counts[tc.status.index()] += 1);
+    counts
+}
+
+/// Write the test suite `ts` and its nested test suites
+fn suite(out: &mut String, ts: &TestSuite) {
+    let [passed, failed, errors, skipped] = counts(ts);
+    // Collapse the test suites where everything passed
+    let open = if failed + errors > 0 { " open" } else { "" };
+    let _ = write!(
+        out,
+        "<details class=\"suite\"{}><summary>{}<span class=\"counts\">\
+         <span class=\"success\">{} passed</span>, <span class=\"failure\">{} failed</span>, \
+         <span class=\"error\">{} errors</span>, <span class=\"skipped\">{} skipped</span>, {:.3}s</span></summary>",
+        open,
+        escape(&ts.name),
+        passed,
+        failed,
+        errors,
+        skipped,
+        ts.time
+    );
+    pane(
+        out,
+        "Standard output",
+        ts.system_out.as_deref().unwrap_or(""),
+    );
+    pane(
+        out,
+        "Standard error",
+        ts.system_err.as_deref().unwrap_or(""),
+    );
+    properties_table(out, &ts.properties.entries());
+    if !ts.cases.is_empty() {
+        out.push_str(
+            "<table><thead><tr><th class=\"sort\" data-key=\"name\">Name</th><th>Class</th>\
+             <th class=\"sort\" data-key=\"status\">Status</th><th class=\"sort\" data-key=\"time\">Time</th></tr></thead>",
+        );
+        for tc in ts.cases.iter() {
+            case(out, tc);
+        }
+        out.push_str("</table>");
+    }
+    for nested in ts.suites.iter() {
+        suite(out, nested);
+    }
+    out.push_str("</details>");
+}
+
+/// Render `suites` as a self-contained HTML page
+pub fn render(suites: &TestSuites, options: &HtmlOptions) -> String {
+    let mut totals = [0; 4];
+    let mut time = 0.;
+    for ts in suites.suites.iter() {
+        for (total, count) in totals.iter_mut().zip(counts(ts)) {
+            *total += count;
+        }
+        time += ts.time;
+    }
+    if suites.time > 0. {
+        time = suites.time;
+    }
+    let [passed, failed, errors, skipped] = totals;
+    let title = escape(&options.title);
+
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{title}</title>\n<style>{style}</style>\n</head>\n<body>\n<h1>{title}</h1>\n\
+         <div class=\"totals\"><span>{tests} tests</span><span class=\"success\">{passed} passed</span>\
+         <span class=\"failure\">{failed} failed</span><span class=\"error\">{errors} errors</span>\
+         <span class=\"skipped\">{skipped} skipped</span><span>{time:.3}s</span></div>\n\
+         <div class=\"toolbar\"><button data-status=\"success\" class=\"success\">Passed</button>\
+         <button data-status=\"failure\" class=\"failure\">Failed</button>\
+         <button data-status=\"error\" class=\"error\">Errors</button>\
+         <button data-status=\"skipped\" class=\"skipped\">Skipped</button>\
+         <input id=\"search\" type=\"search\" placeholder=\"Search test cases\"></div>\n",
+        title = title,
+        style = STYLE,
+        tests = passed + failed + errors + skipped,
+        passed = passed,
+        failed = failed,
+        errors = errors,
+        skipped = skipped,
+        time = time,
+    );
+    for ts in suites.suites.iter() {
+        suite(&mut out, ts);
+        out.push('\n');
+    }
+    let _ = write!(out, "<script>{}</script>\n</body>\n</html>\n", SCRIPT);
+    out
+}
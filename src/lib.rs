@@ -39,24 +39,277 @@
 // See https://stackoverflow.com/questions/61417452
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 
+#[cfg(any(feature = "zip", feature = "tar"))]
+pub mod archive;
+pub mod azure;
+#[cfg(feature = "cluster")]
+pub mod cluster;
+/// Transparent decompression of reports
+#[cfg(any(feature = "gzip", feature = "zstd", feature = "xz"))]
+mod compression;
+pub mod durations;
 /// Errors
 mod errors;
+#[cfg(feature = "export")]
+pub mod export;
+pub mod flaky;
+#[cfg(feature = "gate")]
+pub mod gate;
+pub mod github;
+#[cfg(feature = "html")]
+pub mod html;
+pub mod markdown;
+#[cfg(feature = "otlp")]
+pub mod otlp;
+#[cfg(any(feature = "gate", feature = "quarantine", feature = "query"))]
+pub mod pattern;
+pub mod prometheus;
+#[cfg(feature = "quarantine")]
+pub mod quarantine;
+#[cfg(feature = "query")]
+pub mod query;
+#[cfg(feature = "sarif")]
+pub mod sarif;
+#[cfg(feature = "shard")]
+pub mod shard;
+#[cfg(feature = "stacktrace")]
+pub mod stacktrace;
+#[cfg(feature = "sqlite")]
+pub mod storage;
+pub mod tap;
+pub mod teamcity;
+pub mod writer;
 
 #[cfg(feature = "chrono")]
 use chrono::{DateTime, Utc};
 pub use errors::Error;
+#[cfg(feature = "encoding")]
+use quick_xml::encoding::DecodingReader;
 use quick_xml::escape::unescape;
+use quick_xml::events::BytesRef as XMLBytesRef;
 use quick_xml::events::BytesStart as XMLBytesStart;
 use quick_xml::events::Event as XMLEvent;
 use quick_xml::name::QName;
 use quick_xml::Reader as XMLReader;
 use std::borrow::Cow;
+use std::cell::Cell;
 #[cfg(feature = "properties_as_hashmap")]
 use std::collections::HashMap;
+use std::fs::File;
 use std::io::prelude::*;
+use std::io::BufReader;
+use std::ops::Range;
+use std::path::Path;
+use std::rc::Rc;
 use std::str;
 use std::vec::Vec;
 
+#[derive(Debug, Clone, Default)]
+/// Options controlling how a JUnit report is parsed, see [`from_reader_with_options`]
+pub struct ParseOptions {
+    /// Keep the body of `<failure>`, `<error>`, `<skipped>` and rerun/flaky
+    /// elements verbatim, as well as entity references in `system-out` and
+    /// `system-err`.
+    ///
+    /// By default, each text chunk of a body is trimmed and chunks are joined
+    /// with newlines.
+    pub preserve_whitespace: bool,
+    /// Record the [`Position`] of each [`TestSuite`] and [`TestCase`] in
+    /// the XML data
+    pub track_positions: bool,
+    /// Maximum nesting depth of `<testsuite>` elements, parsing fails with
+    /// [`Error::DepthLimitExceeded`] beyond it
+    ///
+    /// Nested test suites are parsed recursively: untrusted reports should be
+    /// parsed with a limit to avoid overflowing the stack.
+    pub max_depth: Option<usize>,
+    /// Maximum number of test cases in the report, parsing fails with
+    /// [`Error::CasesLimitExceeded`] beyond it
+    pub max_cases: Option<u64>,
+    /// Maximum size in bytes of a text, such as the body of a `<failure>` or
+    /// a `system-out` element, or the value of a property, longer texts are
+    /// truncated and end with [`TRUNCATION_MARKER`]
+    pub max_text_size: Option<usize>,
+    /// Maximum size in bytes of the XML data, once decompressed, parsing
+    /// fails with [`Error::SizeLimitExceeded`] beyond it
+    pub max_total_size: Option<u64>,
+}
+
+/// Marker ending texts truncated to [`ParseOptions::max_text_size`]
+pub const TRUNCATION_MARKER: &str = "[...truncated]";
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+/// Position of an element in the XML data, see [`ParseOptions::track_positions`]
+///
+/// When the data is compressed or not encoded in UTF-8, the position refers
+/// to the decompressed, UTF-8 encoded, data.
+pub struct Position {
+    /// Range of bytes from the start of the opening tag to the end of the
+    /// closing tag
+    pub byte_range: Range<u64>,
+    /// Line of the opening tag, starting at 1
+    pub line: u64,
+}
+
+/// State shared while parsing a report
+struct Context<'a> {
+    /// Options given by the caller
+    options: &'a ParseOptions,
+    /// Number of newlines read so far, shared with the [`LineCounter`]
+    /// wrapping the input when tracking positions
+    newlines: Option<Rc<Cell<u64>>>,
+    /// Current nesting depth of `<testsuite>` elements
+    depth: Cell<usize>,
+    /// Number of test cases parsed so far
+    cases: Cell<u64>,
+}
+
+impl Context<'_> {
+    /// New [`Context`] from the options given by the caller
+    fn new(options: &ParseOptions, newlines: Option<Rc<Cell<u64>>>) -> Context<'_> {
+        Context {
+            options,
+            newlines,
+            depth: Cell::new(0),
+            cases: Cell::new(0),
+        }
+    }
+
+    /// Enter a `<testsuite>` element, checking [`ParseOptions::max_depth`]
+    fn enter_suite(&self) -> Result<(), Error> {
+        let depth = self.depth.get() + 1;
+        if let Some(max) = self.options.max_depth {
+            if depth > max {
+                return Err(Error::DepthLimitExceeded(max));
+            }
+        }
+        self.depth.set(depth);
+        Ok(())
+    }
+
+    /// Leave a `<testsuite>` element
+    fn leave_suite(&self) {
+        self.depth.set(self.depth.get() - 1);
+    }
+
+    /// Count a `<testcase>` element, checking [`ParseOptions::max_cases`]
+    fn count_case(&self) -> Result<(), Error> {
+        let cases = self.cases.get() + 1;
+        if let Some(max) = self.options.max_cases {
+            if cases > max {
+                return Err(Error::CasesLimitExceeded(max));
+            }
+        }
+        self.cases.set(cases);
+        Ok(())
+    }
+
+    /// Position of the element whose opening tag `e` was just read by `r`
+    ///
+    /// The end of the byte range is the end of the opening tag, it has to be
+    /// updated with [`Context::end_position`] once the closing tag is read.
+    fn start_position<B: BufRead>(
+        &self,
+        e: &XMLBytesStart,
+        r: &XMLReader<B>,
+        empty: bool,
+    ) -> Option<Position> {
+        let newlines = self.newlines.as_ref()?.get();
+        let end = r.buffer_position();
+        // `<` and `>`, with `/` for an empty-element tag
+        let markup = if empty { 3 } else { 2 };
+        let start = end.saturating_sub(e.len() as u64 + markup);
+        let tag_newlines = e.iter().filter(|&&c| c == b'\n').count() as u64;
+        Some(Position {
+            byte_range: start..end,
+            line: 1 + newlines.saturating_sub(tag_newlines),
+        })
+    }
+
+    /// Update `position` to end where `r` stands, after a closing tag
+    fn end_position<B: BufRead>(&self, position: &mut Option<Position>, r: &XMLReader<B>) {
+        if let Some(p) = position.as_mut() {
+            p.byte_range.end = r.buffer_position();
+        }
+    }
+}
+
+/// Reader stopping after a given number of bytes of the wrapped reader
+struct SizeLimiter<B: BufRead> {
+    /// The wrapped reader
+    inner: B,
+    /// Number of bytes which can still be read
+    remaining: u64,
+    /// Set when data is left in the wrapped reader after the limit
+    exceeded: Rc<Cell<bool>>,
+}
+
+impl<B: BufRead> Read for SizeLimiter<B> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = {
+            let available = self.fill_buf()?;
+            let n = available.len().min(buf.len());
+            buf[..n].copy_from_slice(&available[..n]);
+            n
+        };
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl<B: BufRead> BufRead for SizeLimiter<B> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        let buf = self.inner.fill_buf()?;
+        if self.remaining == 0 {
+            if !buf.is_empty() {
+                self.exceeded.set(true);
+            }
+            return Ok(&[]);
+        }
+        let n = buf
+            .len()
+            .min(usize::try_from(self.remaining).unwrap_or(usize::MAX));
+        Ok(&buf[..n])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.remaining = self.remaining.saturating_sub(amt as u64);
+        self.inner.consume(amt);
+    }
+}
+
+/// Reader counting the newlines consumed from the wrapped reader
+struct LineCounter<B: BufRead> {
+    /// The wrapped reader
+    inner: B,
+    /// Number of newlines consumed so far
+    newlines: Rc<Cell<u64>>,
+}
+
+impl<B: BufRead> Read for LineCounter<B> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        let newlines = buf[..n].iter().filter(|&&c| c == b'\n').count() as u64;
+        self.newlines.set(self.newlines.get() + newlines);
+        Ok(n)
+    }
+}
+
+impl<B: BufRead> BufRead for LineCounter<B> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        if let Ok(buf) = self.inner.fill_buf() {
+            let newlines = buf[..amt].iter().filter(|&&c| c == b'\n').count() as u64;
+            self.newlines.set(self.newlines.get() + newlines);
+        }
+        self.inner.consume(amt);
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone, Default)]
 /// Properties associated to a [`TestSuite`] or a [`TestCase`]
@@ -69,10 +322,26 @@ pub struct Properties {
     pub vec: Vec<(String, String)>,
 }
 
+/// Append a text chunk to the value of a property, on a new line
+fn push_property_value(value: &mut Option<String>, chunk: &str, options: &ParseOptions) {
+    match value {
+        None => {
+            let mut v = String::new();
+            push_text(&mut v, chunk, options);
+            *value = Some(v);
+        }
+        Some(v) => {
+            push_text(v, "\n", options);
+            push_text(v, chunk, options);
+        }
+    }
+}
+
 /// Parse attributes of a `property` element
 fn parse_property<B: BufRead>(
     e: &XMLBytesStart,
     r: Option<&mut XMLReader<B>>,
+    options: &ParseOptions,
 ) -> Result<(String, String), Error> {
     let mut k: Option<String> = None;
     let mut v: Option<String> = None;
@@ -80,7 +349,15 @@ fn parse_property<B: BufRead>(
         let a = a?;
         match a.key {
             QName(b"name") => k = Some(try_from_attribute_value_string(a.value)?),
-            QName(b"value") => v = Some(try_from_attribute_value_string(a.value)?),
+            QName(b"value") => {
+                let mut value = String::new();
+                push_text(
+                    &mut value,
+                    &try_from_attribute_value_string(a.value)?,
+                    options,
+                );
+                v = Some(value);
+            }
             _ => {}
         };
     }
@@ -92,20 +369,12 @@ fn parse_property<B: BufRead>(
                 Ok(XMLEvent::Eof) => {
                     return Err(Error::UnexpectedEndOfFile("property".to_string()));
                 }
-                Ok(XMLEvent::Text(e)) => match v {
-                    None => v = Some(e.decode()?.trim().to_string()),
-                    Some(ref mut val) => {
-                        val.push('\n');
-                        val.push_str(e.decode()?.trim());
-                    }
-                },
-                Ok(XMLEvent::CData(e)) => match v {
-                    None => v = Some(str::from_utf8(&e)?.to_string()),
-                    Some(ref mut val) => {
-                        val.push('\n');
-                        val.push_str(str::from_utf8(&e)?);
-                    }
-                },
+                Ok(XMLEvent::Text(e)) => {
+                    push_property_value(&mut v, str::from_utf8(&e)?.trim(), options);
+                }
+                Ok(XMLEvent::CData(e)) => {
+                    push_property_value(&mut v, str::from_utf8(&e)?, options);
+                }
                 Ok(XMLEvent::Start(ref e)) => {
                     r.read_to_end_into(e.name(), &mut Vec::new())?;
                 }
@@ -123,8 +392,46 @@ fn parse_property<B: BufRead>(
 }
 
 impl Properties {
+    /// Properties as `(name, value)` pairs, in document order if stored as a
+    /// vector, or else sorted by name
+    pub(crate) fn entries(&self) -> Vec<(&str, &str)> {
+        #[cfg(feature = "properties_as_vector")]
+        return self
+            .vec
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        #[cfg(all(
+            feature = "properties_as_hashmap",
+            not(feature = "properties_as_vector")
+        ))]
+        {
+            let mut entries: Vec<(&str, &str)> = self
+                .hashmap
+                .iter()
+                .map(|(k, v)| (k.as_str(), v.as_str()))
+                .collect();
+            entries.sort_unstable();
+            return entries;
+        }
+        #[allow(unreachable_code)]
+        Vec::new()
+    }
+
+    /// Value of the first property named `name`, if any
+    #[cfg(any(feature = "export", feature = "query"))]
+    pub(crate) fn get(&self, name: &str) -> Option<&str> {
+        self.entries()
+            .into_iter()
+            .find(|(k, _)| *k == name)
+            .map(|(_, v)| v)
+    }
+
     /// Create a [`Properties`] from a XML `properties` element
-    fn from_reader<B: BufRead>(r: &mut XMLReader<B>) -> Result<Self, Error> {
+    fn from_reader<B: BufRead>(
+        r: &mut XMLReader<B>,
+        options: &ParseOptions,
+    ) -> Result<Self, Error> {
         let mut p = Self::default();
         loop {
             let mut buf = Vec::new();
@@ -132,11 +439,11 @@ impl Properties {
                 Ok(XMLEvent::End(ref e)) if e.name() == QName(b"properties") => break,
 
                 Ok(XMLEvent::Empty(ref e)) if e.name() == QName(b"property") => {
-                    let (k, v) = parse_property::<B>(e, None)?;
+                    let (k, v) = parse_property::<B>(e, None, options)?;
                     p.add_property(k, v);
                 }
                 Ok(XMLEvent::Start(ref e)) if e.name() == QName(b"property") => {
-                    let (k, v) = parse_property(e, Some(r))?;
+                    let (k, v) = parse_property(e, Some(r), options)?;
                     p.add_property(k, v);
                 }
                 Ok(XMLEvent::Start(ref e)) => {
@@ -254,6 +561,7 @@ impl RerunOrFlaky {
         e: &XMLBytesStart,
         r: &mut XMLReader<B>,
         kind: RerunOrFlakyKind,
+        ctx: &Context,
     ) -> Result<Self, Error> {
         let mut rt = Self {
             kind,
@@ -271,24 +579,17 @@ impl RerunOrFlaky {
             match r.read_event_into(&mut buf) {
                 Ok(XMLEvent::End(ref end_event)) if end_event.name() == end_tag_name => break,
                 Ok(XMLEvent::Text(e)) => {
-                    if rt.text.is_empty() {
-                        rt.text = e.decode()?.trim().to_string();
-                    } else {
-                        rt.text.push('\n');
-                        rt.text.push_str(e.decode()?.trim());
-                    }
+                    push_body(&mut rt.text, str::from_utf8(&e)?, true, ctx.options);
+                }
+                Ok(XMLEvent::GeneralRef(e)) if ctx.options.preserve_whitespace => {
+                    push_text(&mut rt.text, &resolve_reference(&e)?, ctx.options);
                 }
                 Ok(XMLEvent::CData(e)) => {
-                    if rt.text.is_empty() {
-                        rt.text = str::from_utf8(&e)?.to_string();
-                    } else {
-                        rt.text.push('\n');
-                        rt.text.push_str(str::from_utf8(&e)?);
-                    }
+                    push_body(&mut rt.text, str::from_utf8(&e)?, false, ctx.options);
                 }
                 Ok(XMLEvent::Start(ref start_event)) => match start_event.name() {
                     QName(b"system-out") => {
-                        if let Some(parsed_content) = parse_system(start_event, r)? {
+                        if let Some(parsed_content) = parse_system(start_event, r, ctx)? {
                             let current_out = rt.system_out.get_or_insert_with(String::new);
                             if !current_out.is_empty() {
                                 current_out.push('\n');
@@ -297,7 +598,7 @@ impl RerunOrFlaky {
                         }
                     }
                     QName(b"system-err") => {
-                        if let Some(parsed_content) = parse_system(start_event, r)? {
+                        if let Some(parsed_content) = parse_system(start_event, r, ctx)? {
                             let current_err = rt.system_err.get_or_insert_with(String::new);
                             if !current_err.is_empty() {
                                 current_err.push('\n');
@@ -307,7 +608,7 @@ impl RerunOrFlaky {
                     }
                     QName(b"stackTrace") => {
                         // Overwrite stackTrace as multiple instances are unlikely/undefined
-                        rt.stack_trace = parse_system(start_event, r)?;
+                        rt.stack_trace = parse_system(start_event, r, ctx)?;
                     }
                     _ => {
                         r.read_to_end_into(start_event.name(), &mut Vec::new())?;
@@ -365,7 +666,11 @@ impl TestFailure {
     }
 
     /// New [`TestFailure`] from XML tree
-    fn from_reader<B: BufRead>(e: &XMLBytesStart, r: &mut XMLReader<B>) -> Result<Self, Error> {
+    fn from_reader<B: BufRead>(
+        e: &XMLBytesStart,
+        r: &mut XMLReader<B>,
+        ctx: &Context,
+    ) -> Result<Self, Error> {
         let mut tf = Self::default();
         tf.parse_attributes(e)?;
         loop {
@@ -373,23 +678,16 @@ impl TestFailure {
             match r.read_event_into(&mut buf) {
                 Ok(XMLEvent::End(ref e)) if e.name() == QName(b"failure") => break,
                 Ok(XMLEvent::Text(e)) => {
-                    if tf.text.is_empty() {
-                        tf.text = e.decode()?.trim().to_string();
-                    } else {
-                        tf.text.push('\n');
-                        tf.text.push_str(e.decode()?.trim());
-                    }
+                    push_body(&mut tf.text, str::from_utf8(&e)?, true, ctx.options);
+                }
+                Ok(XMLEvent::GeneralRef(e)) if ctx.options.preserve_whitespace => {
+                    push_text(&mut tf.text, &resolve_reference(&e)?, ctx.options);
                 }
                 Ok(XMLEvent::Eof) => {
                     return Err(Error::UnexpectedEndOfFile("failure".to_string()));
                 }
                 Ok(XMLEvent::CData(e)) => {
-                    if tf.text.is_empty() {
-                        tf.text = str::from_utf8(&e)?.to_string();
-                    } else {
-                        tf.text.push('\n');
-                        tf.text.push_str(str::from_utf8(&e)?);
-                    }
+                    push_body(&mut tf.text, str::from_utf8(&e)?, false, ctx.options);
                 }
                 Ok(XMLEvent::Start(ref e)) => {
                     r.read_to_end_into(e.name(), &mut Vec::new())?;
@@ -436,7 +734,11 @@ impl TestError {
     }
 
     /// New [`TestError`] from XML tree
-    fn from_reader<B: BufRead>(e: &XMLBytesStart, r: &mut XMLReader<B>) -> Result<Self, Error> {
+    fn from_reader<B: BufRead>(
+        e: &XMLBytesStart,
+        r: &mut XMLReader<B>,
+        ctx: &Context,
+    ) -> Result<Self, Error> {
         let mut te = Self::default();
         te.parse_attributes(e)?;
         loop {
@@ -444,23 +746,16 @@ impl TestError {
             match r.read_event_into(&mut buf) {
                 Ok(XMLEvent::End(ref e)) if e.name() == QName(b"error") => break,
                 Ok(XMLEvent::Text(e)) => {
-                    if te.text.is_empty() {
-                        te.text = e.decode()?.trim().to_string();
-                    } else {
-                        te.text.push('\n');
-                        te.text.push_str(e.decode()?.trim());
-                    }
+                    push_body(&mut te.text, str::from_utf8(&e)?, true, ctx.options);
+                }
+                Ok(XMLEvent::GeneralRef(e)) if ctx.options.preserve_whitespace => {
+                    push_text(&mut te.text, &resolve_reference(&e)?, ctx.options);
                 }
                 Ok(XMLEvent::Eof) => {
                     return Err(Error::UnexpectedEndOfFile("error".to_string()));
                 }
                 Ok(XMLEvent::CData(e)) => {
-                    if te.text.is_empty() {
-                        te.text = str::from_utf8(&e)?.to_string();
-                    } else {
-                        te.text.push('\n');
-                        te.text.push_str(str::from_utf8(&e)?);
-                    }
+                    push_body(&mut te.text, str::from_utf8(&e)?, false, ctx.options);
                 }
                 Ok(XMLEvent::Start(ref e)) => {
                     r.read_to_end_into(e.name(), &mut Vec::new())?;
@@ -507,7 +802,11 @@ impl TestSkipped {
     }
 
     /// New [`TestSkipped`] from XML tree
-    fn from_reader<B: BufRead>(e: &XMLBytesStart, r: &mut XMLReader<B>) -> Result<Self, Error> {
+    fn from_reader<B: BufRead>(
+        e: &XMLBytesStart,
+        r: &mut XMLReader<B>,
+        ctx: &Context,
+    ) -> Result<Self, Error> {
         let mut ts = Self::default();
         ts.parse_attributes(e)?;
         loop {
@@ -515,23 +814,16 @@ impl TestSkipped {
             match r.read_event_into(&mut buf) {
                 Ok(XMLEvent::End(ref e)) if e.name() == QName(b"skipped") => break,
                 Ok(XMLEvent::Text(e)) => {
-                    if ts.text.is_empty() {
-                        ts.text = e.decode()?.trim().to_string();
-                    } else {
-                        ts.text.push('\n');
-                        ts.text.push_str(e.decode()?.trim());
-                    }
+                    push_body(&mut ts.text, str::from_utf8(&e)?, true, ctx.options);
+                }
+                Ok(XMLEvent::GeneralRef(e)) if ctx.options.preserve_whitespace => {
+                    push_text(&mut ts.text, &resolve_reference(&e)?, ctx.options);
                 }
                 Ok(XMLEvent::Eof) => {
                     return Err(Error::UnexpectedEndOfFile("skipped".to_string()));
                 }
                 Ok(XMLEvent::CData(e)) => {
-                    if ts.text.is_empty() {
-                        ts.text = str::from_utf8(&e)?.to_string();
-                    } else {
-                        ts.text.push('\n');
-                        ts.text.push_str(str::from_utf8(&e)?);
-                    }
+                    push_body(&mut ts.text, str::from_utf8(&e)?, false, ctx.options);
                 }
                 Ok(XMLEvent::Start(ref e)) => {
                     r.read_to_end_into(e.name(), &mut Vec::new())?;
@@ -613,8 +905,27 @@ impl TestStatus {
         }
         panic!("called `TestStatus::skipped()` on a value that is not TestStatus::Skipped(_)");
     }
+
+    /// Returns the name of the status: `success`, `failure`, `error` or
+    /// `skipped`
+    pub fn as_str(&self) -> &'static str {
+        STATUS_NAMES[self.index()]
+    }
+
+    /// Index of the status in [`STATUS_NAMES`]
+    pub(crate) fn index(&self) -> usize {
+        match self {
+            TestStatus::Success => 0,
+            TestStatus::Failure(_) => 1,
+            TestStatus::Error(_) => 2,
+            TestStatus::Skipped(_) => 3,
+        }
+    }
 }
 
+/// Names of the statuses, see [`TestStatus::as_str`]
+pub(crate) const STATUS_NAMES: [&str; 4] = ["success", "failure", "error", "skipped"];
+
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone, Default)]
 /// A test case
@@ -652,6 +963,11 @@ pub struct TestCase {
     pub properties: Properties,
     /// Reruns of the test case
     pub reruns: Vec<RerunOrFlaky>,
+    /// Path of the file the test case was read from, when known
+    pub source_file: Option<String>,
+    /// Position of the test case in the XML data, see
+    /// [`ParseOptions::track_positions`]
+    pub position: Option<Position>,
 }
 impl TestCase {
     /// Fill up `self` with attributes from the XML tag
@@ -691,17 +1007,31 @@ impl TestCase {
     }
 
     /// New [`TestCase`] from empty XML tag
-    fn new_empty(e: &XMLBytesStart) -> Result<Self, Error> {
-        let mut tc = Self::default();
+    fn new_empty<B: BufRead>(
+        e: &XMLBytesStart,
+        r: &XMLReader<B>,
+        ctx: &Context,
+    ) -> Result<Self, Error> {
+        ctx.count_case()?;
+        let mut tc = Self {
+            position: ctx.start_position(e, r, true),
+            ..Default::default()
+        };
         tc.parse_attributes(e)?;
         Ok(tc)
     }
 
     /// New [`TestCase`] from XML tree
-    fn from_reader<B: BufRead>(e: &XMLBytesStart, r: &mut XMLReader<B>) -> Result<Self, Error> {
+    fn from_reader<B: BufRead>(
+        e: &XMLBytesStart,
+        r: &mut XMLReader<B>,
+        ctx: &Context,
+    ) -> Result<Self, Error> {
+        ctx.count_case()?;
         let mut tc = Self {
             system_out: None,
             system_err: None,
+            position: ctx.start_position(e, r, false),
             ..Default::default()
         };
         tc.parse_attributes(e)?;
@@ -712,14 +1042,10 @@ impl TestCase {
 
                 Ok(XMLEvent::Empty(ref empty_event)) => match empty_event.name() {
                     QName(b"system-out") => {
-                        if tc.system_out.is_none() {
-                            tc.system_out = Some(String::new());
-                        }
+                        tc.system_out.get_or_insert_with(String::new);
                     }
                     QName(b"system-err") => {
-                        if tc.system_err.is_none() {
-                            tc.system_err = Some(String::new());
-                        }
+                        tc.system_err.get_or_insert_with(String::new);
                     }
                     QName(b"flakyFailure") => {
                         tc.reruns.push(RerunOrFlaky::new_empty(
@@ -759,19 +1085,22 @@ impl TestCase {
 
                 Ok(XMLEvent::Start(ref start_event)) => match start_event.name() {
                     QName(b"skipped") => {
-                        tc.status = TestStatus::Skipped(TestSkipped::from_reader(start_event, r)?);
+                        tc.status =
+                            TestStatus::Skipped(TestSkipped::from_reader(start_event, r, ctx)?);
                     }
                     QName(b"failure") => {
-                        tc.status = TestStatus::Failure(TestFailure::from_reader(start_event, r)?);
+                        tc.status =
+                            TestStatus::Failure(TestFailure::from_reader(start_event, r, ctx)?);
                     }
                     QName(b"error") => {
-                        tc.status = TestStatus::Error(TestError::from_reader(start_event, r)?);
+                        tc.status = TestStatus::Error(TestError::from_reader(start_event, r, ctx)?);
                     }
                     QName(b"flakyFailure") => {
                         tc.reruns.push(RerunOrFlaky::from_reader(
                             start_event,
                             r,
                             RerunOrFlakyKind::FlakyFailure,
+                            ctx,
                         )?);
                     }
                     QName(b"flakyError") => {
@@ -779,6 +1108,7 @@ impl TestCase {
                             start_event,
                             r,
                             RerunOrFlakyKind::FlakyError,
+                            ctx,
                         )?);
                     }
                     QName(b"rerunFailure") => {
@@ -786,6 +1116,7 @@ impl TestCase {
                             start_event,
                             r,
                             RerunOrFlakyKind::RerunFailure,
+                            ctx,
                         )?);
                     }
                     QName(b"rerunError") => {
@@ -793,10 +1124,11 @@ impl TestCase {
                             start_event,
                             r,
                             RerunOrFlakyKind::RerunError,
+                            ctx,
                         )?);
                     }
                     QName(b"system-out") => {
-                        if let Some(parsed_content) = parse_system(start_event, r)? {
+                        if let Some(parsed_content) = parse_system(start_event, r, ctx)? {
                             let current_out = tc.system_out.get_or_insert_with(String::new);
                             if !current_out.is_empty() {
                                 current_out.push('\n');
@@ -805,7 +1137,7 @@ impl TestCase {
                         }
                     }
                     QName(b"system-err") => {
-                        if let Some(parsed_content) = parse_system(start_event, r)? {
+                        if let Some(parsed_content) = parse_system(start_event, r, ctx)? {
                             let current_err = tc.system_err.get_or_insert_with(String::new);
                             if !current_err.is_empty() {
                                 current_err.push('\n');
@@ -814,7 +1146,7 @@ impl TestCase {
                         }
                     }
                     QName(b"properties") => {
-                        tc.properties = Properties::from_reader(r)?;
+                        tc.properties = Properties::from_reader(r, ctx.options)?;
                     }
                     _ => {
                         r.read_to_end_into(start_event.name(), &mut Vec::new())?;
@@ -828,6 +1160,7 @@ impl TestCase {
             }
             buf.clear();
         }
+        ctx.end_position(&mut tc.position, r);
         Ok(tc)
     }
 }
@@ -880,8 +1213,36 @@ pub struct TestSuite {
     pub system_err: Option<String>,
     /// Properties of the test suite
     pub properties: Properties,
+    /// Path of the file the test suite was read from, when known
+    pub source_file: Option<String>,
+    /// Position of the test suite in the XML data, see
+    /// [`ParseOptions::track_positions`]
+    pub position: Option<Position>,
 }
 impl TestSuite {
+    /// Set the `source_file` of `self` and of all its test suites and test
+    /// cases
+    pub(crate) fn set_source_file(&mut self, source_file: &str) {
+        self.source_file = Some(source_file.to_string());
+        for tc in self.cases.iter_mut() {
+            tc.source_file = Some(source_file.to_string());
+        }
+        for ts in self.suites.iter_mut() {
+            ts.set_source_file(source_file);
+        }
+    }
+
+    /// Call `f` on every test case of `self` and of its nested test suites,
+    /// along with the test suite containing it
+    pub(crate) fn visit_cases<'a, F: FnMut(&'a TestSuite, &'a TestCase)>(&'a self, f: &mut F) {
+        for tc in self.cases.iter() {
+            f(self, tc);
+        }
+        for ts in self.suites.iter() {
+            ts.visit_cases(f);
+        }
+    }
+
     /// Fill up `self` with attributes from the XML tag
     fn parse_attributes(&mut self, e: &XMLBytesStart) -> Result<(), Error> {
         for a in e.attributes() {
@@ -922,39 +1283,54 @@ impl TestSuite {
     }
 
     /// New [`TestSuite`] from empty XML tag
-    fn new_empty(e: &XMLBytesStart) -> Result<Self, Error> {
-        let mut ts = Self::default();
+    fn new_empty<B: BufRead>(
+        e: &XMLBytesStart,
+        r: &XMLReader<B>,
+        ctx: &Context,
+    ) -> Result<Self, Error> {
+        let mut ts = Self {
+            position: ctx.start_position(e, r, true),
+            ..Default::default()
+        };
         ts.parse_attributes(e)?;
         Ok(ts)
     }
 
     /// New [`TestSuite`] from XML tree
-    fn from_reader<B: BufRead>(e: &XMLBytesStart, r: &mut XMLReader<B>) -> Result<Self, Error> {
-        let mut ts = Self::default();
+    fn from_reader<B: BufRead>(
+        e: &XMLBytesStart,
+        r: &mut XMLReader<B>,
+        ctx: &Context,
+    ) -> Result<Self, Error> {
+        ctx.enter_suite()?;
+        let mut ts = Self {
+            position: ctx.start_position(e, r, false),
+            ..Default::default()
+        };
         ts.parse_attributes(e)?;
         loop {
             let mut buf = Vec::new();
             match r.read_event_into(&mut buf) {
                 Ok(XMLEvent::End(ref e)) if e.name() == QName(b"testsuite") => break,
                 Ok(XMLEvent::Start(ref e)) if e.name() == QName(b"testsuite") => {
-                    ts.suites.push(TestSuite::from_reader(e, r)?);
+                    ts.suites.push(TestSuite::from_reader(e, r, ctx)?);
                 }
                 Ok(XMLEvent::Start(ref e)) if e.name() == QName(b"testcase") => {
-                    ts.cases.push(TestCase::from_reader(e, r)?);
+                    ts.cases.push(TestCase::from_reader(e, r, ctx)?);
                 }
                 Ok(XMLEvent::Empty(ref e)) if e.name() == QName(b"testcase") => {
-                    ts.cases.push(TestCase::new_empty(e)?);
+                    ts.cases.push(TestCase::new_empty(e, r, ctx)?);
                 }
                 Ok(XMLEvent::Empty(ref e)) if e.name() == QName(b"system-out") => {}
                 Ok(XMLEvent::Start(ref e)) if e.name() == QName(b"system-out") => {
-                    ts.system_out = parse_system(e, r)?;
+                    ts.system_out = parse_system(e, r, ctx)?;
                 }
                 Ok(XMLEvent::Empty(ref e)) if e.name() == QName(b"system-err") => {}
                 Ok(XMLEvent::Start(ref e)) if e.name() == QName(b"system-err") => {
-                    ts.system_err = parse_system(e, r)?;
+                    ts.system_err = parse_system(e, r, ctx)?;
                 }
                 Ok(XMLEvent::Start(ref e)) if e.name() == QName(b"properties") => {
-                    ts.properties = Properties::from_reader(r)?;
+                    ts.properties = Properties::from_reader(r, ctx.options)?;
                 }
                 Ok(XMLEvent::Start(ref e)) => {
                     r.read_to_end_into(e.name(), &mut Vec::new())?;
@@ -967,6 +1343,8 @@ impl TestSuite {
             }
             buf.clear();
         }
+        ctx.end_position(&mut ts.position, r);
+        ctx.leave_suite();
         Ok(ts)
     }
 }
@@ -1034,7 +1412,11 @@ impl TestSuites {
     }
 
     /// New [`TestSuites`] from XML tree
-    fn from_reader<B: BufRead>(e: &XMLBytesStart, r: &mut XMLReader<B>) -> Result<Self, Error> {
+    fn from_reader<B: BufRead>(
+        e: &XMLBytesStart,
+        r: &mut XMLReader<B>,
+        ctx: &Context,
+    ) -> Result<Self, Error> {
         let mut ts = Self::default();
         ts.parse_attributes(e)?;
         loop {
@@ -1043,10 +1425,10 @@ impl TestSuites {
                 Ok(XMLEvent::End(ref e)) if e.name() == QName(b"testsuites") => break,
                 Ok(XMLEvent::End(ref e)) if e.name() == QName(b"testrun") => break,
                 Ok(XMLEvent::Start(ref e)) if e.name() == QName(b"testsuite") => {
-                    ts.suites.push(TestSuite::from_reader(e, r)?);
+                    ts.suites.push(TestSuite::from_reader(e, r, ctx)?);
                 }
                 Ok(XMLEvent::Empty(ref e)) if e.name() == QName(b"testsuite") => {
-                    ts.suites.push(TestSuite::new_empty(e)?);
+                    ts.suites.push(TestSuite::new_empty(e, r, ctx)?);
                 }
                 Ok(XMLEvent::Eof) => {
                     return Err(Error::UnexpectedEndOfFile("testsuites".to_string()));
@@ -1095,10 +1477,57 @@ fn try_from_attribute_value_datetime(value: Cow<[u8]>) -> Result<DateTime<Utc>,
     Ok(dt)
 }
 
+/// Append a text chunk to the body of an element
+///
+/// Unless whitespace is preserved, the chunk is trimmed if `trim` is set and
+/// chunks are separated by newlines.
+fn push_body(body: &mut String, chunk: &str, trim: bool, options: &ParseOptions) {
+    if options.preserve_whitespace {
+        push_text(body, chunk, options);
+    } else {
+        if !body.is_empty() {
+            push_text(body, "\n", options);
+        }
+        push_text(body, if trim { chunk.trim() } else { chunk }, options);
+    }
+}
+
+/// Append `chunk` to a text, truncating it to [`ParseOptions::max_text_size`]
+///
+/// Once truncated, [`TRUNCATION_MARKER`] ends the text and further chunks
+/// are dropped.
+fn push_text(text: &mut String, chunk: &str, options: &ParseOptions) {
+    let max = match options.max_text_size {
+        Some(max) => max,
+        None => {
+            text.push_str(chunk);
+            return;
+        }
+    };
+    if text.len() + chunk.len() <= max {
+        text.push_str(chunk);
+    } else if text.len() <= max {
+        let mut end = max - text.len();
+        while !chunk.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.push_str(&chunk[..end]);
+        text.push_str(TRUNCATION_MARKER);
+    }
+}
+
+/// Resolve an entity or character reference found in a text
+fn resolve_reference(e: &XMLBytesRef) -> Result<String, Error> {
+    let s = format!("&{};", str::from_utf8(e)?);
+    let u = unescape(&s)?;
+    Ok(u.to_string())
+}
+
 /// Parse a chunk of xml as system-out or system-err
 fn parse_system<B: BufRead>(
     orig: &XMLBytesStart,
     r: &mut XMLReader<B>,
+    ctx: &Context,
 ) -> Result<Option<String>, Error> {
     let mut res: Option<String> = Some(String::new());
     loop {
@@ -1106,11 +1535,16 @@ fn parse_system<B: BufRead>(
         match r.read_event_into(&mut buf) {
             Ok(XMLEvent::End(ref e)) if e.name() == orig.name() => break,
             Ok(XMLEvent::Text(e)) => {
-                res.get_or_insert(String::new()).push_str(&e.decode()?);
+                let text = res.get_or_insert(String::new());
+                push_text(text, str::from_utf8(&e)?, ctx.options);
             }
             Ok(XMLEvent::CData(e)) => {
-                res.get_or_insert(String::new())
-                    .push_str(str::from_utf8(&e)?);
+                let text = res.get_or_insert(String::new());
+                push_text(text, str::from_utf8(&e)?, ctx.options);
+            }
+            Ok(XMLEvent::GeneralRef(e)) if ctx.options.preserve_whitespace => {
+                let text = res.get_or_insert(String::new());
+                push_text(text, &resolve_reference(&e)?, ctx.options);
             }
             Ok(XMLEvent::Eof) => {
                 return Err(Error::UnexpectedEndOfFile(format!("{:?}", orig.name())));
@@ -1126,8 +1560,37 @@ fn parse_system<B: BufRead>(
     Ok(res)
 }
 
+/// Wrap `reader` to transcode its content into UTF-8
+///
+/// The encoding is detected from the BOM, or else from the `encoding`
+/// attribute of the XML declaration.
+#[cfg(feature = "encoding")]
+fn decoding_reader<B: BufRead>(mut reader: B) -> Result<DecodingReader<B>, Error> {
+    let mut encoding = None;
+    {
+        let head = reader.fill_buf().map_err(quick_xml::Error::from)?;
+        let head = head.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(head);
+        if head.starts_with(b"<?xml") {
+            let mut r = XMLReader::from_reader(head);
+            if let Ok(XMLEvent::Decl(e)) = r.read_event_into(&mut Vec::new()) {
+                encoding = e.encoder();
+            }
+        }
+    }
+    let mut dr = DecodingReader::new(reader);
+    // A BOM takes precedence over the declaration, and UTF-16 can only be
+    // detected that way
+    if let Some(encoding) = encoding.filter(|e| e.is_ascii_compatible()) {
+        dr.set_encoding(encoding);
+    }
+    Ok(dr)
+}
+
 /// Creates a [`TestSuites`](struct.TestSuites.html) structure from a JUnit XML data read from `reader`
 ///
+/// With the `gzip`, `zstd` or `xz` features, compressed data is detected by
+/// its magic bytes and decompressed transparently.
+///
 /// # Example
 /// ```
 /// use std::io::Cursor;
@@ -1145,6 +1608,70 @@ fn parse_system<B: BufRead>(
 ///     assert!(r.is_ok());
 /// ```
 pub fn from_reader<B: BufRead>(reader: B) -> Result<TestSuites, Error> {
+    from_reader_with_options(reader, &ParseOptions::default())
+}
+
+/// Creates a [`TestSuites`](struct.TestSuites.html) structure from a JUnit XML data read from `reader`,
+/// using the given [`ParseOptions`]
+///
+/// # Example
+/// ```
+/// use std::io::Cursor;
+/// use junit_parser::ParseOptions;
+///     let xml = r#"
+/// <testsuite tests="1" failures="1">
+///   <testcase classname="foo" name="AFailingTest">
+///     <failure type="AssertionError">Traceback:
+///     assert foo</failure>
+///   </testcase>
+/// </testsuite>
+/// "#;
+///     let options = ParseOptions {
+///         preserve_whitespace: true,
+///         ..Default::default()
+///     };
+///     let cursor = Cursor::new(xml);
+///     let r = junit_parser::from_reader_with_options(cursor, &options);
+///     assert!(r.is_ok());
+///     let t = r.unwrap();
+///     let tf = t.suites[0].cases[0].status.failure_as_ref();
+///     assert_eq!(tf.text, "Traceback:\n    assert foo");
+/// ```
+pub fn from_reader_with_options<B: BufRead>(
+    reader: B,
+    options: &ParseOptions,
+) -> Result<TestSuites, Error> {
+    #[cfg(any(feature = "gzip", feature = "zstd", feature = "xz"))]
+    let reader = compression::decompressing_reader(reader)?;
+    #[cfg(feature = "encoding")]
+    let reader = decoding_reader(reader)?;
+    let exceeded = Rc::new(Cell::new(false));
+    let reader = SizeLimiter {
+        inner: reader,
+        remaining: options.max_total_size.unwrap_or(u64::MAX),
+        exceeded: exceeded.clone(),
+    };
+    let res = if options.track_positions {
+        let newlines = Rc::new(Cell::new(0));
+        let ctx = Context::new(options, Some(newlines.clone()));
+        parse(
+            LineCounter {
+                inner: reader,
+                newlines,
+            },
+            &ctx,
+        )
+    } else {
+        parse(reader, &Context::new(options, None))
+    };
+    match options.max_total_size {
+        Some(max) if exceeded.get() => Err(Error::SizeLimitExceeded(max)),
+        _ => res,
+    }
+}
+
+/// Parse the JUnit XML data read from `reader`
+fn parse<B: BufRead>(reader: B, ctx: &Context) -> Result<TestSuites, Error> {
     let mut r = XMLReader::from_reader(reader);
     loop {
         let mut buf = Vec::new();
@@ -1156,19 +1683,19 @@ pub fn from_reader<B: BufRead>(reader: B) -> Result<TestSuites, Error> {
                 return TestSuites::new_empty(e);
             }
             Ok(XMLEvent::Start(ref e)) if e.name() == QName(b"testsuites") => {
-                return TestSuites::from_reader(e, &mut r);
+                return TestSuites::from_reader(e, &mut r, ctx);
             }
             Ok(XMLEvent::Start(ref e)) if e.name() == QName(b"testrun") => {
-                return TestSuites::from_reader(e, &mut r);
+                return TestSuites::from_reader(e, &mut r, ctx);
             }
             Ok(XMLEvent::Empty(ref e)) if e.name() == QName(b"testsuite") => {
-                let ts = TestSuite::new_empty(e)?;
+                let ts = TestSuite::new_empty(e, &r, ctx)?;
                 let mut suites = TestSuites::default();
                 suites.suites.push(ts);
                 return Ok(suites);
             }
             Ok(XMLEvent::Start(ref e)) if e.name() == QName(b"testsuite") => {
-                let ts = TestSuite::from_reader(e, &mut r)?;
+                let ts = TestSuite::from_reader(e, &mut r, ctx)?;
                 let mut suites = TestSuites::default();
                 suites.suites.push(ts);
                 return Ok(suites);
@@ -1185,3 +1712,30 @@ pub fn from_reader<B: BufRead>(reader: B) -> Result<TestSuites, Error> {
         buf.clear();
     }
 }
+
+/// Creates a [`TestSuites`](struct.TestSuites.html) structure from the JUnit XML file at `path`
+///
+/// The `source_file` of every [`TestSuite`] and [`TestCase`] is set to `path`.
+///
+/// # Example
+/// ```no_run
+///     let r = junit_parser::from_path("target/junit.xml.gz");
+/// ```
+pub fn from_path<P: AsRef<Path>>(path: P) -> Result<TestSuites, Error> {
+    from_path_with_options(path, &ParseOptions::default())
+}
+
+/// Creates a [`TestSuites`](struct.TestSuites.html) structure from the JUnit XML file at `path`,
+/// using the given [`ParseOptions`]
+pub fn from_path_with_options<P: AsRef<Path>>(
+    path: P,
+    options: &ParseOptions,
+) -> Result<TestSuites, Error> {
+    let file = File::open(&path)?;
+    let mut ts = from_reader_with_options(BufReader::new(file), options)?;
+    let source_file = path.as_ref().display().to_string();
+    for suite in ts.suites.iter_mut() {
+        suite.set_source_file(&source_file);
+    }
+    Ok(ts)
+}
@@ -0,0 +1,395 @@
+//! Render a report as Markdown
+//!
+//! The summary is meant for CI step summaries, such as GitHub's
+//! `$GITHUB_STEP_SUMMARY`, and for pull request comments. It is made of:
+//!
+//! - the totals of the report, compared to a baseline report if any
+//! - the test cases failing but not in the baseline, and the ones fixed
+//! - a collapsible section per failure or error, with its message and the
+//!   beginning of its body
+//! - a table of the flaky test cases, which have rerun or flaky entries
+//! - a table of the top-level test suites
+//!
+//! Items are written in this order as long as the summary stays under
+//! [`MarkdownOptions::max_size`], the ones left out are counted in a note.
+//!
+//! # Example
+//!
+//! ```
+//! use junit_parser::markdown::{render, MarkdownOptions};
+//! use std::io::Cursor;
+//!
+//! let xml = r#"<testsuite name="s"><testcase name="a"><failure message="boom"/></testcase><testcase name="b"/></testsuite>"#;
+//! let report = junit_parser::from_reader(Cursor::new(xml)).unwrap();
+//! let markdown = render(&report, None, &MarkdownOptions::default());
+//! assert!(markdown.contains("| 2 | 1 | 1 | 0 | 0 |"));
+//! assert!(markdown.contains("<summary>❌ a: boom</summary>"));
+//! ```
+
+use crate::{TestCase, TestStatus, TestSuite, TestSuites};
+use std::collections::HashSet;
+
+#[derive(Debug, Clone)]
+/// Options of the Markdown renderer, see [`render`]
+pub struct MarkdownOptions {
+    /// Title of the summary
+    pub title: String,
+    /// Maximum size of the summary, in bytes
+    ///
+    /// The default of 65,000 bytes fits in a GitHub comment.
+    pub max_size: usize,
+    /// Maximum number of lines of the body of a failure or error
+    pub max_body_lines: usize,
+    /// Maximum number of bytes of the body of a failure or error
+    pub max_body_size: usize,
+}
+
+impl Default for MarkdownOptions {
+    fn default() -> Self {
+        Self {
+            title: "Test results".to_string(),
+            max_size: 65_000,
+            max_body_lines: 30,
+            max_body_size: 4_000,
+        }
+    }
+}
+
+/// Number of test cases per outcome, and duration
+#[derive(Default, Clone, Copy)]
+struct Totals {
+    /// All the test cases
+    tests: usize,
+    /// Passing test cases
+    passed: usize,
+    /// Failing test cases
+    failed: usize,
+    /// Test cases in error
+    errors: usize,
+    /// Skipped test cases
+    skipped: usize,
+    /// Duration, from the `time` attribute or else summed up from the test
+    /// cases
+    time: f64,
+}
+
+impl Totals {
+    /// Count the test case `tc`
+    fn add(&mut self, tc: &TestCase) {
+        self.tests += 1;
+        self.time += tc.time;
+        match tc.status {
+            TestStatus::Success => self.passed += 1,
+            TestStatus::Failure(_) => self.failed += 1,
+            TestStatus::Error(_) => self.errors += 1,
+            TestStatus::Skipped(_) => self.skipped += 1,
+        }
+    }
+
+    /// Totals of the test suite `ts`, nested ones included
+    fn of_suite(ts: &TestSuite) -> Totals {
+        let mut totals = Totals::default();
+        ts.visit_cases(&mut |_, tc| totals.add(tc));
+        if ts.time > 0. {
+            totals.time = ts.time;
+        }
+        totals
+    }
+
+    /// Totals of the report `suites`
+    fn of_report(suites: &TestSuites) -> Totals {
+        let mut totals = Totals::default();
+        for ts in suites.suites.iter() {
+            let t = Totals::of_suite(ts);
+            totals.tests += t.tests;
+            totals.passed += t.passed;
+            totals.failed += t.failed;
+            totals.errors += t.errors;
+            totals.skipped += t.skipped;
+            totals.time += t.time;
+        }
+        if suites.time > 0. {
+            totals.time = suites.time;
+        }
+        totals
+    }
+}
+
+/// Escape the characters of `s` interpreted by HTML, and the line breaks
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '\n' | '\r' => escaped.push(' '),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escape `s` as the content of a table cell, where `|` ends the cell
+fn escape_cell(s: &str) -> String {
+    escape(s).replace('|', "\\|")
+}
+
+/// Format `count`, followed by its difference with `baseline` if any
+fn count_with_delta(count: usize, baseline: Option<usize>) -> String {
+    match baseline {
+        Some(b) if b < count => format!("{} (+{})", count, count - b),
+        Some(b) if b > count => format!("{} (-{})", count, b - count),
+        _ => count.to_string(),
+    }
+}
+
+/// First lines of `text`, within the limits of `options`
+///
+/// A line longer than the room left is cut, on a char boundary.
+fn truncate_body(text: &str, options: &MarkdownOptions) -> String {
+    let mut body = String::new();
+    for (i, line) in text.trim_end().lines().enumerate() {
+        if i >= options.max_body_lines {
+            body.push_str(crate::TRUNCATION_MARKER);
+            body.push('\n');
+            break;
+        }
+        // Room for the line, without its line break
+        let room = options.max_body_size.saturating_sub(body.len() + 1);
+        if line.len() > room {
+            let mut end = room;
+            while !line.is_char_boundary(end) {
+                end -= 1;
+            }
+            body.push_str(&line[..end]);
+            body.push_str(crate::TRUNCATION_MARKER);
+            body.push('\n');
+            break;
+        }
+        body.push_str(line);
+        body.push('\n');
+    }
+    body
+}
+
+/// Code block of `body`, with a fence longer than any backtick run in it
+fn code_block(body: &str) -> String {
+    let mut longest = 0;
+    let mut run = 0;
+    for c in body.chars() {
+        if c == '`' {
+            run += 1;
+            longest = longest.max(run);
+        } else {
+            run = 0;
+        }
+    }
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{}\n{}{}\n", fence, body, fence)
+}
+
+/// Markdown being written within a size budget
+struct Budget {
+    /// Markdown written so far
+    out: String,
+    /// Maximum size of the Markdown
+    max_size: usize,
+}
+
+/// Room kept to report what was left out
+const RESERVED: usize = 100;
+
+impl Budget {
+    /// Write `s` if it fits in the budget
+    fn push(&mut self, s: &str) -> bool {
+        if self.out.len() + s.len() + RESERVED > self.max_size {
+            return false;
+        }
+        self.out.push_str(s);
+        true
+    }
+
+    /// Write the note that `n` items of `what` were left out
+    fn omitted(&mut self, n: usize, what: &str) {
+        if n > 0 {
+            let note = format!("\n_{} {} not shown to keep the summary short._\n", n, what);
+            if self.out.len() + note.len() <= self.max_size {
+                self.out.push_str(&note);
+            }
+        }
+    }
+
+    /// Write the `items` of a list or table after its `header`, as long as
+    /// they fit
+    fn items(&mut self, header: &str, items: &[String], what: &str) {
+        if !self.push(header) {
+            self.omitted(items.len(), what);
+            return;
+        }
+        for (i, item) in items.iter().enumerate() {
+            if !self.push(item) {
+                self.omitted(items.len() - i, what);
+                return;
+            }
+        }
+    }
+}
+
+/// Names of the test cases of `suites` failing or in error
+fn failing(suites: &TestSuites) -> Vec<&str> {
+    let mut names = Vec::new();
+    for ts in suites.suites.iter() {
+        ts.visit_cases(&mut |_, tc| {
+            if tc.status.is_failure() || tc.status.is_error() {
+                names.push(tc.name.as_str());
+            }
+        });
+    }
+    names
+}
+
+/// Render `suites` as Markdown, compared to the `baseline` report if any
+pub fn render(
+    suites: &TestSuites,
+    baseline: Option<&TestSuites>,
+    options: &MarkdownOptions,
+) -> String {
+    let mut md = Budget {
+        out: String::new(),
+        max_size: options.max_size,
+    };
+    let totals = Totals::of_report(suites);
+    let before = baseline.map(Totals::of_report);
+    let icon = if totals.failed + totals.errors > 0 {
+        "❌"
+    } else {
+        "✅"
+    };
+    md.push(&format!("## {} {}\n\n", icon, escape(&options.title)));
+    md.push("| Tests | Passed | Failed | Errors | Skipped | Duration |\n");
+    md.push("|------:|-------:|-------:|-------:|--------:|---------:|\n");
+    md.push(&format!(
+        "| {} | {} | {} | {} | {} | {:.3}s |\n",
+        count_with_delta(totals.tests, before.map(|b| b.tests)),
+        count_with_delta(totals.passed, before.map(|b| b.passed)),
+        count_with_delta(totals.failed, before.map(|b| b.failed)),
+        count_with_delta(totals.errors, before.map(|b| b.errors)),
+        count_with_delta(totals.skipped, before.map(|b| b.skipped)),
+        totals.time
+    ));
+
+    if let Some(baseline) = baseline {
+        let now = failing(suites);
+        let was = failing(baseline);
+        let now_set: HashSet<&str> = now.iter().copied().collect();
+        let was_set: HashSet<&str> = was.iter().copied().collect();
+        let new: Vec<String> = now
+            .iter()
+            .filter(|n| !was_set.contains(*n))
+            .map(|n| format!("- {}\n", escape(n)))
+            .collect();
+        let fixed: Vec<String> = was
+            .iter()
+            .filter(|n| !now_set.contains(*n))
+            .map(|n| format!("- {}\n", escape(n)))
+            .collect();
+        if !new.is_empty() {
+            md.push(&format!("\n### New failures ({})\n\n", new.len()));
+            md.items("", &new, "new failures");
+        }
+        if !fixed.is_empty() {
+            md.push(&format!("\n### Fixed ({})\n\n", fixed.len()));
+            md.items("", &fixed, "fixed test cases");
+        }
+    }
+
+    let mut failures = Vec::new();
+    for ts in suites.suites.iter() {
+        ts.visit_cases(&mut |ts, tc| {
+            let (icon, message, text) = match &tc.status {
+                TestStatus::Failure(f) => ("❌", &f.message, &f.text),
+                TestStatus::Error(e) => ("💥", &e.message, &e.text),
+                _ => return,
+            };
+            let mut summary = format!("{} {}", icon, escape(&tc.name));
+            if !message.is_empty() {
+                summary.push_str(": ");
+                summary.push_str(&escape(message));
+            }
+            let mut details = format!("<details><summary>{}</summary>\n\n", summary);
+            details.push_str(&format!("Test suite: {}\n\n", escape(&ts.name)));
+            let body = truncate_body(text, options);
+            if !body.is_empty() {
+                details.push_str(&code_block(&body));
+                details.push('\n');
+            }
+            details.push_str("</details>\n");
+            failures.push(details);
+        });
+    }
+    if !failures.is_empty() {
+        md.push(&format!("\n### Failures ({})\n\n", failures.len()));
+        md.items("", &failures, "failures");
+    }
+
+    let mut flaky = Vec::new();
+    for ts in suites.suites.iter() {
+        ts.visit_cases(&mut |_, tc| {
+            if tc.reruns.is_empty() {
+                return;
+            }
+            let status = match tc.status {
+                TestStatus::Success => "passed",
+                TestStatus::Failure(_) => "failed",
+                TestStatus::Error(_) => "error",
+                TestStatus::Skipped(_) => "skipped",
+            };
+            let message = tc
+                .reruns
+                .iter()
+                .map(|r| r.message.as_str())
+                .find(|m| !m.is_empty())
+                .unwrap_or("");
+            flaky.push(format!(
+                "| {} | {} | {} | {} |\n",
+                escape_cell(&tc.name),
+                status,
+                tc.reruns.len(),
+                escape_cell(message)
+            ));
+        });
+    }
+    if !flaky.is_empty() {
+        md.push(&format!("\n### Flaky tests ({})\n\n", flaky.len()));
+        md.items(
+            "| Test | Final status | Reruns | Message |\n|:-----|:-------------|-------:|:--------|\n",
+            &flaky,
+            "flaky tests",
+        );
+    }
+
+    let rows: Vec<String> = suites
+        .suites
+        .iter()
+        .map(|ts| {
+            let t = Totals::of_suite(ts);
+            format!(
+                "| {} | {} | {} | {} | {} | {} | {:.3}s |\n",
+                escape_cell(&ts.name),
+                t.tests,
+                t.passed,
+                t.failed,
+                t.errors,
+                t.skipped,
+                t.time
+            )
+        })
+        .collect();
+    md.items(
+        "\n### Test suites\n\n| Suite | Tests | Passed | Failed | Errors | Skipped | Duration |\n|:------|------:|-------:|-------:|-------:|--------:|---------:|\n",
+        &rows,
+        "test suites",
+    );
+    md.out
+}
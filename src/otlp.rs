@@ -0,0 +1,396 @@
+//! Export a report as OpenTelemetry traces, in the OTLP/JSON format
+//!
+//! The report is a trace made of:
+//!
+//! - a root span for the [`TestSuites`]
+//! - a child span per [`TestSuite`], nested like the test suites
+//! - a leaf span per [`TestCase`]
+//!
+//! Spans start at the `timestamp` of their element and last its `time`.
+//! Timestamps without offset are in UTC. Elements without timestamp, or with
+//! one that is invalid or before the Unix epoch, start when their previous
+//! sibling ends, or with their parent for the first one, as if they ran one
+//! after the other. The report starts at [`OtlpOptions::start`] when nothing
+//! has a timestamp.
+//!
+//! Spans of failures and errors have an error status with the failure
+//! message. The status, the failure type and message, and the properties are
+//! also span attributes, named after the OpenTelemetry semantic conventions
+//! for tests where they exist.
+//!
+//! The JSON can be written to a file and imported later by a collector, for
+//! example with its `otlpjsonfile` receiver.
+//!
+//! # Example
+//!
+//! ```
+//! use junit_parser::otlp::{to_value, OtlpOptions};
+//! use std::io::Cursor;
+//!
+//! let xml = r#"
+//! <testsuite name="cart" timestamp="2024-05-01T10:00:00Z" time="1.5">
+//!   <testcase name="total" time="1.5"><failure message="expected 10"/></testcase>
+//! </testsuite>"#;
+//! let report = junit_parser::from_reader(Cursor::new(xml)).unwrap();
+//! let traces = to_value(&report, &OtlpOptions::default());
+//! let spans = &traces["resourceSpans"][0]["scopeSpans"][0]["spans"];
+//! let case = &spans[2];
+//! assert_eq!(case["name"], "total");
+//! assert_eq!(case["parentSpanId"], spans[1]["spanId"]);
+//! assert_eq!(case["startTimeUnixNano"], "1714557600000000000");
+//! assert_eq!(case["endTimeUnixNano"], "1714557601500000000");
+//! assert_eq!(case["status"]["message"], "expected 10");
+//! ```
+
+use crate::{Error, Properties, TestCase, TestStatus, TestSuite, TestSuites};
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+#[derive(Debug, Clone)]
+/// Options of the OTLP export
+pub struct OtlpOptions {
+    /// Value of the `service.name` resource attribute
+    pub service_name: String,
+    /// Start of the report when nothing in it has a timestamp, now if `None`
+    /// or before the Unix epoch
+    pub start: Option<DateTime<Utc>>,
+}
+
+impl Default for OtlpOptions {
+    fn default() -> Self {
+        Self {
+            service_name: "junit".to_string(),
+            start: None,
+        }
+    }
+}
+
+/// `SPAN_KIND_INTERNAL`
+const KIND_INTERNAL: u8 = 1;
+/// `STATUS_CODE_OK`
+const STATUS_OK: u8 = 1;
+/// `STATUS_CODE_ERROR`
+const STATUS_ERROR: u8 = 2;
+
+/// Nanoseconds since the Unix epoch of `dt`, if it is after the epoch and
+/// before 2262
+fn nanos(dt: &DateTime<Utc>) -> Option<i64> {
+    dt.timestamp()
+        .checked_mul(1_000_000_000)?
+        .checked_add(i64::from(dt.timestamp_subsec_nanos()))
+        .filter(|nanos| *nanos >= 0)
+}
+
+/// Nanoseconds since the Unix epoch of the timestamp `t`
+#[cfg(feature = "chrono")]
+fn timestamp(t: &Option<DateTime<Utc>>) -> Option<i64> {
+    t.as_ref().and_then(nanos)
+}
+
+/// Nanoseconds since the Unix epoch of the timestamp `t`, in RFC 3339 or else
+/// in UTC when it has no offset
+#[cfg(not(feature = "chrono"))]
+fn timestamp(t: &Option<String>) -> Option<i64> {
+    use chrono::{NaiveDateTime, TimeZone};
+
+    let t = t.as_deref()?.trim();
+    let dt = match DateTime::parse_from_rfc3339(t) {
+        Ok(dt) => dt.with_timezone(&Utc),
+        Err(_) => {
+            Utc.from_utc_datetime(&NaiveDateTime::parse_from_str(t, "%Y-%m-%dT%H:%M:%S%.f").ok()?)
+        }
+    };
+    nanos(&dt)
+}
+
+/// Nanoseconds of the duration `time`, in seconds, saturating to `i64::MAX`
+fn duration(time: f64) -> i64 {
+    if time > 0. {
+        (time * 1e9).round() as i64
+    } else {
+        0
+    }
+}
+
+/// String attribute `key`
+fn string_attribute(key: &str, value: &str) -> Value {
+    json!({ "key": key, "value": { "stringValue": value } })
+}
+
+/// Integer attribute `key`, OTLP/JSON encoding 64 bits integers as strings
+fn int_attribute(key: &str, value: u64) -> Value {
+    json!({ "key": key, "value": { "intValue": value.to_string() } })
+}
+
+/// Attributes of `properties`, prefixed with `junit.property.`
+fn property_attributes(attributes: &mut Vec<Value>, properties: &Properties) {
+    for (name, value) in properties.entries() {
+        attributes.push(string_attribute(&format!("junit.property.{}", name), value));
+    }
+}
+
+/// Builder of the spans of a report
+struct Trace {
+    /// Randomly seeded hasher giving the identifiers
+    random: RandomState,
+    /// Identifier of the trace, as hexadecimal
+    trace_id: String,
+    /// Number of identifiers given
+    ids: u64,
+    /// Spans of the trace, parents before their children
+    spans: Vec<Value>,
+}
+
+/// Span of a [`Trace`]
+struct Span<'a> {
+    /// Identifier of the span, as hexadecimal
+    span_id: String,
+    /// Identifier of the parent span, if any
+    parent: Option<&'a str>,
+    /// Name of the span
+    name: &'a str,
+    /// Start, in nanoseconds since the Unix epoch
+    start: i64,
+    /// End, in nanoseconds since the Unix epoch
+    end: i64,
+    /// Attributes of the span
+    attributes: Vec<Value>,
+    /// Status of the span
+    status: Value,
+}
+
+impl Span<'_> {
+    /// OTLP/JSON span, in the trace `trace_id`
+    fn to_value(&self, trace_id: &str) -> Value {
+        let mut span = json!({
+            "traceId": trace_id,
+            "spanId": self.span_id,
+            "name": self.name,
+            "kind": KIND_INTERNAL,
+            "startTimeUnixNano": self.start.to_string(),
+            "endTimeUnixNano": self.end.to_string(),
+            "attributes": self.attributes,
+            "status": self.status,
+        });
+        if let Some(parent) = self.parent {
+            span["parentSpanId"] = json!(parent);
+        }
+        span
+    }
+}
+
+impl Trace {
+    /// New trace without spans, with a random identifier
+    fn new() -> Self {
+        let random = RandomState::new();
+        let mut trace = Self {
+            random,
+            trace_id: String::new(),
+            ids: 0,
+            spans: Vec::new(),
+        };
+        trace.trace_id = format!("{:016x}{:016x}", trace.random_u64(), trace.random_u64());
+        trace
+    }
+
+    /// Next random 64 bits identifier, never 0
+    fn random_u64(&mut self) -> u64 {
+        self.ids += 1;
+        let mut hasher = self.random.build_hasher();
+        hasher.write_u64(self.ids);
+        hasher.finish().max(1)
+    }
+
+    /// New span identifier, as hexadecimal
+    fn span_id(&mut self) -> String {
+        format!("{:016x}", self.random_u64())
+    }
+
+    /// Add the span of the test case `tc` starting at `start` if it has no
+    /// timestamp, returns its end
+    fn case(&mut self, parent: &str, start: i64, tc: &TestCase) -> i64 {
+        let start = timestamp(&tc.timestamp).unwrap_or(start);
+        let end = start.saturating_add(duration(tc.time));
+
+        let mut attributes = vec![string_attribute("test.case.name", &tc.name)];
+        let failure = match &tc.status {
+            TestStatus::Failure(f) => Some((&f.failure_type, &f.message)),
+            TestStatus::Error(e) => Some((&e.error_type, &e.message)),
+            TestStatus::Success | TestStatus::Skipped(_) => None,
+        };
+        match &tc.status {
+            TestStatus::Success => {
+                attributes.push(string_attribute("test.case.result.status", "pass"))
+            }
+            TestStatus::Failure(_) | TestStatus::Error(_) => {
+                attributes.push(string_attribute("test.case.result.status", "fail"))
+            }
+            TestStatus::Skipped(_) => {}
+        }
+        attributes.push(string_attribute("junit.status", tc.status.as_str()));
+        if let Some(classname) = &tc.classname {
+            attributes.push(string_attribute("junit.classname", classname));
+        }
+        if let Some(file) = &tc.file {
+            attributes.push(string_attribute("code.filepath", file));
+        }
+        if let Some(line) = tc.line {
+            attributes.push(int_attribute("code.lineno", line));
+        }
+        if let Some((failure_type, message)) = failure {
+            if !failure_type.is_empty() {
+                attributes.push(string_attribute("junit.failure.type", failure_type));
+            }
+            if !message.is_empty() {
+                attributes.push(string_attribute("junit.failure.message", message));
+            }
+        }
+        if !tc.reruns.is_empty() {
+            attributes.push(int_attribute("junit.reruns", tc.reruns.len() as u64));
+        }
+        property_attributes(&mut attributes, &tc.properties);
+
+        let status = match failure {
+            Some((_, message)) => json!({ "code": STATUS_ERROR, "message": message }),
+            None if tc.status.is_success() => json!({ "code": STATUS_OK }),
+            None => json!({}),
+        };
+        let span = Span {
+            span_id: self.span_id(),
+            parent: Some(parent),
+            name: &tc.original_name,
+            start,
+            end,
+            attributes,
+            status,
+        };
+        self.spans.push(span.to_value(&self.trace_id));
+        end
+    }
+
+    /// Add the spans of the test suite `ts` starting at `start` if it has no
+    /// timestamp, returns its end and whether a test case failed
+    fn suite(&mut self, parent: &str, start: i64, ts: &TestSuite) -> (i64, bool) {
+        let start = timestamp(&ts.timestamp).unwrap_or(start);
+        let mut end = start.saturating_add(duration(ts.time));
+        let span_id = self.span_id();
+        // Reserve the place of the span before the ones of its children
+        let index = self.spans.len();
+        self.spans.push(Value::Null);
+
+        let mut failed = false;
+        let mut cursor = start;
+        for tc in ts.cases.iter() {
+            cursor = self.case(&span_id, cursor, tc);
+            end = end.max(cursor);
+            failed |= matches!(tc.status, TestStatus::Failure(_) | TestStatus::Error(_));
+        }
+        for nested in ts.suites.iter() {
+            let (nested_end, nested_failed) = self.suite(&span_id, cursor, nested);
+            cursor = nested_end;
+            end = end.max(cursor);
+            failed |= nested_failed;
+        }
+
+        let mut attributes = vec![
+            string_attribute("test.suite.name", &ts.name),
+            string_attribute(
+                "test.suite.run.status",
+                if failed { "failure" } else { "success" },
+            ),
+        ];
+        if let Some(hostname) = &ts.hostname {
+            attributes.push(string_attribute("host.name", hostname));
+        }
+        property_attributes(&mut attributes, &ts.properties);
+        let status = json!({ "code": if failed { STATUS_ERROR } else { STATUS_OK } });
+        let span = Span {
+            span_id,
+            parent: Some(parent),
+            name: &ts.name,
+            start,
+            end,
+            attributes,
+            status,
+        };
+        self.spans[index] = span.to_value(&self.trace_id);
+        (end, failed)
+    }
+}
+
+/// Start of `suites`: its timestamp, or else the earliest timestamp of its
+/// test suites, or else the start of `options`, or else now
+fn start(suites: &TestSuites, options: &OtlpOptions) -> i64 {
+    if let Some(start) = timestamp(&suites.timestamp) {
+        return start;
+    }
+    suites
+        .suites
+        .iter()
+        .filter_map(|ts| timestamp(&ts.timestamp))
+        .min()
+        .or_else(|| options.start.as_ref().and_then(nanos))
+        .or_else(|| nanos(&Utc::now()))
+        .unwrap_or(0)
+}
+
+/// OTLP/JSON traces of `suites`
+pub fn to_value(suites: &TestSuites, options: &OtlpOptions) -> Value {
+    let mut trace = Trace::new();
+    let start = start(suites, options);
+    let mut end = start.saturating_add(duration(suites.time));
+    let span_id = trace.span_id();
+    trace.spans.push(Value::Null);
+
+    let mut failed = false;
+    let mut cursor = start;
+    for ts in suites.suites.iter() {
+        let (suite_end, suite_failed) = trace.suite(&span_id, cursor, ts);
+        cursor = suite_end;
+        end = end.max(cursor);
+        failed |= suite_failed;
+    }
+
+    let name = if suites.name.is_empty() {
+        "junit"
+    } else {
+        suites.name.as_str()
+    };
+    let attributes = vec![string_attribute(
+        "test.suite.run.status",
+        if failed { "failure" } else { "success" },
+    )];
+    let status = json!({ "code": if failed { STATUS_ERROR } else { STATUS_OK } });
+    let span = Span {
+        span_id,
+        parent: None,
+        name,
+        start,
+        end,
+        attributes,
+        status,
+    };
+    trace.spans[0] = span.to_value(&trace.trace_id);
+
+    json!({
+        "resourceSpans": [{
+            "resource": {
+                "attributes": [string_attribute("service.name", &options.service_name)]
+            },
+            "scopeSpans": [{
+                "scope": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION")
+                },
+                "spans": trace.spans
+            }]
+        }]
+    })
+}
+
+/// OTLP/JSON traces of `suites`, as JSON
+pub fn to_json(suites: &TestSuites, options: &OtlpOptions) -> Result<String, Error> {
+    Ok(serde_json::to_string(&to_value(suites, options))?)
+}
@@ -0,0 +1,97 @@
+//! Patterns matching the names of test cases, test suites or classes
+//!
+//! A [`Pattern`] is parsed from a string: `glob:` introduces a glob pattern,
+//! `regex:` a regular expression, anything else is an exact name.
+//!
+//! # Example
+//!
+//! ```
+//! use junit_parser::pattern::Pattern;
+//!
+//! let pattern: Pattern = "glob:com.acme.*Test".parse().unwrap();
+//! assert!(pattern.matches("com.acme.CartTest"));
+//! let pattern: Pattern = "regex:^com\\.acme\\.".parse().unwrap();
+//! assert!(pattern.matches("com.acme.CartTest"));
+//! ```
+
+use crate::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Prefix of glob patterns
+const GLOB_PREFIX: &str = "glob:";
+/// Prefix of regular expressions
+const REGEX_PREFIX: &str = "regex:";
+
+#[derive(Debug, Clone)]
+/// Pattern matching names
+pub enum Pattern {
+    /// Exact name
+    Exact(String),
+    /// Glob pattern, `*` matches any sequence of characters
+    Glob(glob::Pattern),
+    /// Regular expression, matching anywhere in the name unless anchored
+    Regex(regex::Regex),
+}
+
+impl Pattern {
+    /// Whether `name` matches the pattern
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            Pattern::Exact(exact) => exact == name,
+            Pattern::Glob(glob) => glob.matches(name),
+            Pattern::Regex(regex) => regex.is_match(name),
+        }
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = Error;
+
+    /// Parse a glob pattern prefixed with `glob:`, a regular expression
+    /// prefixed with `regex:`, or else an exact name
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(glob) = s.strip_prefix(GLOB_PREFIX) {
+            Ok(Pattern::Glob(glob::Pattern::new(glob)?))
+        } else if let Some(regex) = s.strip_prefix(REGEX_PREFIX) {
+            Ok(Pattern::Regex(regex::Regex::new(regex)?))
+        } else {
+            Ok(Pattern::Exact(s.to_string()))
+        }
+    }
+}
+
+impl PartialEq for Pattern {
+    /// Whether both patterns are of the same kind and have the same source
+    fn eq(&self, other: &Self) -> bool {
+        self.to_string() == other.to_string()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Pattern {
+    /// Serialize the pattern as formatted by [`fmt::Display`]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Pattern {
+    /// Deserialize the pattern from a string parsed by [`Pattern::from_str`]
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl fmt::Display for Pattern {
+    /// Format the pattern as parsed by [`Pattern::from_str`]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pattern::Exact(exact) => f.write_str(exact),
+            Pattern::Glob(glob) => write!(f, "{}{}", GLOB_PREFIX, glob),
+            Pattern::Regex(regex) => write!(f, "{}{}", REGEX_PREFIX, regex),
+        }
+    }
+}
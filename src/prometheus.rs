@@ -0,0 +1,268 @@
+//! Export metrics of a report in the Prometheus text format
+//!
+//! The metrics are meant for the textfile collector of the node exporter:
+//!
+//! - `junit_tests_total{suite,status}`: number of test cases per test suite
+//!   and status, `success`, `failure`, `error` or `skipped`
+//! - `junit_suite_duration_seconds{suite}`: duration of each test suite
+//! - `junit_flaky_tests_total{suite}`: number of test cases passing after
+//!   reruns per test suite
+//! - `junit_reruns_total{suite,kind}`: number of rerun and flaky entries per
+//!   test suite and kind, `flaky_failure`, `flaky_error`, `rerun_failure` or
+//!   `rerun_error`
+//! - `junit_case_duration_seconds{suite,case}`: duration of each test case,
+//!   only with [`PrometheusOptions::case_durations`] as there is a series per
+//!   test case
+//!
+//! Test cases count in the test suite directly containing them, nested test
+//! suites having their own series. Test suites, or test cases of a test
+//! suite, with the same name are summed up.
+//!
+//! # Example
+//!
+//! ```
+//! use junit_parser::prometheus::{render, PrometheusOptions};
+//! use std::io::Cursor;
+//!
+//! let xml = r#"<testsuite name="cart" time="1.5"><testcase name="a"/><testcase name="b"><failure/></testcase></testsuite>"#;
+//! let report = junit_parser::from_reader(Cursor::new(xml)).unwrap();
+//! let options = PrometheusOptions {
+//!     labels: vec![("branch".to_string(), "main".to_string())],
+//!     ..Default::default()
+//! };
+//! let metrics = render(&report, &options);
+//! assert!(metrics.contains("junit_tests_total{branch=\"main\",suite=\"cart\",status=\"failure\"} 1\n"));
+//! assert!(metrics.contains("junit_suite_duration_seconds{branch=\"main\",suite=\"cart\"} 1.5\n"));
+//! ```
+
+use crate::{RerunOrFlakyKind, TestSuite, TestSuites, STATUS_NAMES};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// Exposition format of the metrics
+pub enum Format {
+    /// Prometheus text format
+    #[default]
+    Prometheus,
+    /// OpenMetrics text format, ending with `# EOF`
+    OpenMetrics,
+}
+
+#[derive(Debug, Clone)]
+/// Options of the Prometheus export, see [`render`]
+pub struct PrometheusOptions {
+    /// Exposition format
+    pub format: Format,
+    /// Prefix of the metric names
+    pub prefix: String,
+    /// Labels added to every series, such as the job or the branch
+    ///
+    /// Characters not allowed in label names are replaced with `_`.
+    pub labels: Vec<(String, String)>,
+    /// Whether to export the duration of each test case
+    pub case_durations: bool,
+}
+
+impl Default for PrometheusOptions {
+    fn default() -> Self {
+        Self {
+            format: Format::default(),
+            prefix: "junit".to_string(),
+            labels: Vec::new(),
+            case_durations: false,
+        }
+    }
+}
+
+/// Kinds of rerun and flaky entries, in the order of the series
+const KINDS: [&str; 4] = [
+    "flaky_failure",
+    "flaky_error",
+    "rerun_failure",
+    "rerun_error",
+];
+
+/// `name` with the characters not allowed in metric and label names replaced
+/// with `_`
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .enumerate()
+        .map(|(i, c)| {
+            if c.is_ascii_alphabetic() || c == '_' || (i > 0 && c.is_ascii_digit()) {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Escape `s` as the value of a label
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// `value` as a sample value
+fn value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0. { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Metrics of a test suite
+#[derive(Default)]
+struct SuiteMetrics {
+    /// Number of test cases per status, in the order of [`STATUS_NAMES`]
+    tests: [u64; 4],
+    time: f64,
+    flaky: u64,
+    /// Number of rerun and flaky entries per kind, in the order of [`KINDS`]
+    reruns: [u64; 4],
+    /// Duration per test case
+    cases: BTreeMap<String, f64>,
+}
+
+/// Writer of the metric families
+struct Writer<'a> {
+    options: &'a PrometheusOptions,
+    /// Labels of every series, formatted
+    labels: String,
+    out: String,
+}
+
+impl Writer<'_> {
+    /// Write the `HELP` and `TYPE` lines of the family `name`, returns the name
+    /// of its samples
+    fn family(&mut self, name: &str, kind: &str, help: &str) -> String {
+        let name = format!("{}_{}", sanitize(&self.options.prefix), name);
+        // OpenMetrics names counter families without their `_total` suffix
+        let family = match (self.options.format, kind) {
+            (Format::OpenMetrics, "counter") => name.trim_end_matches("_total"),
+            _ => name.as_str(),
+        };
+        let _ = writeln!(self.out, "# HELP {} {}", family, help);
+        let _ = writeln!(self.out, "# TYPE {} {}", family, kind);
+        name
+    }
+
+    /// Write a sample of `name` with the labels of every series and `labels`
+    fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: &str) {
+        let mut all = self.labels.clone();
+        for (key, val) in labels {
+            if !all.is_empty() {
+                all.push(',');
+            }
+            let _ = write!(all, "{}=\"{}\"", key, escape(val));
+        }
+        let _ = writeln!(self.out, "{}{{{}}} {}", name, all, value);
+    }
+}
+
+/// Add the metrics of `ts` and its nested test suites to `suites`
+fn collect(suites: &mut BTreeMap<String, SuiteMetrics>, ts: &TestSuite) {
+    let metrics = suites.entry(ts.name.clone()).or_default();
+    metrics.time += ts.time;
+    for tc in ts.cases.iter() {
+        metrics.tests[tc.status.index()] += 1;
+        if tc.status.is_success() && !tc.reruns.is_empty() {
+            metrics.flaky += 1;
+        }
+        for rerun in tc.reruns.iter() {
+            let kind = match rerun.kind {
+                RerunOrFlakyKind::FlakyFailure => 0,
+                RerunOrFlakyKind::FlakyError => 1,
+                RerunOrFlakyKind::RerunFailure => 2,
+                RerunOrFlakyKind::RerunError => 3,
+            };
+            metrics.reruns[kind] += 1;
+        }
+        *metrics.cases.entry(tc.name.clone()).or_default() += tc.time;
+    }
+    for nested in ts.suites.iter() {
+        collect(suites, nested);
+    }
+}
+
+/// Render the metrics of `suites` in the text format
+pub fn render(suites: &TestSuites, options: &PrometheusOptions) -> String {
+    let mut metrics = BTreeMap::new();
+    for ts in suites.suites.iter() {
+        collect(&mut metrics, ts);
+    }
+
+    let labels = options
+        .labels
+        .iter()
+        .map(|(k, v)| format!("{}=\"{}\"", sanitize(k), escape(v)))
+        .collect::<Vec<_>>()
+        .join(",");
+    let mut w = Writer {
+        options,
+        labels,
+        out: String::new(),
+    };
+
+    let name = w.family("tests_total", "counter", "Number of test cases per status");
+    for (suite, m) in metrics.iter() {
+        for (status, count) in STATUS_NAMES.iter().zip(m.tests.iter()) {
+            w.sample(
+                &name,
+                &[("suite", suite), ("status", status)],
+                &count.to_string(),
+            );
+        }
+    }
+    let name = w.family(
+        "suite_duration_seconds",
+        "gauge",
+        "Duration of the test suite",
+    );
+    for (suite, m) in metrics.iter() {
+        w.sample(&name, &[("suite", suite)], &value(m.time));
+    }
+    let name = w.family(
+        "flaky_tests_total",
+        "counter",
+        "Number of test cases passing after reruns",
+    );
+    for (suite, m) in metrics.iter() {
+        w.sample(&name, &[("suite", suite)], &m.flaky.to_string());
+    }
+    let name = w.family(
+        "reruns_total",
+        "counter",
+        "Number of rerun and flaky entries per kind",
+    );
+    for (suite, m) in metrics.iter() {
+        for (kind, count) in KINDS.iter().zip(m.reruns.iter()) {
+            w.sample(
+                &name,
+                &[("suite", suite), ("kind", kind)],
+                &count.to_string(),
+            );
+        }
+    }
+    if options.case_durations {
+        let name = w.family(
+            "case_duration_seconds",
+            "gauge",
+            "Duration of the test case",
+        );
+        for (suite, m) in metrics.iter() {
+            for (case, time) in m.cases.iter() {
+                w.sample(&name, &[("suite", suite), ("case", case)], &value(*time));
+            }
+        }
+    }
+    if options.format == Format::OpenMetrics {
+        w.out.push_str("# EOF\n");
+    }
+    w.out
+}
@@ -0,0 +1,278 @@
+//! Apply a list of known failures to a report
+//!
+//! Each [`Entry`] of a [`Quarantine`] list matches the names of test cases,
+//! see [`TestCase::name`](crate::TestCase::name), and may expire at a given
+//! date or refer to the ticket tracking the failure. The failing test cases
+//! and the test cases in error matched by an entry not expired yet are
+//! quarantined: they are reported, and with [`Action::Skip`] rewritten as
+//! skipped so that they no longer fail the build.
+//!
+//! Entries match names with a [`Pattern`]: `glob:` introduces a glob
+//! pattern, `regex:` a regular expression, anything else is an exact name.
+//!
+//! With the `serde` feature, a list can be deserialized from entries such as
+//! `{"test": "glob:com.acme.*", "expires": "2026-12-31", "ticket": "ACME-42"}`.
+//!
+//! # Example
+//!
+//! ```
+//! use junit_parser::quarantine::{Action, Entry, NaiveDate, Quarantine};
+//! use std::io::Cursor;
+//!
+//! let xml = r#"<testsuite><testcase name="a"><failure/></testcase><testcase name="b"/></testsuite>"#;
+//! let mut report = junit_parser::from_reader(Cursor::new(xml)).unwrap();
+//! let mut entry = Entry::new("regex:^a$".parse().unwrap());
+//! entry.ticket = Some("ACME-42".to_string());
+//! let quarantine = Quarantine {
+//!     entries: vec![entry],
+//! };
+//! let today = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+//! let applied = quarantine.apply(&mut report, today, Action::Skip);
+//! assert_eq!(applied.quarantined[0].name, "a");
+//! assert!(report.suites[0].cases[0].status.is_skipped());
+//! assert_eq!(report.suites[0].failures, 0);
+//! ```
+
+/// Pattern matching the names of test cases
+pub use crate::pattern::Pattern;
+#[cfg(feature = "serde")]
+use crate::Error;
+use crate::{TestSkipped, TestStatus, TestSuite, TestSuites};
+/// Date of expiry of an [`Entry`]
+pub use chrono::NaiveDate;
+
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "EntrySpec"))]
+#[derive(Debug, Clone)]
+/// Entry of a [`Quarantine`] list
+pub struct Entry {
+    /// Pattern matching the names of the quarantined test cases
+    pub pattern: Pattern,
+    /// Last day the entry applies, if any
+    pub expires: Option<NaiveDate>,
+    /// Reference of the ticket tracking the failure
+    pub ticket: Option<String>,
+    /// Why the test cases are quarantined
+    pub reason: Option<String>,
+}
+
+impl Entry {
+    /// New entry matching `pattern`, without expiry date
+    pub fn new(pattern: Pattern) -> Self {
+        Self {
+            pattern,
+            expires: None,
+            ticket: None,
+            reason: None,
+        }
+    }
+
+    /// Whether the entry no longer applies on `today`
+    pub fn is_expired(&self, today: NaiveDate) -> bool {
+        self.expires.map_or(false, |expires| expires < today)
+    }
+
+    /// Message of the [`TestSkipped`] replacing a quarantined failure
+    fn message(&self) -> String {
+        let mut message = "quarantined".to_string();
+        if let Some(ticket) = &self.ticket {
+            message.push_str(" (");
+            message.push_str(ticket);
+            message.push(')');
+        }
+        if let Some(reason) = &self.reason {
+            message.push_str(": ");
+            message.push_str(reason);
+        }
+        message
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+/// Serialized form of an [`Entry`]
+struct EntrySpec {
+    /// [`Pattern`] as parsed by [`Pattern::from_str`](std::str::FromStr)
+    test: String,
+    /// Expiry date, as `YYYY-MM-DD`
+    expires: Option<String>,
+    /// Reference of the ticket
+    ticket: Option<String>,
+    /// Reason of the quarantine
+    reason: Option<String>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<EntrySpec> for Entry {
+    type Error = Error;
+
+    fn try_from(spec: EntrySpec) -> Result<Self, Self::Error> {
+        let expires = match spec.expires {
+            Some(date) => Some(NaiveDate::parse_from_str(&date, "%Y-%m-%d")?),
+            None => None,
+        };
+        Ok(Entry {
+            pattern: spec.test.parse()?,
+            expires,
+            ticket: spec.ticket,
+            reason: spec.reason,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// What to do with the quarantined test cases
+pub enum Action {
+    /// Rewrite them as skipped, with `quarantined` as `type` and a message
+    /// giving the ticket and the reason of the entry
+    #[default]
+    Skip,
+    /// Leave the report untouched, only list them
+    Report,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Default)]
+/// A failing test case matched by an entry, see [`Quarantine::apply`]
+pub struct QuarantinedCase {
+    /// Name of the test case
+    pub name: String,
+    /// Name of the test suite of the test case
+    pub suite: String,
+    /// Index of the matching entry in [`Quarantine::entries`]
+    pub entry: usize,
+    /// Ticket of the matching entry
+    pub ticket: Option<String>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Outcome of [`Quarantine::apply`]
+pub struct Applied {
+    /// The quarantined test cases
+    pub quarantined: Vec<QuarantinedCase>,
+    /// Indexes of the expired entries, which were not applied
+    pub expired: Vec<usize>,
+    /// Indexes of the entries not expired which matched no failing test
+    /// case, and may be removed
+    pub unused: Vec<usize>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+#[derive(Debug, Clone, Default)]
+/// List of known failures
+pub struct Quarantine {
+    /// The entries, the first one matching a test case applies
+    pub entries: Vec<Entry>,
+}
+
+/// Number of failures and errors quarantined in a test suite
+#[derive(Default, Clone, Copy)]
+struct Removed {
+    /// Failures rewritten as skipped
+    failures: u64,
+    /// Errors rewritten as skipped
+    errors: u64,
+}
+
+impl Removed {
+    /// Update the counters of a test suite or of a report
+    fn update(&self, failures: &mut u64, errors: &mut u64, skipped: &mut u64) {
+        *failures = failures.saturating_sub(self.failures);
+        *errors = errors.saturating_sub(self.errors);
+        *skipped += self.failures + self.errors;
+    }
+}
+
+impl Quarantine {
+    /// Quarantine the failing test cases and the test cases in error of
+    /// `suites` matched by an entry not expired on `today`
+    ///
+    /// With [`Action::Skip`], the counters of the test suites and of the
+    /// report are updated accordingly.
+    pub fn apply(&self, suites: &mut TestSuites, today: NaiveDate, action: Action) -> Applied {
+        let active: Vec<bool> = self.entries.iter().map(|e| !e.is_expired(today)).collect();
+        let mut used = vec![false; self.entries.len()];
+        let mut applied = Applied::default();
+        let mut removed = Removed::default();
+        for ts in suites.suites.iter_mut() {
+            let r = self.apply_suite(ts, &active, &mut used, action, &mut applied);
+            removed.failures += r.failures;
+            removed.errors += r.errors;
+        }
+        removed.update(
+            &mut suites.failures,
+            &mut suites.errors,
+            &mut suites.skipped,
+        );
+        for (i, active) in active.iter().enumerate() {
+            if !active {
+                applied.expired.push(i);
+            } else if !used[i] {
+                applied.unused.push(i);
+            }
+        }
+        applied
+    }
+
+    /// Apply the active entries to the test suite `ts` and its nested test
+    /// suites
+    fn apply_suite(
+        &self,
+        ts: &mut TestSuite,
+        active: &[bool],
+        used: &mut [bool],
+        action: Action,
+        applied: &mut Applied,
+    ) -> Removed {
+        let mut removed = Removed::default();
+        for nested in ts.suites.iter_mut() {
+            let r = self.apply_suite(nested, active, used, action, applied);
+            removed.failures += r.failures;
+            removed.errors += r.errors;
+        }
+        for tc in ts.cases.iter_mut() {
+            if !matches!(tc.status, TestStatus::Failure(_) | TestStatus::Error(_)) {
+                continue;
+            }
+            let found = self
+                .entries
+                .iter()
+                .enumerate()
+                .find(|(i, e)| active[*i] && e.pattern.matches(&tc.name));
+            let (i, entry) = match found {
+                Some(found) => found,
+                None => continue,
+            };
+            used[i] = true;
+            applied.quarantined.push(QuarantinedCase {
+                name: tc.name.clone(),
+                suite: ts.name.clone(),
+                entry: i,
+                ticket: entry.ticket.clone(),
+            });
+            if action == Action::Skip {
+                let text = match &tc.status {
+                    TestStatus::Failure(f) => {
+                        removed.failures += 1;
+                        f.text.clone()
+                    }
+                    TestStatus::Error(e) => {
+                        removed.errors += 1;
+                        e.text.clone()
+                    }
+                    _ => String::new(),
+                };
+                tc.status = TestStatus::Skipped(TestSkipped {
+                    message: entry.message(),
+                    text,
+                    skipped_type: "quarantined".to_string(),
+                });
+            }
+        }
+        removed.update(&mut ts.failures, &mut ts.errors, &mut ts.skipped);
+        removed
+    }
+}
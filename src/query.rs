@@ -0,0 +1,536 @@
+//! Select test cases with a filter expression
+//!
+//! A [`Filter`] is built programmatically or parsed from an expression made
+//! of comparisons combined with `and`, `or`, `not` and parentheses:
+//!
+//! ```text
+//! status = failure and suite ~ "com.acme.payments.*" and time > 2 and property.team = core
+//! ```
+//!
+//! The fields of a comparison are:
+//!
+//! - `name`: [`TestCase::name`], prefixed with the class name or group
+//! - `classname`, `group`: attributes of the test case
+//! - `suite`: name of the test suite of the test case
+//! - `file`: file of the test case, or else of its test suite
+//! - `type`, `message`: type and message of the failure, error or skipped
+//!   tag of the test case
+//! - `property.<name>`: property of the test case, or else of its test suite,
+//!   found only with the `properties_as_hashmap` or `properties_as_vector`
+//!   features
+//! - `status`: `success`, `failure`, `error` or `skipped`
+//! - `time`: duration of the test case, in seconds
+//!
+//! Text fields are compared with `=` and `!=` for exact values, `~` and `!~`
+//! for glob patterns, `=~` and `!=~` for regular expressions. `time` is
+//! compared with `<`, `<=`, `>`, `>=`, `=` and `!=`. Values are either double
+//! quoted, with `\"` and `\\` escapes, or bare words.
+//!
+//! # Example
+//!
+//! ```
+//! use junit_parser::query::{prune, select, Filter};
+//! use std::io::Cursor;
+//!
+//! let xml = r#"
+//! <testsuites>
+//!   <testsuite name="com.acme.payments.CheckoutTest">
+//!     <testcase name="pay" time="3"><failure type="Timeout"/></testcase>
+//!     <testcase name="refund" time="1"><failure/></testcase>
+//!   </testsuite>
+//!   <testsuite name="com.acme.LoginTest">
+//!     <testcase name="login" time="5"><failure/></testcase>
+//!   </testsuite>
+//! </testsuites>
+//! "#;
+//! let report = junit_parser::from_reader(Cursor::new(xml)).unwrap();
+//! let filter: Filter = r#"status = failure and suite ~ "com.acme.payments.*" and time > 2"#
+//!     .parse()
+//!     .unwrap();
+//! let selected = select(&report, &filter);
+//! assert_eq!(selected.len(), 1);
+//! assert_eq!(selected[0].1.name, "pay");
+//!
+//! let pruned = prune(report, &filter);
+//! assert_eq!(pruned.suites.len(), 1);
+//! assert_eq!(pruned.failures, 1);
+//! ```
+
+use crate::pattern::Pattern;
+use crate::{Error, TestCase, TestStatus, TestSuite, TestSuites, STATUS_NAMES};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Status of a test case, see [`TestStatus`]
+pub enum Status {
+    /// [`TestStatus::Success`]
+    Success,
+    /// [`TestStatus::Failure`]
+    Failure,
+    /// [`TestStatus::Error`]
+    Error,
+    /// [`TestStatus::Skipped`]
+    Skipped,
+}
+
+/// Statuses, in the order of [`STATUS_NAMES`]
+const STATUSES: [Status; 4] = [
+    Status::Success,
+    Status::Failure,
+    Status::Error,
+    Status::Skipped,
+];
+
+impl Status {
+    /// Whether `status` is this one
+    fn is(&self, status: &TestStatus) -> bool {
+        matches!(
+            (self, status),
+            (Status::Success, TestStatus::Success)
+                | (Status::Failure, TestStatus::Failure(_))
+                | (Status::Error, TestStatus::Error(_))
+                | (Status::Skipped, TestStatus::Skipped(_))
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Comparison of the duration of a test case
+pub enum Comparison {
+    /// `<`
+    Less,
+    /// `<=`
+    LessOrEqual,
+    /// `>`
+    Greater,
+    /// `>=`
+    GreaterOrEqual,
+    /// `=`
+    Equal,
+    /// `!=`
+    NotEqual,
+}
+
+#[derive(Debug, Clone)]
+/// Filter selecting test cases
+///
+/// Filters on optional fields, such as [`Filter::ClassName`], never select
+/// test cases without these fields.
+pub enum Filter {
+    /// Test cases selected by all the filters
+    And(Vec<Filter>),
+    /// Test cases selected by any of the filters
+    Or(Vec<Filter>),
+    /// Test cases not selected by the filter
+    Not(Box<Filter>),
+    /// Test cases whose [`TestCase::name`] matches
+    Name(Pattern),
+    /// Test cases whose `classname` matches
+    ClassName(Pattern),
+    /// Test cases whose `group` matches
+    Group(Pattern),
+    /// Test cases whose test suite name matches
+    Suite(Pattern),
+    /// Test cases whose file, or else the file of their test suite, matches
+    File(Pattern),
+    /// Test cases whose failure, error or skipped type matches
+    Type(Pattern),
+    /// Test cases whose failure, error or skipped message matches
+    Message(Pattern),
+    /// Test cases with the property whose value matches, looked up in the
+    /// test case then in its test suite
+    Property(String, Pattern),
+    /// Test cases with the status
+    Status(Status),
+    /// Test cases whose duration compares to the value
+    Time(Comparison, f64),
+}
+
+/// Type and message of the failure, error or skipped tag of `tc`
+fn details(tc: &TestCase) -> Option<(&str, &str)> {
+    match &tc.status {
+        TestStatus::Success => None,
+        TestStatus::Failure(f) => Some((&f.failure_type, &f.message)),
+        TestStatus::Error(e) => Some((&e.error_type, &e.message)),
+        TestStatus::Skipped(s) => Some((&s.skipped_type, &s.message)),
+    }
+}
+
+/// Whether `value` is set and matches `pattern`
+fn matches(pattern: &Pattern, value: Option<&str>) -> bool {
+    value.map_or(false, |value| pattern.matches(value))
+}
+
+impl Filter {
+    /// Whether the test case `tc` of the test suite `ts` is selected
+    pub fn matches(&self, ts: &TestSuite, tc: &TestCase) -> bool {
+        match self {
+            Filter::And(filters) => filters.iter().all(|f| f.matches(ts, tc)),
+            Filter::Or(filters) => filters.iter().any(|f| f.matches(ts, tc)),
+            Filter::Not(filter) => !filter.matches(ts, tc),
+            Filter::Name(p) => p.matches(&tc.name),
+            Filter::ClassName(p) => matches(p, tc.classname.as_deref()),
+            Filter::Group(p) => matches(p, tc.group.as_deref()),
+            Filter::Suite(p) => p.matches(&ts.name),
+            Filter::File(p) => matches(p, tc.file.as_deref().or(ts.file.as_deref())),
+            Filter::Type(p) => matches(p, details(tc).map(|d| d.0)),
+            Filter::Message(p) => matches(p, details(tc).map(|d| d.1)),
+            Filter::Property(name, p) => matches(
+                p,
+                tc.properties.get(name).or_else(|| ts.properties.get(name)),
+            ),
+            Filter::Status(status) => status.is(&tc.status),
+            Filter::Time(cmp, value) => match cmp {
+                Comparison::Less => tc.time < *value,
+                Comparison::LessOrEqual => tc.time <= *value,
+                Comparison::Greater => tc.time > *value,
+                Comparison::GreaterOrEqual => tc.time >= *value,
+                Comparison::Equal => tc.time == *value,
+                Comparison::NotEqual => tc.time != *value,
+            },
+        }
+    }
+}
+
+/// The test cases of `suites` selected by `filter`, along with their test
+/// suites, nested ones included
+pub fn select<'a>(suites: &'a TestSuites, filter: &Filter) -> Vec<(&'a TestSuite, &'a TestCase)> {
+    let mut selected = Vec::new();
+    for ts in suites.suites.iter() {
+        ts.visit_cases(&mut |ts, tc| {
+            if filter.matches(ts, tc) {
+                selected.push((ts, tc));
+            }
+        });
+    }
+    selected
+}
+
+/// Keep the test cases of `ts` selected by `filter`, and the test suites
+/// with selected test cases
+fn prune_suite(mut ts: TestSuite, filter: &Filter) -> Option<TestSuite> {
+    let cases = std::mem::take(&mut ts.cases);
+    let suites = std::mem::take(&mut ts.suites);
+    ts.suites = suites
+        .into_iter()
+        .filter_map(|nested| prune_suite(nested, filter))
+        .collect();
+    let cases: Vec<TestCase> = cases
+        .into_iter()
+        .filter(|tc| filter.matches(&ts, tc))
+        .collect();
+    ts.cases = cases;
+    if ts.cases.is_empty() && ts.suites.is_empty() {
+        return None;
+    }
+    ts.tests = ts.cases.len() as u64;
+    ts.failures = ts.cases.iter().filter(|tc| tc.status.is_failure()).count() as u64;
+    ts.errors = ts.cases.iter().filter(|tc| tc.status.is_error()).count() as u64;
+    ts.skipped = ts.cases.iter().filter(|tc| tc.status.is_skipped()).count() as u64;
+    for nested in ts.suites.iter() {
+        ts.tests += nested.tests;
+        ts.failures += nested.failures;
+        ts.errors += nested.errors;
+        ts.skipped += nested.skipped;
+    }
+    Some(ts)
+}
+
+/// Keep only the test cases of `suites` selected by `filter`, and the test
+/// suites with selected test cases
+///
+/// The `tests`, `failures`, `errors` and `skipped` counters of the report and
+/// of the remaining test suites are recomputed, their `time` is left as is.
+pub fn prune(mut suites: TestSuites, filter: &Filter) -> TestSuites {
+    suites.suites = std::mem::take(&mut suites.suites)
+        .into_iter()
+        .filter_map(|ts| prune_suite(ts, filter))
+        .collect();
+    suites.tests = suites.suites.iter().map(|ts| ts.tests).sum();
+    suites.failures = suites.suites.iter().map(|ts| ts.failures).sum();
+    suites.errors = suites.suites.iter().map(|ts| ts.errors).sum();
+    suites.skipped = suites.suites.iter().map(|ts| ts.skipped).sum();
+    suites
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Token of a filter expression
+enum Token {
+    /// `(`
+    Open,
+    /// `)`
+    Close,
+    /// Comparison operator
+    Operator(String),
+    /// Bare word
+    Word(String),
+    /// Double quoted string
+    Quoted(String),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Open => f.write_str("`(`"),
+            Token::Close => f.write_str("`)`"),
+            Token::Operator(s) | Token::Word(s) => write!(f, "`{}`", s),
+            Token::Quoted(s) => write!(f, "{:?}", s),
+        }
+    }
+}
+
+/// Characters of comparison operators
+const OPERATOR_CHARS: &[char] = &['=', '!', '~', '<', '>'];
+
+/// Split the expression `s` into tokens
+fn tokenize(s: &str) -> Result<Vec<Token>, Error> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '(' {
+            chars.next();
+            tokens.push(Token::Open);
+        } else if c == ')' {
+            chars.next();
+            tokens.push(Token::Close);
+        } else if c == '"' {
+            chars.next();
+            let mut quoted = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => match chars.next() {
+                        Some(c) => quoted.push(c),
+                        None => return Err(invalid("unterminated string")),
+                    },
+                    Some(c) => quoted.push(c),
+                    None => return Err(invalid("unterminated string")),
+                }
+            }
+            tokens.push(Token::Quoted(quoted));
+        } else if OPERATOR_CHARS.contains(&c) {
+            let mut operator = String::new();
+            while let Some(&c) = chars.peek() {
+                if !OPERATOR_CHARS.contains(&c) {
+                    break;
+                }
+                operator.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Operator(operator));
+        } else {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || "()\"".contains(c) || OPERATOR_CHARS.contains(&c) {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Word(word));
+        }
+    }
+    Ok(tokens)
+}
+
+/// [`Error::InvalidQuery`] with `message`
+fn invalid(message: &str) -> Error {
+    Error::InvalidQuery(message.to_string())
+}
+
+/// Recursive descent parser of filter expressions
+struct Parser {
+    /// Tokens of the expression
+    tokens: Vec<Token>,
+    /// Index of the next token
+    pos: usize,
+}
+
+impl Parser {
+    /// Next token, if any, without consuming it
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    /// Consume the next token
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// Consume the next token if it is the keyword `keyword`
+    fn keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token::Word(w)) if w.eq_ignore_ascii_case(keyword) => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// `or := and ("or" and)*`
+    fn or(&mut self) -> Result<Filter, Error> {
+        let mut filters = vec![self.and()?];
+        while self.keyword("or") {
+            filters.push(self.and()?);
+        }
+        Ok(if filters.len() == 1 {
+            filters.remove(0)
+        } else {
+            Filter::Or(filters)
+        })
+    }
+
+    /// `and := not ("and" not)*`
+    fn and(&mut self) -> Result<Filter, Error> {
+        let mut filters = vec![self.not()?];
+        while self.keyword("and") {
+            filters.push(self.not()?);
+        }
+        Ok(if filters.len() == 1 {
+            filters.remove(0)
+        } else {
+            Filter::And(filters)
+        })
+    }
+
+    /// `not := "not" not | "(" or ")" | comparison`
+    fn not(&mut self) -> Result<Filter, Error> {
+        if self.keyword("not") {
+            return Ok(Filter::Not(Box::new(self.not()?)));
+        }
+        if self.peek() == Some(&Token::Open) {
+            self.pos += 1;
+            let filter = self.or()?;
+            return match self.next() {
+                Some(Token::Close) => Ok(filter),
+                _ => Err(invalid("expected `)`")),
+            };
+        }
+        self.comparison()
+    }
+
+    /// `comparison := field operator value`
+    fn comparison(&mut self) -> Result<Filter, Error> {
+        let field = match self.next() {
+            Some(Token::Word(field)) => field,
+            _ => return Err(invalid("expected a field")),
+        };
+        let operator = match self.next() {
+            Some(Token::Operator(operator)) => operator,
+            _ => return Err(invalid(&format!("expected an operator after `{}`", field))),
+        };
+        let value = match self.next() {
+            Some(Token::Word(value)) | Some(Token::Quoted(value)) => value,
+            _ => return Err(invalid(&format!("expected a value after `{}`", operator))),
+        };
+        match field.as_str() {
+            "status" => status(&operator, &value),
+            "time" => time(&operator, &value),
+            _ => {
+                let (negated, pattern) = pattern(&operator, &value)?;
+                let filter = match field.as_str() {
+                    "name" => Filter::Name(pattern),
+                    "classname" => Filter::ClassName(pattern),
+                    "group" => Filter::Group(pattern),
+                    "suite" => Filter::Suite(pattern),
+                    "file" => Filter::File(pattern),
+                    "type" => Filter::Type(pattern),
+                    "message" => Filter::Message(pattern),
+                    _ => match field.strip_prefix("property.") {
+                        Some(name) if !name.is_empty() => {
+                            Filter::Property(name.to_string(), pattern)
+                        }
+                        _ => return Err(invalid(&format!("unknown field `{}`", field))),
+                    },
+                };
+                Ok(negate(negated, filter))
+            }
+        }
+    }
+}
+
+/// `filter`, negated if `negated`
+fn negate(negated: bool, filter: Filter) -> Filter {
+    if negated {
+        Filter::Not(Box::new(filter))
+    } else {
+        filter
+    }
+}
+
+/// Pattern compared to a text field with `operator`, along with whether the
+/// comparison is negated
+fn pattern(operator: &str, value: &str) -> Result<(bool, Pattern), Error> {
+    let (negated, operator) = match operator.strip_prefix('!') {
+        Some(operator) => (true, operator),
+        None => (false, operator),
+    };
+    let pattern = match operator {
+        "=" => Pattern::Exact(value.to_string()),
+        "~" => Pattern::Glob(glob::Pattern::new(value)?),
+        "=~" => Pattern::Regex(regex::Regex::new(value)?),
+        _ => return Err(invalid(&format!("unknown operator `{}`", operator))),
+    };
+    Ok((negated, pattern))
+}
+
+/// Filter on the status
+fn status(operator: &str, value: &str) -> Result<Filter, Error> {
+    let status = match STATUS_NAMES.iter().position(|name| *name == value) {
+        Some(i) => STATUSES[i],
+        None => return Err(invalid(&format!("unknown status `{}`", value))),
+    };
+    match operator {
+        "=" => Ok(Filter::Status(status)),
+        "!=" => Ok(negate(true, Filter::Status(status))),
+        _ => Err(invalid(&format!(
+            "unexpected operator `{}` for status",
+            operator
+        ))),
+    }
+}
+
+/// Filter on the duration
+fn time(operator: &str, value: &str) -> Result<Filter, Error> {
+    let cmp = match operator {
+        "<" => Comparison::Less,
+        "<=" => Comparison::LessOrEqual,
+        ">" => Comparison::Greater,
+        ">=" => Comparison::GreaterOrEqual,
+        "=" => Comparison::Equal,
+        "!=" => Comparison::NotEqual,
+        _ => {
+            return Err(invalid(&format!(
+                "unexpected operator `{}` for time",
+                operator
+            )))
+        }
+    };
+    let value = value
+        .parse()
+        .map_err(|_| invalid(&format!("invalid time `{}`", value)))?;
+    Ok(Filter::Time(cmp, value))
+}
+
+impl FromStr for Filter {
+    type Err = Error;
+
+    /// Parse a filter expression
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            pos: 0,
+        };
+        let filter = parser.or()?;
+        match parser.peek() {
+            None => Ok(filter),
+            Some(token) => Err(invalid(&format!("unexpected {}", token))),
+        }
+    }
+}
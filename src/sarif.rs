@@ -0,0 +1,162 @@
+//! Export failures as a SARIF 2.1.0 log
+//!
+//! Each failing test case, or test case in error, gives a result so that test
+//! failures are shown by code scanning tools alongside static analysis
+//! findings. The rule of a result is the type of the failure, or
+//! `test-failure` and `test-error` for failures without type, and its
+//! location is the `file` and `line` of the test case.
+//!
+//! # Example
+//!
+//! ```
+//! use junit_parser::sarif::{to_value, SarifOptions};
+//! use std::io::Cursor;
+//!
+//! let xml = r#"
+//! <testsuite name="cart">
+//!   <testcase name="test_total" file="src/cart.py" line="12">
+//!     <failure type="AssertionError" message="expected 10, got 11"/>
+//!   </testcase>
+//! </testsuite>"#;
+//! let report = junit_parser::from_reader(Cursor::new(xml)).unwrap();
+//! let log = to_value(&report, &SarifOptions::default());
+//! let result = &log["runs"][0]["results"][0];
+//! assert_eq!(result["ruleId"], "AssertionError");
+//! assert_eq!(result["message"]["text"], "expected 10, got 11");
+//! assert_eq!(
+//!     result["locations"][0]["physicalLocation"]["region"]["startLine"],
+//!     12
+//! );
+//! ```
+
+use crate::{Error, TestCase, TestStatus, TestSuite, TestSuites};
+use serde_json::{json, Map, Value};
+
+/// URI of the JSON schema of SARIF 2.1.0
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+#[derive(Debug, Clone)]
+/// Options of the SARIF export
+pub struct SarifOptions {
+    /// Name of the tool reported in the log
+    pub tool_name: String,
+    /// Version of the tool reported in the log
+    pub tool_version: Option<String>,
+}
+
+impl Default for SarifOptions {
+    fn default() -> Self {
+        Self {
+            tool_name: env!("CARGO_PKG_NAME").to_string(),
+            tool_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+        }
+    }
+}
+
+/// Rule id, message and body of the failure of `tc`, if it failed
+fn failure(tc: &TestCase) -> Option<(&str, &str, &str)> {
+    match &tc.status {
+        TestStatus::Failure(f) if f.failure_type.is_empty() => {
+            Some(("test-failure", &f.message, &f.text))
+        }
+        TestStatus::Failure(f) => Some((&f.failure_type, &f.message, &f.text)),
+        TestStatus::Error(e) if e.error_type.is_empty() => {
+            Some(("test-error", &e.message, &e.text))
+        }
+        TestStatus::Error(e) => Some((&e.error_type, &e.message, &e.text)),
+        _ => None,
+    }
+}
+
+/// Result of the failure of `tc`, of the rule at index `rule_index`
+fn result(
+    ts: &TestSuite,
+    tc: &TestCase,
+    rule: &str,
+    rule_index: usize,
+    message: &str,
+    text: &str,
+) -> Value {
+    let message = if !message.is_empty() {
+        message
+    } else {
+        text.lines()
+            .map(str::trim)
+            .find(|l| !l.is_empty())
+            .unwrap_or("Test failed")
+    };
+    let mut result = Map::new();
+    result.insert("ruleId".to_string(), json!(rule));
+    result.insert("ruleIndex".to_string(), json!(rule_index));
+    result.insert("level".to_string(), json!("error"));
+    result.insert("message".to_string(), json!({ "text": message }));
+    if let Some(file) = &tc.file {
+        let mut location = json!({
+            "artifactLocation": { "uri": file.replace('\\', "/") }
+        });
+        if let Some(line) = tc.line {
+            location["region"] = json!({ "startLine": line });
+        }
+        result.insert(
+            "locations".to_string(),
+            json!([{
+                "physicalLocation": location,
+                "logicalLocations": [{ "fullyQualifiedName": tc.name, "kind": "function" }]
+            }]),
+        );
+    }
+    result.insert(
+        "partialFingerprints".to_string(),
+        json!({ "testName/v1": tc.name }),
+    );
+    let mut properties = json!({ "suite": ts.name, "time": tc.time });
+    if !text.is_empty() {
+        properties["details"] = json!(text);
+    }
+    result.insert("properties".to_string(), properties);
+    Value::Object(result)
+}
+
+/// SARIF log of the failing test cases, and the test cases in error, of
+/// `suites`
+pub fn to_value(suites: &TestSuites, options: &SarifOptions) -> Value {
+    let mut rules: Vec<&str> = Vec::new();
+    let mut results = Vec::new();
+    for ts in suites.suites.iter() {
+        ts.visit_cases(&mut |ts, tc| {
+            if let Some((rule, message, text)) = failure(tc) {
+                let index = match rules.iter().position(|r| *r == rule) {
+                    Some(index) => index,
+                    None => {
+                        rules.push(rule);
+                        rules.len() - 1
+                    }
+                };
+                results.push(result(ts, tc, rule, index, message, text));
+            }
+        });
+    }
+
+    let rules: Vec<Value> = rules
+        .iter()
+        .map(|rule| json!({ "id": rule, "shortDescription": { "text": rule } }))
+        .collect();
+    let mut driver = json!({ "name": options.tool_name, "rules": rules });
+    if let Some(version) = &options.tool_version {
+        driver["version"] = json!(version);
+    }
+    json!({
+        "$schema": SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": { "driver": driver },
+            "results": results
+        }]
+    })
+}
+
+/// SARIF log of the failing test cases, and the test cases in error, of
+/// `suites`, as JSON
+pub fn to_json(suites: &TestSuites, options: &SarifOptions) -> Result<String, Error> {
+    Ok(serde_json::to_string_pretty(&to_value(suites, options))?)
+}
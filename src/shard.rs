@@ -0,0 +1,259 @@
+//! Split tests across shards balanced by their past durations
+//!
+//! The duration of each test case, class or suite is estimated from the
+//! reports of past runs, then the tests are assigned to the shards, longest
+//! first, each one to the shard with the shortest estimated time so far.
+//!
+//! # Example
+//!
+//! ```
+//! use junit_parser::shard::{plan_shards, ShardOptions};
+//! use std::io::Cursor;
+//!
+//! let xml = r#"
+//! <testsuite>
+//!   <testcase name="a" time="4"/>
+//!   <testcase name="b" time="3"/>
+//!   <testcase name="c" time="2"/>
+//!   <testcase name="d" time="1"/>
+//! </testsuite>
+//! "#;
+//! let report = junit_parser::from_reader(Cursor::new(xml)).unwrap();
+//! let options = ShardOptions {
+//!     shards: 2,
+//!     ..Default::default()
+//! };
+//! let plan = plan_shards([&report], ["a", "b", "c", "d", "e"], &options);
+//! // "e" is unknown, its duration is estimated to the average of 2.5
+//! assert_eq!(plan.shards[0].tests, vec!["a", "c"]);
+//! assert_eq!(plan.shards[1].tests, vec!["b", "e", "d"]);
+//! assert_eq!(plan.unknown, vec!["e"]);
+//! println!("{}", plan.to_json().unwrap());
+//! ```
+
+use crate::{Error, TestCase, TestSuite, TestSuites};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+/// What is assigned to a shard
+pub enum Granularity {
+    /// Test cases, identified by their [`TestCase::name`]
+    #[default]
+    Case,
+    /// Classes, identified by the `classname` of their test cases, or else
+    /// by their `group`, or else by the name of their test suite
+    Class,
+    /// Test suites, identified by their name
+    Suite,
+}
+
+impl Granularity {
+    /// Identifier of the test case `tc` of the test suite `ts`
+    fn id(&self, ts: &TestSuite, tc: &TestCase) -> String {
+        match self {
+            Granularity::Case => tc.name.clone(),
+            Granularity::Class => tc
+                .classname
+                .as_ref()
+                .or(tc.group.as_ref())
+                .unwrap_or(&ts.name)
+                .clone(),
+            Granularity::Suite => ts.name.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Options of the planner, see [`plan_shards`]
+pub struct ShardOptions {
+    /// Number of shards, at least one
+    pub shards: usize,
+    /// What is assigned to a shard
+    pub granularity: Granularity,
+    /// Estimated duration of the tests not found in the past reports, the
+    /// average duration of the known tests if not set
+    pub default_time: Option<f64>,
+}
+
+impl Default for ShardOptions {
+    fn default() -> Self {
+        Self {
+            shards: 1,
+            granularity: Granularity::default(),
+            default_time: None,
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Default)]
+/// Tests assigned to a shard
+pub struct Shard {
+    /// Index of the shard, starting at 0
+    pub index: usize,
+    /// Sum of the estimated durations of the tests of the shard
+    pub estimated_time: f64,
+    /// Identifiers of the tests of the shard, longest first
+    pub tests: Vec<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Default)]
+/// Assignment of tests to shards, see [`plan_shards`]
+pub struct Plan {
+    /// What is assigned to a shard
+    pub granularity: Granularity,
+    /// The shards
+    pub shards: Vec<Shard>,
+    /// Identifiers of the tests not found in the past reports, whose
+    /// duration was estimated with [`ShardOptions::default_time`]
+    pub unknown: Vec<String>,
+}
+
+impl Plan {
+    /// Estimated time of the longest shard
+    pub fn longest(&self) -> f64 {
+        self.shards
+            .iter()
+            .map(|s| s.estimated_time)
+            .fold(0., f64::max)
+    }
+
+    /// Assignment as JSON
+    pub fn to_json(&self) -> Result<String, Error> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Write one file per shard in the directory `dir`, named `shard-N.txt`
+    /// and listing the identifiers of its tests, one per line
+    ///
+    /// Returns the paths of the files written.
+    pub fn write_shard_files<P: AsRef<Path>>(&self, dir: P) -> Result<Vec<PathBuf>, Error> {
+        let mut paths = Vec::with_capacity(self.shards.len());
+        for shard in self.shards.iter() {
+            let path = dir.as_ref().join(format!("shard-{}.txt", shard.index));
+            let mut content = String::new();
+            for test in shard.tests.iter() {
+                content.push_str(test);
+                content.push('\n');
+            }
+            fs::write(&path, content)?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+}
+
+/// Identifiers of the tests of `suites`, in the order they first appear
+pub fn test_ids(suites: &TestSuites, granularity: Granularity) -> Vec<String> {
+    let mut seen = BTreeSet::new();
+    let mut ids = Vec::new();
+    for ts in suites.suites.iter() {
+        ts.visit_cases(&mut |ts, tc| {
+            let id = granularity.id(ts, tc);
+            if seen.insert(id.clone()) {
+                ids.push(id);
+            }
+        });
+    }
+    ids
+}
+
+/// Estimate the duration of each test from past reports
+///
+/// The duration of a test is the sum of the times of its test cases in a
+/// report, averaged over the reports it appears in.
+fn estimate<'a, I: IntoIterator<Item = &'a TestSuites>>(
+    history: I,
+    granularity: Granularity,
+) -> HashMap<String, f64> {
+    let mut totals: HashMap<String, (f64, usize)> = HashMap::new();
+    for suites in history {
+        let mut run: HashMap<String, f64> = HashMap::new();
+        for ts in suites.suites.iter() {
+            ts.visit_cases(&mut |ts, tc| {
+                *run.entry(granularity.id(ts, tc)).or_insert(0.) += tc.time;
+            });
+        }
+        for (id, time) in run {
+            let total = totals.entry(id).or_insert((0., 0));
+            total.0 += time;
+            total.1 += 1;
+        }
+    }
+    totals
+        .into_iter()
+        .map(|(id, (time, n))| (id, time / n as f64))
+        .collect()
+}
+
+/// Assign `tests` to shards, balancing the durations estimated from the
+/// reports of `history`
+///
+/// Tests are assigned from the longest one to the shortest one, each to the
+/// shard with the shortest estimated time so far, which keeps the longest
+/// shard close to the optimum. Tests listed several times are assigned once.
+/// Use [`test_ids`] to get the tests of a report.
+pub fn plan_shards<'a, H, T, S>(history: H, tests: T, options: &ShardOptions) -> Plan
+where
+    H: IntoIterator<Item = &'a TestSuites>,
+    T: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let known = estimate(history, options.granularity);
+    let default_time = options.default_time.unwrap_or_else(|| {
+        if known.is_empty() {
+            1.
+        } else {
+            known.values().sum::<f64>() / known.len() as f64
+        }
+    });
+
+    let mut seen = BTreeSet::new();
+    let mut unknown = Vec::new();
+    let mut estimated: Vec<(String, f64)> = Vec::new();
+    for test in tests {
+        let test = test.as_ref();
+        if !seen.insert(test.to_string()) {
+            continue;
+        }
+        let time = match known.get(test) {
+            Some(time) => *time,
+            None => {
+                unknown.push(test.to_string());
+                default_time
+            }
+        };
+        estimated.push((test.to_string(), time));
+    }
+    // Longest first, ties broken by identifier to get a stable plan
+    estimated.sort_by(|a, b| {
+        b.1.partial_cmp(&a.1)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.0.cmp(&b.0))
+    });
+
+    let mut shards: Vec<Shard> = (0..options.shards.max(1))
+        .map(|index| Shard {
+            index,
+            ..Default::default()
+        })
+        .collect();
+    for (test, time) in estimated {
+        let mut shortest = 0;
+        for (i, shard) in shards.iter().enumerate() {
+            if shard.estimated_time < shards[shortest].estimated_time {
+                shortest = i;
+            }
+        }
+        let shard = &mut shards[shortest];
+        shard.estimated_time += time;
+        shard.tests.push(test);
+    }
+    Plan {
+        granularity: options.granularity,
+        shards,
+        unknown,
+    }
+}
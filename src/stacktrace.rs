@@ -0,0 +1,673 @@
+//! Parse the stack traces found in the bodies of failures and errors
+//!
+//! The following formats are recognized:
+//! - Java and Kotlin: `at com.example.Foo.bar(Foo.java:12)` frames, with
+//!   `Caused by:` chains
+//! - Python: tracebacks, with chained exceptions, and pytest's long format
+//! - Rust: panic messages and backtraces
+//! - Go: panics with goroutine stacks and `go test` failure locations
+//! - .NET: `at Foo.Bar() in Foo.cs:line 12` frames, with inner exceptions
+//! - JavaScript: V8 (`at foo (file.js:1:2)`) and Firefox (`foo@file.js:1:2`)
+//!   stacks, with `[cause]:` chains
+//!
+//! Whatever the language, frames are ordered from the innermost one, where
+//! the error was raised, to the outermost one: Python tracebacks, printed
+//! with the most recent call last, are reversed.
+//!
+//! # Example
+//!
+//! ```
+//! use junit_parser::stacktrace::{Language, StackTrace};
+//!
+//! let text = "java.lang.IllegalStateException: boom
+//!     at com.example.Foo.bar(Foo.java:12)
+//!     at com.example.FooTest.test(FooTest.java:7)";
+//! let trace = StackTrace::parse(text).unwrap();
+//! assert_eq!(trace.language, Language::Java);
+//! let frame = trace.top_frame().unwrap();
+//! assert_eq!(frame.module.as_deref(), Some("com.example.Foo"));
+//! assert_eq!(frame.function.as_deref(), Some("bar"));
+//! assert_eq!(frame.file.as_deref(), Some("Foo.java"));
+//! assert_eq!(frame.line, Some(12));
+//! ```
+
+use crate::{RerunOrFlaky, TestError, TestFailure};
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Language, or runtime, which printed a stack trace
+pub enum Language {
+    /// Java, Kotlin or any JVM language
+    Java,
+    /// Python
+    Python,
+    /// Rust
+    Rust,
+    /// Go
+    Go,
+    /// .NET
+    DotNet,
+    /// JavaScript
+    JavaScript,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+/// A frame of a stack trace
+pub struct Frame {
+    /// Name of the function, or method
+    pub function: Option<String>,
+    /// Module, package or class of the function
+    pub module: Option<String>,
+    /// Path of the source file
+    pub file: Option<String>,
+    /// Line in the source file, starting at 1
+    pub line: Option<u64>,
+    /// Column in the source file, starting at 1
+    pub column: Option<u64>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A stack trace, along with the stack trace of its cause
+pub struct StackTrace {
+    /// Language which printed the stack trace
+    pub language: Language,
+    /// Message of the exception or panic, usually with its type
+    pub message: Option<String>,
+    /// Frames, from the innermost one to the outermost one
+    pub frames: Vec<Frame>,
+    /// Stack trace of the exception which caused this one
+    pub cause: Option<Box<StackTrace>>,
+}
+
+impl StackTrace {
+    /// New empty stack trace
+    fn new(language: Language) -> Self {
+        Self {
+            language,
+            message: None,
+            frames: Vec::new(),
+            cause: None,
+        }
+    }
+
+    /// Parse the stack trace found in `text`
+    ///
+    /// Returns `None` if no supported format is recognized.
+    pub fn parse(text: &str) -> Option<StackTrace> {
+        match detect_language(text)? {
+            Language::Java => parse_java(text),
+            Language::Python => parse_python(text),
+            Language::Rust => parse_rust(text),
+            Language::Go => parse_go(text),
+            Language::DotNet => parse_dotnet(text),
+            Language::JavaScript => parse_javascript(text),
+        }
+    }
+
+    /// Innermost frame, where the error was raised
+    pub fn top_frame(&self) -> Option<&Frame> {
+        self.frames.first()
+    }
+
+    /// Innermost cause of the stack trace, `self` if it has no cause
+    pub fn root_cause(&self) -> &StackTrace {
+        let mut trace = self;
+        while let Some(cause) = &trace.cause {
+            trace = cause;
+        }
+        trace
+    }
+}
+
+impl TestFailure {
+    /// Parse the stack trace found in the body of the `<failure />` tag, see
+    /// [`StackTrace::parse`]
+    pub fn parse_stack_trace(&self) -> Option<StackTrace> {
+        StackTrace::parse(&self.text)
+    }
+}
+
+impl TestError {
+    /// Parse the stack trace found in the body of the `<error />` tag, see
+    /// [`StackTrace::parse`]
+    pub fn parse_stack_trace(&self) -> Option<StackTrace> {
+        StackTrace::parse(&self.text)
+    }
+}
+
+impl RerunOrFlaky {
+    /// Parse the stack trace found in the `stackTrace` element, or else in the
+    /// body of the tag, see [`StackTrace::parse`]
+    pub fn parse_stack_trace(&self) -> Option<StackTrace> {
+        match &self.stack_trace {
+            Some(stack_trace) => StackTrace::parse(stack_trace),
+            None => StackTrace::parse(&self.text),
+        }
+    }
+}
+
+/// Detect the language of the stack trace in `text`
+fn detect_language(text: &str) -> Option<Language> {
+    let mut firefox = false;
+    for line in text.lines() {
+        let t = line.trim();
+        if t.starts_with("Traceback (most recent call last)")
+            || (t.starts_with("File \"") && t.contains("\", line "))
+        {
+            return Some(Language::Python);
+        }
+        if t.starts_with("thread '") && t.contains("' panicked at ") {
+            return Some(Language::Rust);
+        }
+        if t.starts_with("panic: ") || (t.starts_with("goroutine ") && t.ends_with("]:")) {
+            return Some(Language::Go);
+        }
+        if t == DOTNET_END_OF_INNER {
+            return Some(Language::DotNet);
+        }
+        if let Some(rest) = t.strip_prefix("at ") {
+            if let Some(language) = classify_at_frame(rest) {
+                return Some(language);
+            }
+        }
+        if let Some((loc, _)) = t.split_once(": ") {
+            let (file, line, _) = split_location(loc);
+            if let (Some(file), Some(_)) = (file, line) {
+                if file.ends_with(".py") {
+                    return Some(Language::Python);
+                }
+                if file.ends_with(".go") {
+                    return Some(Language::Go);
+                }
+            }
+        }
+        if let Some((_, loc)) = t.split_once('@') {
+            firefox |= split_location(loc).2.is_some();
+        }
+    }
+    if firefox {
+        Some(Language::JavaScript)
+    } else {
+        None
+    }
+}
+
+/// Detect the language of a frame starting with `at `, given without it
+fn classify_at_frame(rest: &str) -> Option<Language> {
+    if rest.contains(") in ") {
+        return Some(Language::DotNet);
+    }
+    match rest.strip_suffix(')').and_then(|r| r.split_once('(')) {
+        Some((name, _)) if name.ends_with(' ') => Some(Language::JavaScript),
+        Some((_, "Native Method" | "Unknown Source")) => Some(Language::Java),
+        Some((_, loc)) if loc.contains('.') && !loc.contains(' ') => Some(Language::Java),
+        Some(_) => Some(Language::DotNet),
+        None if split_location(rest).2.is_some() => Some(Language::JavaScript),
+        None => None,
+    }
+}
+
+/// Split a location such as `file.js:12:3` into its file, line and column
+fn split_location(loc: &str) -> (Option<String>, Option<u64>, Option<u64>) {
+    let mut file = loc;
+    let mut numbers = Vec::new();
+    while numbers.len() < 2 {
+        match file.rsplit_once(':') {
+            Some((f, n)) if !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()) => {
+                numbers.push(n.parse().ok());
+                file = f;
+            }
+            _ => break,
+        }
+    }
+    let file = if file.is_empty() {
+        None
+    } else {
+        Some(file.to_string())
+    };
+    match numbers[..] {
+        [line] => (file, line, None),
+        [column, line] => (file, line, column),
+        _ => (file, None, None),
+    }
+}
+
+/// Split a qualified name such as `module.function` on the last `separator`
+fn split_name(name: &str, separator: &str) -> (Option<String>, Option<String>) {
+    match name.rsplit_once(separator) {
+        Some((module, function)) => (Some(module.to_string()), Some(function.to_string())),
+        None if name.is_empty() => (None, None),
+        None => (None, Some(name.to_string())),
+    }
+}
+
+/// Link stack traces listed from the outermost one to their innermost cause
+fn chain(mut traces: Vec<StackTrace>) -> Option<StackTrace> {
+    let mut cause: Option<StackTrace> = None;
+    while let Some(mut trace) = traces.pop() {
+        trace.cause = cause.map(Box::new);
+        cause = Some(trace);
+    }
+    cause
+}
+
+/// Parse a frame such as `com.example.Foo.bar(Foo.java:12)`
+fn java_frame(rest: &str) -> Option<Frame> {
+    let (name, loc) = rest.split_once('(')?;
+    let loc = &loc[..loc.rfind(')')?];
+    // Skip the class loader and module, as in `app//` or `java.base/`
+    let name = name.rsplit_once('/').map_or(name, |(_, n)| n);
+    let (module, function) = split_name(name, ".");
+    let (file, line, _) = match loc {
+        "Native Method" | "Unknown Source" => (None, None, None),
+        _ => split_location(loc),
+    };
+    Some(Frame {
+        function,
+        module,
+        file,
+        line,
+        column: None,
+    })
+}
+
+/// Parse a Java or Kotlin stack trace
+fn parse_java(text: &str) -> Option<StackTrace> {
+    let mut traces = Vec::new();
+    let mut current = StackTrace::new(Language::Java);
+    // Indentation of the `Suppressed:` exception being skipped
+    let mut suppressed: Option<usize> = None;
+    for line in text.lines() {
+        let t = line.trim();
+        let indent = line.len() - line.trim_start().len();
+        if let Some(cause) = t.strip_prefix("Caused by: ") {
+            match suppressed {
+                Some(i) if indent >= i => continue,
+                _ => suppressed = None,
+            }
+            traces.push(std::mem::replace(
+                &mut current,
+                StackTrace::new(Language::Java),
+            ));
+            current.message = Some(cause.to_string());
+        } else if suppressed.is_some() {
+            continue;
+        } else if t.starts_with("Suppressed: ") {
+            suppressed = Some(indent);
+        } else if let Some(rest) = t.strip_prefix("at ") {
+            current.frames.extend(java_frame(rest));
+        } else if current.message.is_none() && current.frames.is_empty() && !t.is_empty() {
+            current.message = Some(t.to_string());
+        }
+    }
+    traces.push(current);
+    chain(traces)
+}
+
+/// Parse a Python traceback, or a failure in pytest's long format
+fn parse_python(text: &str) -> Option<StackTrace> {
+    // Chained exceptions are printed from the innermost cause
+    let mut traces = Vec::new();
+    let mut current = StackTrace::new(Language::Python);
+    for line in text.lines() {
+        let t = line.trim();
+        let is_separator = t.starts_with("Traceback (most recent call last)")
+            || t == "During handling of the above exception, another exception occurred:"
+            || t == "The above exception was the direct cause of the following exception:";
+        if is_separator {
+            if current.message.is_some() || !current.frames.is_empty() {
+                traces.push(std::mem::replace(
+                    &mut current,
+                    StackTrace::new(Language::Python),
+                ));
+            }
+        } else if let Some(rest) = t.strip_prefix("File \"") {
+            let (file, rest) = match rest.split_once("\", line ") {
+                Some(split) => split,
+                None => continue,
+            };
+            let (line, function) = match rest.split_once(", in ") {
+                Some((line, function)) => (line, Some(function.to_string())),
+                None => (rest, None),
+            };
+            current.frames.push(Frame {
+                function,
+                module: None,
+                file: Some(file.to_string()),
+                line: line.parse().ok(),
+                column: None,
+            });
+        } else if let Some(message) = t.strip_prefix("E ") {
+            if current.message.is_none() {
+                current.message = Some(message.trim().to_string());
+            }
+        } else if let Some((file, line, message)) = pytest_location(t) {
+            current.frames.push(Frame {
+                file: Some(file),
+                line: Some(line),
+                ..Default::default()
+            });
+            if current.message.is_none() && !message.is_empty() {
+                current.message = Some(message.to_string());
+            }
+        } else if current.message.is_none()
+            && !current.frames.is_empty()
+            && !t.is_empty()
+            && !line.starts_with(char::is_whitespace)
+        {
+            current.message = Some(t.to_string());
+        }
+    }
+    traces.push(current);
+    for trace in traces.iter_mut() {
+        trace.frames.reverse();
+    }
+    traces.reverse();
+    chain(traces)
+}
+
+/// Parse a location printed by pytest, such as `test_foo.py:3: AssertionError`
+fn pytest_location(t: &str) -> Option<(String, u64, &str)> {
+    let (loc, message) = match t.split_once(": ") {
+        Some((loc, message)) => (loc, message),
+        None => (t.strip_suffix(':')?, ""),
+    };
+    match split_location(loc) {
+        (Some(file), Some(line), None) if file.ends_with(".py") && !file.contains(' ') => {
+            Some((file, line, message))
+        }
+        _ => None,
+    }
+}
+
+/// Remove the hash suffix of a Rust symbol, as in `foo::bar::h0123456789abcdef`
+fn strip_rust_hash(symbol: &str) -> &str {
+    match symbol.rsplit_once("::") {
+        Some((name, hash))
+            if hash.len() == 17
+                && hash.starts_with('h')
+                && hash[1..].bytes().all(|b| b.is_ascii_hexdigit()) =>
+        {
+            name
+        }
+        _ => symbol,
+    }
+}
+
+/// Parse a Rust panic, along with its backtrace
+///
+/// The location of the panic is the first frame, followed by the frames of
+/// the backtrace, if any.
+fn parse_rust(text: &str) -> Option<StackTrace> {
+    let mut trace = StackTrace::new(Language::Rust);
+    let mut expect_message = false;
+    // Message of a panic printed before Rust 1.73, up to its location
+    let mut old_message: Option<String> = None;
+    let mut in_backtrace = false;
+    for line in text.lines() {
+        let t = line.trim();
+        if let Some(message) = old_message.as_mut() {
+            match t.rsplit_once("', ") {
+                Some((end, loc)) if split_location(loc).1.is_some() => {
+                    message.push('\n');
+                    message.push_str(end);
+                    let (file, line, column) = split_location(loc);
+                    trace.frames.insert(
+                        0,
+                        Frame {
+                            file,
+                            line,
+                            column,
+                            ..Default::default()
+                        },
+                    );
+                    trace.message = old_message.take();
+                }
+                _ => {
+                    message.push('\n');
+                    message.push_str(line);
+                }
+            }
+        } else if let Some((_, rest)) = t.split_once("' panicked at ") {
+            if trace.message.is_some() {
+                break;
+            }
+            if let Some(rest) = rest.strip_prefix('\'') {
+                match rest.rsplit_once("', ") {
+                    Some((message, loc)) if split_location(loc).1.is_some() => {
+                        let (file, line, column) = split_location(loc);
+                        trace.frames.insert(
+                            0,
+                            Frame {
+                                file,
+                                line,
+                                column,
+                                ..Default::default()
+                            },
+                        );
+                        trace.message = Some(message.to_string());
+                        old_message = None;
+                    }
+                    _ => old_message = Some(rest.to_string()),
+                }
+            } else {
+                let loc = rest.strip_suffix(':').unwrap_or(rest);
+                let (file, line, column) = split_location(loc);
+                trace.frames.insert(
+                    0,
+                    Frame {
+                        file,
+                        line,
+                        column,
+                        ..Default::default()
+                    },
+                );
+                expect_message = true;
+            }
+        } else if expect_message && !t.is_empty() {
+            trace.message = Some(t.to_string());
+            expect_message = false;
+        } else if t == "stack backtrace:" {
+            in_backtrace = true;
+        } else if in_backtrace {
+            if let Some(loc) = t.strip_prefix("at ") {
+                if let Some(frame) = trace.frames.last_mut() {
+                    let (file, line, column) = split_location(loc);
+                    frame.file = file;
+                    frame.line = line;
+                    frame.column = column;
+                }
+            } else if let Some((n, symbol)) = t.split_once(": ") {
+                if n.bytes().all(|b| b.is_ascii_digit()) {
+                    let (module, function) = split_name(strip_rust_hash(symbol), "::");
+                    trace.frames.push(Frame {
+                        function,
+                        module,
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+    }
+    Some(trace)
+}
+
+/// Parse a function of a goroutine stack, such as `main.(*T).foo(0x1, 0x2)`
+fn go_frame(t: &str) -> Frame {
+    let name = t.strip_prefix("created by ").unwrap_or(t);
+    let name = name.split_once(" in goroutine ").map_or(name, |(n, _)| n);
+    let name = match (name.ends_with(')'), name.rfind('(')) {
+        (true, Some(i)) => &name[..i],
+        _ => name,
+    };
+    let slash = name.rfind('/').map_or(0, |i| i + 1);
+    let (module, function) = match name[slash..].find('.') {
+        Some(dot) => (
+            Some(name[..slash + dot].to_string()),
+            Some(name[slash + dot + 1..].to_string()),
+        ),
+        None => (None, Some(name.to_string())),
+    };
+    Frame {
+        function,
+        module,
+        ..Default::default()
+    }
+}
+
+/// Parse a Go panic, with the stack of the panicking goroutine, or the
+/// locations of failures printed by `go test`
+fn parse_go(text: &str) -> Option<StackTrace> {
+    let mut trace = StackTrace::new(Language::Go);
+    let mut in_goroutine = false;
+    let mut goroutine_done = false;
+    for line in text.lines() {
+        let t = line.trim();
+        if let Some(message) = t.strip_prefix("panic: ") {
+            if trace.message.is_none() {
+                trace.message = Some(message.to_string());
+            }
+        } else if t.starts_with("goroutine ") && t.ends_with("]:") {
+            if goroutine_done || in_goroutine {
+                break;
+            }
+            in_goroutine = true;
+        } else if in_goroutine {
+            if t.is_empty() {
+                in_goroutine = false;
+                goroutine_done = true;
+            } else if line.starts_with('\t') {
+                if let Some(frame) = trace.frames.last_mut() {
+                    let loc = t.split_once(" +0x").map_or(t, |(l, _)| l);
+                    let (file, line, column) = split_location(loc);
+                    frame.file = file;
+                    frame.line = line;
+                    frame.column = column;
+                }
+            } else {
+                trace.frames.push(go_frame(t));
+            }
+        } else if let Some((loc, message)) = t.split_once(": ") {
+            if let (Some(file), Some(line), None) = split_location(loc) {
+                if file.ends_with(".go") && !file.contains(' ') {
+                    trace.frames.push(Frame {
+                        file: Some(file),
+                        line: Some(line),
+                        ..Default::default()
+                    });
+                    if trace.message.is_none() {
+                        trace.message = Some(message.to_string());
+                    }
+                }
+            }
+        }
+    }
+    Some(trace)
+}
+
+/// Line ending the frames of an inner exception in .NET stack traces
+const DOTNET_END_OF_INNER: &str = "--- End of inner exception stack trace ---";
+
+/// Parse a frame such as `Foo.Bar.Baz(Int32 x) in C:\src\Bar.cs:line 12`
+fn dotnet_frame(rest: &str) -> Frame {
+    let (call, location) = match rest.split_once(" in ") {
+        Some((call, location)) => (call, Some(location)),
+        None => (rest, None),
+    };
+    let name = call.split_once('(').map_or(call, |(n, _)| n);
+    let (module, function) = split_name(name, ".");
+    let (file, line) = match location.map(|l| l.rsplit_once(":line ")) {
+        Some(Some((file, line))) => (Some(file.to_string()), line.parse().ok()),
+        Some(None) => (location.map(str::to_string), None),
+        None => (None, None),
+    };
+    Frame {
+        function,
+        module,
+        file,
+        line,
+        column: None,
+    }
+}
+
+/// Parse a .NET stack trace, with its inner exceptions
+fn parse_dotnet(text: &str) -> Option<StackTrace> {
+    // Inner exceptions are printed from the innermost one
+    let mut traces = Vec::new();
+    let mut current = StackTrace::new(Language::DotNet);
+    let mut message: Option<String> = None;
+    let mut inner_messages: Vec<String> = Vec::new();
+    for line in text.lines() {
+        let t = line.trim();
+        if t == DOTNET_END_OF_INNER {
+            current.message = inner_messages.pop();
+            traces.push(std::mem::replace(
+                &mut current,
+                StackTrace::new(Language::DotNet),
+            ));
+        } else if let Some(rest) = t.strip_prefix("at ") {
+            current.frames.push(dotnet_frame(rest));
+        } else if message.is_none() && !t.is_empty() && !t.starts_with("---") {
+            let mut parts = t.split(" ---> ");
+            message = parts.next().map(str::to_string);
+            inner_messages = parts.map(str::to_string).collect();
+        }
+    }
+    current.message = message;
+    traces.push(current);
+    traces.reverse();
+    chain(traces)
+}
+
+/// Parse a V8 frame such as `foo (file.js:1:2)`, given without its `at `
+fn v8_frame(rest: &str) -> Frame {
+    let rest = rest.strip_prefix("async ").unwrap_or(rest);
+    let (name, loc) = match rest.strip_suffix(')').and_then(|r| r.split_once(" (")) {
+        Some((name, loc)) => (name.strip_prefix("new ").unwrap_or(name), loc),
+        None => ("", rest),
+    };
+    let (module, function) = split_name(name, ".");
+    let (file, line, column) = split_location(loc);
+    Frame {
+        function,
+        module,
+        file,
+        line,
+        column,
+    }
+}
+
+/// Parse a JavaScript stack trace, with its causes
+fn parse_javascript(text: &str) -> Option<StackTrace> {
+    let mut traces = Vec::new();
+    let mut current = StackTrace::new(Language::JavaScript);
+    for line in text.lines() {
+        let t = line.trim();
+        if let Some(cause) = t.strip_prefix("[cause]: ") {
+            traces.push(std::mem::replace(
+                &mut current,
+                StackTrace::new(Language::JavaScript),
+            ));
+            current.message = Some(cause.to_string());
+        } else if let Some(rest) = t.strip_prefix("at ") {
+            current.frames.push(v8_frame(rest));
+        } else if let Some((name, (file, Some(line), column))) =
+            t.split_once('@').map(|(n, l)| (n, split_location(l)))
+        {
+            let (module, function) = split_name(name, ".");
+            current.frames.push(Frame {
+                function,
+                module,
+                file,
+                line: Some(line),
+                column,
+            });
+        } else if current.message.is_none() && current.frames.is_empty() && !t.is_empty() {
+            current.message = Some(t.to_string());
+        }
+    }
+    traces.push(current);
+    chain(traces)
+}
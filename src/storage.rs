@@ -0,0 +1,483 @@
+//! Store the history of reports in a local SQLite database
+//!
+//! Each report ingested with [`Store::ingest`] is a run, stored along with
+//! its test suites, test cases, failures, reruns and properties. The history
+//! of a test case is then queried by its [`TestCase::name`].
+//!
+//! The database has the following tables:
+//! - `runs`: one row per ingested report, with a label such as a commit or a
+//!   pipeline identifier
+//! - `suites`: the test suites of a run, nested ones referring to their
+//!   parent with `parent_id`
+//! - `cases`: the test cases of a suite, with their `status`, either
+//!   `success`, `failure`, `error` or `skipped`
+//! - `failures`: the failure, error or skipped details of a test case
+//! - `reruns`: the rerun or flaky entries of a test case
+//! - `properties`: the properties of a test suite or of a test case
+//!
+//! # Example
+//!
+//! ```
+//! use junit_parser::storage::{Status, Store};
+//! use std::io::Cursor;
+//!
+//! let mut store = Store::open_in_memory().unwrap();
+//! for (label, xml) in [
+//!     ("v1", r#"<testsuite><testcase name="a" time="1.5"/></testsuite>"#),
+//!     ("v2", r#"<testsuite><testcase name="a" time="2"><failure/></testcase></testsuite>"#),
+//! ] {
+//!     let report = junit_parser::from_reader(Cursor::new(xml)).unwrap();
+//!     store.ingest(&report, label).unwrap();
+//! }
+//! let history = store.test_history("a").unwrap();
+//! assert_eq!(history.len(), 2);
+//! assert_eq!(history[1].label, "v2");
+//! assert_eq!(history[1].status, Status::Failure);
+//! assert_eq!(store.failure_rate("a", None).unwrap(), Some(0.5));
+//! ```
+
+use crate::{Error, RerunOrFlakyKind, TestCase, TestStatus, TestSuite, TestSuites, STATUS_NAMES};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ValueRef};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Version of the schema, stored in the `user_version` of the database
+const SCHEMA_VERSION: i64 = 1;
+
+/// Statements creating the tables and indexes
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY,
+    label TEXT NOT NULL,
+    ingested_at INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    timestamp TEXT,
+    time REAL NOT NULL,
+    tests INTEGER NOT NULL,
+    errors INTEGER NOT NULL,
+    failures INTEGER NOT NULL,
+    skipped INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS suites (
+    id INTEGER PRIMARY KEY,
+    run_id INTEGER NOT NULL REFERENCES runs(id) ON DELETE CASCADE,
+    parent_id INTEGER REFERENCES suites(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    timestamp TEXT,
+    time REAL NOT NULL,
+    tests INTEGER NOT NULL,
+    errors INTEGER NOT NULL,
+    failures INTEGER NOT NULL,
+    skipped INTEGER NOT NULL,
+    hostname TEXT,
+    source_file TEXT
+);
+CREATE INDEX IF NOT EXISTS suites_run ON suites(run_id);
+CREATE TABLE IF NOT EXISTS cases (
+    id INTEGER PRIMARY KEY,
+    run_id INTEGER NOT NULL REFERENCES runs(id) ON DELETE CASCADE,
+    suite_id INTEGER NOT NULL REFERENCES suites(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    classname TEXT,
+    status TEXT NOT NULL,
+    time REAL NOT NULL,
+    file TEXT,
+    line INTEGER
+);
+CREATE INDEX IF NOT EXISTS cases_name_run ON cases(name, run_id);
+CREATE INDEX IF NOT EXISTS cases_run_status ON cases(run_id, status);
+CREATE INDEX IF NOT EXISTS cases_suite ON cases(suite_id);
+CREATE TABLE IF NOT EXISTS failures (
+    case_id INTEGER NOT NULL REFERENCES cases(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    type TEXT NOT NULL,
+    message TEXT NOT NULL,
+    text TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS failures_case ON failures(case_id);
+CREATE TABLE IF NOT EXISTS reruns (
+    case_id INTEGER NOT NULL REFERENCES cases(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    type TEXT NOT NULL,
+    message TEXT NOT NULL,
+    time REAL NOT NULL,
+    text TEXT NOT NULL,
+    stack_trace TEXT
+);
+CREATE INDEX IF NOT EXISTS reruns_case ON reruns(case_id);
+CREATE TABLE IF NOT EXISTS properties (
+    suite_id INTEGER REFERENCES suites(id) ON DELETE CASCADE,
+    case_id INTEGER REFERENCES cases(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    value TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS properties_suite ON properties(suite_id);
+CREATE INDEX IF NOT EXISTS properties_case ON properties(case_id);
+";
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Status of a test case, as stored in the `status` column of `cases`
+pub enum Status {
+    /// Success
+    Success,
+    /// Test case has a `<failure />` tag
+    Failure,
+    /// Test case has a `<error />` tag
+    Error,
+    /// Test case has a `<skipped />` tag
+    Skipped,
+}
+
+/// Statuses, in the order of their declaration and of [`STATUS_NAMES`]
+const STATUSES: [Status; 4] = [
+    Status::Success,
+    Status::Failure,
+    Status::Error,
+    Status::Skipped,
+];
+
+impl Status {
+    /// Name of the status in the database, see [`TestStatus::as_str`]
+    pub fn as_str(&self) -> &'static str {
+        STATUS_NAMES[*self as usize]
+    }
+
+    /// Status from its name in the database
+    fn from_name(name: &str) -> Option<Status> {
+        let i = STATUS_NAMES.iter().position(|n| *n == name)?;
+        Some(STATUSES[i])
+    }
+}
+
+impl FromSql for Status {
+    /// Status from its name in the database, failing on unknown names
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let name = value.as_str()?;
+        Status::from_name(name)
+            .ok_or_else(|| FromSqlError::Other(format!("unknown status {:?}", name).into()))
+    }
+}
+
+impl From<&TestStatus> for Status {
+    fn from(status: &TestStatus) -> Self {
+        match status {
+            TestStatus::Success => Status::Success,
+            TestStatus::Failure(_) => Status::Failure,
+            TestStatus::Error(_) => Status::Error,
+            TestStatus::Skipped(_) => Status::Skipped,
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+/// A run, from the `runs` table
+pub struct Run {
+    /// Identifier of the run
+    pub id: i64,
+    /// Label given to [`Store::ingest`]
+    pub label: String,
+    /// When the run was ingested, in seconds since the Unix epoch
+    pub ingested_at: i64,
+    /// Name of the report
+    pub name: String,
+    /// Time of the report
+    pub time: f64,
+    /// Number of tests of the report
+    pub tests: u64,
+    /// Number of tests in error of the report
+    pub errors: u64,
+    /// Number of tests in failure of the report
+    pub failures: u64,
+    /// Number of tests skipped of the report
+    pub skipped: u64,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+/// Outcome of a test case in a run, see [`Store::test_history`]
+pub struct CaseRun {
+    /// Identifier of the run
+    pub run_id: i64,
+    /// Label of the run
+    pub label: String,
+    /// Status of the test case
+    pub status: Status,
+    /// How long the test case took to run
+    pub time: f64,
+    /// Message of the failure, error or skipped tag, if any
+    pub message: Option<String>,
+}
+
+/// History of reports stored in an SQLite database
+pub struct Store {
+    /// Connection to the database
+    conn: Connection,
+}
+
+impl Store {
+    /// Open the database at `path`, creating it if needed
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Store, Error> {
+        Store::init(Connection::open(path)?)
+    }
+
+    /// Open a database held in memory, lost once dropped
+    pub fn open_in_memory() -> Result<Store, Error> {
+        Store::init(Connection::open_in_memory()?)
+    }
+
+    /// Create the schema of the database, if needed
+    ///
+    /// Fails with [`Error::UnsupportedSchemaVersion`] if the database has a
+    /// newer schema.
+    fn init(conn: Connection) -> Result<Store, Error> {
+        let version: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version > SCHEMA_VERSION {
+            return Err(Error::UnsupportedSchemaVersion(version));
+        }
+        conn.pragma_update(None, "foreign_keys", true)?;
+        conn.execute_batch(SCHEMA)?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        Ok(Store { conn })
+    }
+
+    /// Store the report `suites` as a new run named `label`, returning the
+    /// identifier of the run
+    pub fn ingest(&mut self, suites: &TestSuites, label: &str) -> Result<i64, Error> {
+        let ingested_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as i64);
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO runs (label, ingested_at, name, timestamp, time, tests, errors, failures, skipped)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                label,
+                ingested_at,
+                suites.name,
+                timestamp(&suites.timestamp),
+                suites.time,
+                suites.tests as i64,
+                suites.errors as i64,
+                suites.failures as i64,
+                suites.skipped as i64,
+            ],
+        )?;
+        let run_id = tx.last_insert_rowid();
+        for ts in suites.suites.iter() {
+            insert_suite(&tx, run_id, None, ts)?;
+        }
+        tx.commit()?;
+        Ok(run_id)
+    }
+
+    /// All the runs, from the oldest to the newest one
+    pub fn runs(&self) -> Result<Vec<Run>, Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, label, ingested_at, name, time, tests, errors, failures, skipped
+             FROM runs ORDER BY id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(Run {
+                id: row.get(0)?,
+                label: row.get(1)?,
+                ingested_at: row.get(2)?,
+                name: row.get(3)?,
+                time: row.get(4)?,
+                tests: row.get::<_, i64>(5)? as u64,
+                errors: row.get::<_, i64>(6)? as u64,
+                failures: row.get::<_, i64>(7)? as u64,
+                skipped: row.get::<_, i64>(8)? as u64,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Outcomes of the test case `name` in every run it appears in, from the
+    /// oldest to the newest run
+    pub fn test_history(&self, name: &str) -> Result<Vec<CaseRun>, Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT cases.run_id, runs.label, cases.status, cases.time,
+                    (SELECT message FROM failures WHERE failures.case_id = cases.id)
+             FROM cases JOIN runs ON runs.id = cases.run_id
+             WHERE cases.name = ?1
+             ORDER BY cases.run_id, cases.id",
+        )?;
+        let rows = stmt.query_map([name], |row| {
+            Ok(CaseRun {
+                run_id: row.get(0)?,
+                label: row.get(1)?,
+                status: row.get(2)?,
+                time: row.get(3)?,
+                message: row.get(4)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Duration of the test case `name` in each of the last `last_runs` runs
+    /// it appears in, or in all of them, from the oldest to the newest run
+    ///
+    /// Returns pairs of run identifier and duration, summing up the durations
+    /// of a test case appearing several times in a run.
+    pub fn duration_trend(
+        &self,
+        name: &str,
+        last_runs: Option<usize>,
+    ) -> Result<Vec<(i64, f64)>, Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT run_id, total FROM (
+                 SELECT run_id, SUM(time) AS total FROM cases
+                 WHERE name = ?1 AND status != 'skipped'
+                 GROUP BY run_id ORDER BY run_id DESC LIMIT ?2
+             ) ORDER BY run_id",
+        )?;
+        let rows = stmt.query_map(params![name, limit(last_runs)], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Ratio of runs in which the test case `name` failed or ended in error,
+    /// among the last `last_runs` runs executing it, or among all of them
+    ///
+    /// Returns `None` if the test case was never executed.
+    pub fn failure_rate(&self, name: &str, last_runs: Option<usize>) -> Result<Option<f64>, Error> {
+        let rate = self
+            .conn
+            .query_row(
+                "SELECT AVG(failed) FROM (
+                     SELECT MAX(status IN ('failure', 'error')) AS failed FROM cases
+                     WHERE name = ?1 AND status != 'skipped'
+                     GROUP BY run_id ORDER BY run_id DESC LIMIT ?2
+                 )",
+                params![name, limit(last_runs)],
+                |row| row.get::<_, Option<f64>>(0),
+            )
+            .optional()?;
+        Ok(rate.flatten())
+    }
+}
+
+/// `LIMIT` matching `last_runs`, `-1` meaning no limit
+fn limit(last_runs: Option<usize>) -> i64 {
+    last_runs.map_or(-1, |n| n as i64)
+}
+
+/// Name of the tag of a rerun or flaky entry
+fn rerun_kind(kind: RerunOrFlakyKind) -> &'static str {
+    match kind {
+        RerunOrFlakyKind::FlakyFailure => "flakyFailure",
+        RerunOrFlakyKind::FlakyError => "flakyError",
+        RerunOrFlakyKind::RerunFailure => "rerunFailure",
+        RerunOrFlakyKind::RerunError => "rerunError",
+    }
+}
+
+/// Timestamp as stored in the database
+#[cfg(feature = "chrono")]
+fn timestamp(t: &Option<chrono::DateTime<chrono::Utc>>) -> Option<String> {
+    t.map(|t| t.to_rfc3339())
+}
+
+/// Timestamp as stored in the database
+#[cfg(not(feature = "chrono"))]
+fn timestamp(t: &Option<String>) -> Option<String> {
+    t.clone()
+}
+
+/// Store the test suite `ts` of the run `run_id`, along with its content
+fn insert_suite(
+    tx: &Transaction,
+    run_id: i64,
+    parent_id: Option<i64>,
+    ts: &TestSuite,
+) -> Result<(), Error> {
+    tx.execute(
+        "INSERT INTO suites (run_id, parent_id, name, timestamp, time, tests, errors, failures, skipped, hostname, source_file)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        params![
+            run_id,
+            parent_id,
+            ts.name,
+            timestamp(&ts.timestamp),
+            ts.time,
+            ts.tests as i64,
+            ts.errors as i64,
+            ts.failures as i64,
+            ts.skipped as i64,
+            ts.hostname,
+            ts.source_file,
+        ],
+    )?;
+    let suite_id = tx.last_insert_rowid();
+    for (name, value) in ts.properties.entries() {
+        tx.execute(
+            "INSERT INTO properties (suite_id, name, value) VALUES (?1, ?2, ?3)",
+            params![suite_id, name, value],
+        )?;
+    }
+    for tc in ts.cases.iter() {
+        insert_case(tx, run_id, suite_id, tc)?;
+    }
+    for nested in ts.suites.iter() {
+        insert_suite(tx, run_id, Some(suite_id), nested)?;
+    }
+    Ok(())
+}
+
+/// Store the test case `tc` of the test suite `suite_id`, along with its
+/// failure, reruns and properties
+fn insert_case(tx: &Transaction, run_id: i64, suite_id: i64, tc: &TestCase) -> Result<(), Error> {
+    tx.execute(
+        "INSERT INTO cases (run_id, suite_id, name, classname, status, time, file, line)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            run_id,
+            suite_id,
+            tc.name,
+            tc.classname,
+            tc.status.as_str(),
+            tc.time,
+            tc.file,
+            tc.line.map(|l| l as i64),
+        ],
+    )?;
+    let case_id = tx.last_insert_rowid();
+    let failure = match &tc.status {
+        TestStatus::Success => None,
+        TestStatus::Failure(f) => Some((&f.failure_type, &f.message, &f.text)),
+        TestStatus::Error(e) => Some((&e.error_type, &e.message, &e.text)),
+        TestStatus::Skipped(s) => Some((&s.skipped_type, &s.message, &s.text)),
+    };
+    if let Some((failure_type, message, text)) = failure {
+        tx.execute(
+            "INSERT INTO failures (case_id, kind, type, message, text) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![case_id, tc.status.as_str(), failure_type, message, text],
+        )?;
+    }
+    for rerun in tc.reruns.iter() {
+        tx.execute(
+            "INSERT INTO reruns (case_id, kind, type, message, time, text, stack_trace)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                case_id,
+                rerun_kind(rerun.kind),
+                rerun.rerun_type,
+                rerun.message,
+                rerun.time,
+                rerun.text,
+                rerun.stack_trace,
+            ],
+        )?;
+    }
+    for (name, value) in tc.properties.entries() {
+        tx.execute(
+            "INSERT INTO properties (case_id, name, value) VALUES (?1, ?2, ?3)",
+            params![case_id, name, value],
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,186 @@
+//! Render a report as TAP
+//!
+//! Each test suite is a subtest, in which each test case is a test point:
+//!
+//! - `ok` for passing test cases
+//! - `not ok` for failing test cases and test cases in error, followed by a
+//!   YAML diagnostic block with the message, type, duration, file and line of
+//!   the failure
+//! - `ok` with a `# SKIP` directive, carrying the message of the skipped tag,
+//!   for skipped test cases
+//!
+//! The test cases of a test suite come before its nested test suites.
+//!
+//! # Example
+//!
+//! ```
+//! use junit_parser::tap::{render, TapOptions};
+//! use std::io::Cursor;
+//!
+//! let xml = r#"<testsuite name="s"><testcase name="a"/><testcase name="b"><skipped message="later"/></testcase></testsuite>"#;
+//! let report = junit_parser::from_reader(Cursor::new(xml)).unwrap();
+//! assert_eq!(
+//!     render(&report, &TapOptions::default()),
+//!     "TAP version 14\n1..1\n# Subtest: s\n    1..2\n    ok 1 - a\n    ok 2 - b # SKIP later\nok 1 - s\n"
+//! );
+//! ```
+
+use crate::{TestCase, TestStatus, TestSuite, TestSuites};
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// Version of the TAP specification
+pub enum TapVersion {
+    /// TAP 13, whose consumers read indented subtests as comments
+    Tap13,
+    /// TAP 14
+    #[default]
+    Tap14,
+}
+
+#[derive(Debug, Clone, Default)]
+/// Options of the TAP renderer, see [`render`]
+pub struct TapOptions {
+    /// Version written in the header
+    pub version: TapVersion,
+}
+
+/// Indentation of a subtest
+const INDENT: &str = "    ";
+
+/// Escape `s` as the description or the directive of a test point
+fn escape_description(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('#', "\\#")
+        .replace(['\r', '\n'], " ")
+}
+
+/// `s` as a double-quoted YAML scalar
+fn yaml_string(s: &str) -> String {
+    let mut res = String::with_capacity(s.len() + 2);
+    res.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\r' => res.push_str("\\r"),
+            '\t' => res.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(res, "\\u{:04x}", c as u32);
+            }
+            c => res.push(c),
+        }
+    }
+    res.push('"');
+    res
+}
+
+/// Write the YAML diagnostic block of the failure of `tc`
+fn diagnostics(
+    out: &mut String,
+    indent: &str,
+    tc: &TestCase,
+    severity: &str,
+    message: &str,
+    failure_type: &str,
+    text: &str,
+) {
+    let _ = writeln!(out, "{}  ---", indent);
+    if !message.is_empty() {
+        let _ = writeln!(out, "{}  message: {}", indent, yaml_string(message));
+    }
+    let _ = writeln!(out, "{}  severity: {}", indent, severity);
+    if !failure_type.is_empty() {
+        let _ = writeln!(out, "{}  type: {}", indent, yaml_string(failure_type));
+    }
+    let _ = writeln!(out, "{}  duration_ms: {}", indent, tc.time * 1000.);
+    if let Some(file) = &tc.file {
+        let _ = writeln!(out, "{}  file: {}", indent, yaml_string(file));
+    }
+    if let Some(line) = tc.line {
+        let _ = writeln!(out, "{}  line: {}", indent, line);
+    }
+    if !text.is_empty() {
+        let _ = writeln!(out, "{}  stack: {}", indent, yaml_string(text));
+    }
+    let _ = writeln!(out, "{}  ...", indent);
+}
+
+/// Write the test point number `n` of the test case `tc`, returns whether it
+/// is `ok`
+fn case(out: &mut String, indent: &str, n: usize, tc: &TestCase) -> bool {
+    let description = escape_description(&tc.name);
+    match &tc.status {
+        TestStatus::Success => {
+            let _ = writeln!(out, "{}ok {} - {}", indent, n, description);
+            true
+        }
+        TestStatus::Skipped(s) => {
+            let _ = write!(out, "{}ok {} - {} # SKIP", indent, n, description);
+            if !s.message.is_empty() {
+                let _ = write!(out, " {}", escape_description(&s.message));
+            }
+            out.push('\n');
+            true
+        }
+        TestStatus::Failure(f) => {
+            let _ = writeln!(out, "{}not ok {} - {}", indent, n, description);
+            diagnostics(
+                out,
+                indent,
+                tc,
+                "fail",
+                &f.message,
+                &f.failure_type,
+                &f.text,
+            );
+            false
+        }
+        TestStatus::Error(e) => {
+            let _ = writeln!(out, "{}not ok {} - {}", indent, n, description);
+            diagnostics(out, indent, tc, "error", &e.message, &e.error_type, &e.text);
+            false
+        }
+    }
+}
+
+/// Write the test suite `ts` as the subtest number `n`, returns whether it is
+/// `ok`
+fn suite(out: &mut String, indent: &str, n: usize, ts: &TestSuite) -> bool {
+    let name = escape_description(&ts.name);
+    let _ = writeln!(out, "{}# Subtest: {}", indent, name);
+    let inner = format!("{}{}", indent, INDENT);
+    let _ = writeln!(out, "{}1..{}", inner, ts.cases.len() + ts.suites.len());
+    let mut ok = true;
+    for (i, tc) in ts.cases.iter().enumerate() {
+        ok &= case(out, &inner, i + 1, tc);
+    }
+    for (i, nested) in ts.suites.iter().enumerate() {
+        ok &= suite(out, &inner, ts.cases.len() + i + 1, nested);
+    }
+    let _ = writeln!(
+        out,
+        "{}{} {} - {}",
+        indent,
+        if ok { "ok" } else { "not ok" },
+        n,
+        name
+    );
+    ok
+}
+
+/// Render `suites` as a TAP stream
+pub fn render(suites: &TestSuites, options: &TapOptions) -> String {
+    let mut out = String::new();
+    let version = match options.version {
+        TapVersion::Tap13 => 13,
+        TapVersion::Tap14 => 14,
+    };
+    let _ = writeln!(out, "TAP version {}", version);
+    let _ = writeln!(out, "1..{}", suites.suites.len());
+    for (i, ts) in suites.suites.iter().enumerate() {
+        suite(&mut out, "", i + 1, ts);
+    }
+    out
+}
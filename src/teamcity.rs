@@ -0,0 +1,185 @@
+//! Replay a report as TeamCity service messages
+//!
+//! Each test suite is reported between `testSuiteStarted` and
+//! `testSuiteFinished` messages, and each test case between `testStarted` and
+//! `testFinished` messages, with `testFailed` for failures and errors,
+//! `testIgnored` for skipped test cases, and `testStdOut` and `testStdErr` for
+//! their outputs:
+//!
+//! ```text
+//! ##teamcity[testStarted name='test_cart' captureStandardOutput='false']
+//! ##teamcity[testFailed name='test_cart' message='expected |'10|'' details='']
+//! ##teamcity[testFinished name='test_cart' duration='12']
+//! ```
+//!
+//! # Example
+//!
+//! ```
+//! use junit_parser::teamcity::{messages, TeamCityOptions};
+//! use std::io::Cursor;
+//!
+//! let xml = r#"<testsuite name="s"><testcase name="a" time="0.5"/></testsuite>"#;
+//! let report = junit_parser::from_reader(Cursor::new(xml)).unwrap();
+//! let lines: Vec<String> = messages(&report, &TeamCityOptions::default())
+//!     .iter()
+//!     .map(ToString::to_string)
+//!     .collect();
+//! assert_eq!(
+//!     lines,
+//!     vec![
+//!         "##teamcity[testSuiteStarted name='s']",
+//!         "##teamcity[testStarted name='a' captureStandardOutput='false']",
+//!         "##teamcity[testFinished name='a' duration='500']",
+//!         "##teamcity[testSuiteFinished name='s']",
+//!     ]
+//! );
+//! ```
+
+use crate::{TestCase, TestStatus, TestSuite, TestSuites};
+use std::fmt;
+
+#[derive(Debug, Clone)]
+/// Options of [`messages`]
+pub struct TeamCityOptions {
+    /// Value of the `flowId` attribute of every message, to tell apart
+    /// reports replayed concurrently
+    pub flow_id: Option<String>,
+    /// Whether to report the `system-out` and `system-err` of test cases
+    pub outputs: bool,
+}
+
+impl Default for TeamCityOptions {
+    fn default() -> Self {
+        Self {
+            flow_id: None,
+            outputs: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A service message, formatted with [`fmt::Display`]
+pub struct ServiceMessage {
+    /// Name of the message, such as `testStarted`
+    pub name: String,
+    /// Attributes of the message, in order
+    pub attributes: Vec<(String, String)>,
+}
+
+/// Escape `s` as the value of an attribute of a service message
+fn escape(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '|' => res.push_str("||"),
+            '\'' => res.push_str("|'"),
+            '\n' => res.push_str("|n"),
+            '\r' => res.push_str("|r"),
+            '[' => res.push_str("|["),
+            ']' => res.push_str("|]"),
+            '\u{0085}' => res.push_str("|x"),
+            '\u{2028}' => res.push_str("|l"),
+            '\u{2029}' => res.push_str("|p"),
+            c => res.push(c),
+        }
+    }
+    res
+}
+
+impl fmt::Display for ServiceMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "##teamcity[{}", self.name)?;
+        for (key, value) in self.attributes.iter() {
+            write!(f, " {}='{}'", key, escape(value))?;
+        }
+        write!(f, "]")
+    }
+}
+
+/// Builder of the messages of a report
+struct Messages<'a> {
+    options: &'a TeamCityOptions,
+    messages: Vec<ServiceMessage>,
+}
+
+impl Messages<'_> {
+    /// Add the message `name` with `attributes`, and the flow id if any
+    fn push(&mut self, name: &str, attributes: &[(&str, &str)]) {
+        let mut attributes: Vec<(String, String)> = attributes
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        if let Some(flow_id) = &self.options.flow_id {
+            attributes.push(("flowId".to_string(), flow_id.clone()));
+        }
+        self.messages.push(ServiceMessage {
+            name: name.to_string(),
+            attributes,
+        });
+    }
+
+    /// Add the messages of the test case `tc`
+    fn case(&mut self, tc: &TestCase) {
+        let name = tc.name.as_str();
+        self.push(
+            "testStarted",
+            &[("name", name), ("captureStandardOutput", "false")],
+        );
+        if self.options.outputs {
+            if let Some(out) = tc.system_out.as_deref().filter(|s| !s.is_empty()) {
+                self.push("testStdOut", &[("name", name), ("out", out)]);
+            }
+            if let Some(err) = tc.system_err.as_deref().filter(|s| !s.is_empty()) {
+                self.push("testStdErr", &[("name", name), ("out", err)]);
+            }
+        }
+        match &tc.status {
+            TestStatus::Success => {}
+            TestStatus::Failure(f) => self.push(
+                "testFailed",
+                &[
+                    ("name", name),
+                    ("message", &f.message),
+                    ("details", &f.text),
+                ],
+            ),
+            TestStatus::Error(e) => self.push(
+                "testFailed",
+                &[
+                    ("name", name),
+                    ("message", &e.message),
+                    ("details", &e.text),
+                ],
+            ),
+            TestStatus::Skipped(s) => {
+                self.push("testIgnored", &[("name", name), ("message", &s.message)])
+            }
+        }
+        let duration = ((tc.time * 1000.).round() as u64).to_string();
+        self.push("testFinished", &[("name", name), ("duration", &duration)]);
+    }
+
+    /// Add the messages of the test suite `ts` and its nested test suites
+    fn suite(&mut self, ts: &TestSuite) {
+        self.push("testSuiteStarted", &[("name", &ts.name)]);
+        for tc in ts.cases.iter() {
+            self.case(tc);
+        }
+        for nested in ts.suites.iter() {
+            self.suite(nested);
+        }
+        self.push("testSuiteFinished", &[("name", &ts.name)]);
+    }
+}
+
+/// Service messages reporting the test suites and test cases of `suites`
+pub fn messages(suites: &TestSuites, options: &TeamCityOptions) -> Vec<ServiceMessage> {
+    let mut messages = Messages {
+        options,
+        messages: Vec::new(),
+    };
+    for ts in suites.suites.iter() {
+        messages.suite(ts);
+    }
+    messages.messages
+}
@@ -0,0 +1,451 @@
+//! Write a report as JUnit XML, normalized for a CI system
+//!
+//! CI systems interpret JUnit reports slightly differently, a
+//! [`Profile`] restructures the report for one of them before writing it:
+//!
+//! - [`Profile::GitLab`]: GitLab ignores nested test suites, they are
+//!   flattened into top-level test suites named after their path, such as
+//!   `parent.child`. GitLab only reads `[[ATTACHMENT|path]]` markers from the
+//!   `system-out` of test cases, the markers found in the `system-out` of a
+//!   test suite are copied to its test cases.
+//! - [`Profile::Jenkins`]: nested test suites are flattened likewise, and the
+//!   `classname` of test cases is prefixed with the package of their test
+//!   suite, or else its name, when it has no package, as Jenkins groups test
+//!   cases by the package part of their `classname`.
+//!
+//! Both profiles recompute the number of tests, failures, errors and skipped
+//! test cases of the test suites, and of the report, from their test cases.
+//! [`Profile::Canonical`] writes the report as is.
+//!
+//! # Example
+//!
+//! ```
+//! use junit_parser::writer::{to_string, Profile, WriteOptions};
+//! use std::io::Cursor;
+//!
+//! let xml = r#"<testsuite name="shop"><testsuite name="cart"><testcase name="total" classname="Cart"/></testsuite></testsuite>"#;
+//! let report = junit_parser::from_reader(Cursor::new(xml)).unwrap();
+//! let options = WriteOptions {
+//!     profile: Profile::Jenkins,
+//!     indent: None,
+//! };
+//! assert_eq!(
+//!     to_string(&report, &options).unwrap(),
+//!     "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+//!      <testsuites tests=\"1\" failures=\"0\" errors=\"0\" skipped=\"0\" time=\"0\">\
+//!      <testsuite name=\"shop.cart\" tests=\"1\" failures=\"0\" errors=\"0\" skipped=\"0\" time=\"0\">\
+//!      <testcase name=\"total\" classname=\"shop.cart.Cart\" time=\"0\"/>\
+//!      </testsuite></testsuites>"
+//! );
+//! ```
+
+use crate::{Error, Properties, RerunOrFlakyKind, TestCase, TestStatus, TestSuite, TestSuites};
+use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::Writer;
+use std::io::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// CI system the report is written for
+pub enum Profile {
+    /// Write the report as is
+    #[default]
+    Canonical,
+    /// GitLab CI
+    GitLab,
+    /// Jenkins, with its JUnit plugin
+    Jenkins,
+}
+
+#[derive(Debug, Clone)]
+/// Options of the writer
+pub struct WriteOptions {
+    /// CI system the report is written for
+    pub profile: Profile,
+    /// Number of spaces of indentation, or `None` to write everything on one
+    /// line
+    pub indent: Option<usize>,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        Self {
+            profile: Profile::default(),
+            indent: Some(2),
+        }
+    }
+}
+
+/// Prefix of the attachment markers
+const ATTACHMENT: &str = "[[ATTACHMENT|";
+
+/// `parent.name`, or `name` if `parent` is empty
+fn join(parent: &str, name: &str) -> String {
+    if parent.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", parent, name)
+    }
+}
+
+/// Append `text` to the output `out`, on a new line
+fn append(out: &mut Option<String>, text: &str) {
+    match out {
+        Some(out) if !out.is_empty() => {
+            if !out.ends_with('\n') {
+                out.push('\n');
+            }
+            out.push_str(text);
+        }
+        _ => *out = Some(text.to_string()),
+    }
+}
+
+/// Move the test suites nested in `ts` to `flat`, named after their path
+fn flatten(mut ts: TestSuite, flat: &mut Vec<TestSuite>) {
+    let nested = std::mem::take(&mut ts.suites);
+    // Test suites only containing other test suites are dropped
+    let container = ts.cases.is_empty()
+        && !nested.is_empty()
+        && ts.system_out.is_none()
+        && ts.system_err.is_none();
+    let name = ts.name.clone();
+    if !container {
+        flat.push(ts);
+    }
+    for mut child in nested {
+        child.name = join(&name, &child.name);
+        flatten(child, flat);
+    }
+}
+
+/// Update the counters of `ts` from its test cases
+fn recount(ts: &mut TestSuite) {
+    ts.tests = ts.cases.len() as u64;
+    ts.failures = ts.cases.iter().filter(|tc| tc.status.is_failure()).count() as u64;
+    ts.errors = ts.cases.iter().filter(|tc| tc.status.is_error()).count() as u64;
+    ts.skipped = ts.cases.iter().filter(|tc| tc.status.is_skipped()).count() as u64;
+    if ts.time <= 0. {
+        ts.time = ts.cases.iter().map(|tc| tc.time).sum();
+    }
+}
+
+/// Copy the attachment markers of the `system-out` of `ts` to its test cases
+fn copy_attachments(ts: &mut TestSuite) {
+    let markers: Vec<&str> = match &ts.system_out {
+        Some(out) => out
+            .lines()
+            .map(str::trim)
+            .filter(|l| l.starts_with(ATTACHMENT))
+            .collect(),
+        None => return,
+    };
+    for tc in ts.cases.iter_mut() {
+        for marker in markers.iter() {
+            if !tc
+                .system_out
+                .as_deref()
+                .map_or(false, |o| o.contains(marker))
+            {
+                append(&mut tc.system_out, marker);
+            }
+        }
+    }
+}
+
+/// Prefix the classname of the test cases of `ts` with its package, or else
+/// its name
+fn package_classnames(ts: &mut TestSuite) {
+    let package = ts.package.clone().unwrap_or_else(|| ts.name.clone());
+    for tc in ts.cases.iter_mut() {
+        let classname = match &tc.classname {
+            Some(classname) if classname.contains('.') => continue,
+            Some(classname) => join(&package, classname),
+            None if package.is_empty() => continue,
+            None => package.clone(),
+        };
+        tc.name = format!("{}::{}", classname, tc.original_name);
+        tc.classname = Some(classname);
+    }
+}
+
+/// Restructure `suites` for the CI system `profile`
+pub fn normalize(suites: &mut TestSuites, profile: Profile) {
+    if profile == Profile::Canonical {
+        return;
+    }
+    let mut flat = Vec::new();
+    for ts in std::mem::take(&mut suites.suites) {
+        flatten(ts, &mut flat);
+    }
+    for ts in flat.iter_mut() {
+        match profile {
+            Profile::GitLab => copy_attachments(ts),
+            Profile::Jenkins => package_classnames(ts),
+            Profile::Canonical => {}
+        }
+        recount(ts);
+    }
+    suites.suites = flat;
+    suites.tests = suites.suites.iter().map(|ts| ts.tests).sum();
+    suites.failures = suites.suites.iter().map(|ts| ts.failures).sum();
+    suites.errors = suites.suites.iter().map(|ts| ts.errors).sum();
+    suites.skipped = suites.suites.iter().map(|ts| ts.skipped).sum();
+    if suites.time <= 0. {
+        suites.time = suites.suites.iter().map(|ts| ts.time).sum();
+    }
+}
+
+/// Timestamp attribute value
+#[cfg(feature = "chrono")]
+fn timestamp(timestamp: &chrono::DateTime<chrono::Utc>) -> String {
+    timestamp.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true)
+}
+
+/// Timestamp attribute value
+#[cfg(not(feature = "chrono"))]
+fn timestamp<S: AsRef<str>>(timestamp: S) -> String {
+    timestamp.as_ref().to_string()
+}
+
+/// Writer of the XML elements
+struct XmlWriter<W: Write> {
+    writer: Writer<W>,
+}
+
+impl<W: Write> XmlWriter<W> {
+    /// Write the start tag of `name` with `attributes`, the empty tag if
+    /// `empty`
+    fn start(&mut self, name: &str, attributes: &[(&str, &str)], empty: bool) -> Result<(), Error> {
+        let start = BytesStart::new(name).with_attributes(attributes.iter().copied());
+        if empty {
+            self.writer.write_event(Event::Empty(start))?;
+        } else {
+            self.writer.write_event(Event::Start(start))?;
+        }
+        Ok(())
+    }
+
+    /// Write the end tag of `name`
+    fn end(&mut self, name: &str) -> Result<(), Error> {
+        self.writer.write_event(Event::End(BytesEnd::new(name)))?;
+        Ok(())
+    }
+
+    /// Write the element `name` containing `text`, if any
+    fn text_element(&mut self, name: &str, text: Option<&str>) -> Result<(), Error> {
+        if let Some(text) = text {
+            self.start(name, &[], false)?;
+            self.writer.write_event(Event::Text(BytesText::new(text)))?;
+            self.end(name)?;
+        }
+        Ok(())
+    }
+
+    /// Write the element `properties`, if any
+    fn properties(&mut self, properties: &Properties) -> Result<(), Error> {
+        let entries = properties.entries();
+        if entries.is_empty() {
+            return Ok(());
+        }
+        self.start("properties", &[], false)?;
+        for (name, value) in entries {
+            self.start("property", &[("name", name), ("value", value)], true)?;
+        }
+        self.end("properties")
+    }
+
+    /// Write the element `name` of a failure, error or skipped tag
+    fn status(&mut self, name: &str, message: &str, kind: &str, text: &str) -> Result<(), Error> {
+        let mut attributes = Vec::new();
+        if !message.is_empty() {
+            attributes.push(("message", message));
+        }
+        if !kind.is_empty() {
+            attributes.push(("type", kind));
+        }
+        self.start(name, &attributes, text.is_empty())?;
+        if !text.is_empty() {
+            self.writer.write_event(Event::Text(BytesText::new(text)))?;
+            self.end(name)?;
+        }
+        Ok(())
+    }
+
+    /// Write the element `testcase`
+    fn case(&mut self, tc: &TestCase) -> Result<(), Error> {
+        let time = tc.time.to_string();
+        let line = tc.line.map(|l| l.to_string());
+        let ts = tc.timestamp.as_ref().map(timestamp);
+        let mut attributes = vec![("name", tc.original_name.as_str())];
+        let optional = [
+            ("classname", tc.classname.as_deref()),
+            ("group", tc.group.as_deref()),
+            ("file", tc.file.as_deref()),
+            ("line", line.as_deref()),
+            ("timestamp", ts.as_deref()),
+        ];
+        attributes.extend(optional.iter().filter_map(|(k, v)| Some((*k, (*v)?))));
+        attributes.push(("time", &time));
+
+        let empty = tc.status.is_success()
+            && tc.reruns.is_empty()
+            && tc.system_out.is_none()
+            && tc.system_err.is_none()
+            && tc.properties.entries().is_empty();
+        self.start("testcase", &attributes, empty)?;
+        if empty {
+            return Ok(());
+        }
+        self.properties(&tc.properties)?;
+        match &tc.status {
+            TestStatus::Success => {}
+            TestStatus::Failure(f) => {
+                self.status("failure", &f.message, &f.failure_type, &f.text)?
+            }
+            TestStatus::Error(e) => self.status("error", &e.message, &e.error_type, &e.text)?,
+            TestStatus::Skipped(s) => {
+                self.status("skipped", &s.message, &s.skipped_type, &s.text)?
+            }
+        }
+        for rerun in tc.reruns.iter() {
+            let name = match rerun.kind {
+                RerunOrFlakyKind::FlakyFailure => "flakyFailure",
+                RerunOrFlakyKind::FlakyError => "flakyError",
+                RerunOrFlakyKind::RerunFailure => "rerunFailure",
+                RerunOrFlakyKind::RerunError => "rerunError",
+            };
+            let time = rerun.time.to_string();
+            let ts = rerun.timestamp.as_ref().map(timestamp);
+            let mut attributes = Vec::new();
+            if !rerun.message.is_empty() {
+                attributes.push(("message", rerun.message.as_str()));
+            }
+            if !rerun.rerun_type.is_empty() {
+                attributes.push(("type", rerun.rerun_type.as_str()));
+            }
+            if let Some(ts) = &ts {
+                attributes.push(("timestamp", ts));
+            }
+            attributes.push(("time", &time));
+            self.start(name, &attributes, false)?;
+            self.text_element("stackTrace", rerun.stack_trace.as_deref())?;
+            self.text_element("system-out", rerun.system_out.as_deref())?;
+            self.text_element("system-err", rerun.system_err.as_deref())?;
+            // The body comes last, so that no indentation is added after it
+            if !rerun.text.is_empty() {
+                self.writer
+                    .write_event(Event::Text(BytesText::new(&rerun.text)))?;
+            }
+            self.end(name)?;
+        }
+        self.text_element("system-out", tc.system_out.as_deref())?;
+        self.text_element("system-err", tc.system_err.as_deref())?;
+        self.end("testcase")
+    }
+
+    /// Write the element `testsuite` and its nested test suites
+    fn suite(&mut self, ts: &TestSuite) -> Result<(), Error> {
+        let counts = [
+            ts.tests.to_string(),
+            ts.failures.to_string(),
+            ts.errors.to_string(),
+            ts.skipped.to_string(),
+            ts.time.to_string(),
+        ];
+        let assertions = ts.assertions.map(|a| a.to_string());
+        let timestamp = ts.timestamp.as_ref().map(timestamp);
+        let mut attributes = vec![
+            ("name", ts.name.as_str()),
+            ("tests", &counts[0]),
+            ("failures", &counts[1]),
+            ("errors", &counts[2]),
+            ("skipped", &counts[3]),
+            ("time", &counts[4]),
+        ];
+        let optional = [
+            ("assertions", assertions.as_deref()),
+            ("timestamp", timestamp.as_deref()),
+            ("hostname", ts.hostname.as_deref()),
+            ("id", ts.id.as_deref()),
+            ("package", ts.package.as_deref()),
+            ("file", ts.file.as_deref()),
+            ("log", ts.log.as_deref()),
+            ("url", ts.url.as_deref()),
+            ("version", ts.version.as_deref()),
+        ];
+        attributes.extend(optional.iter().filter_map(|(k, v)| Some((*k, (*v)?))));
+
+        self.start("testsuite", &attributes, false)?;
+        self.properties(&ts.properties)?;
+        for tc in ts.cases.iter() {
+            self.case(tc)?;
+        }
+        for nested in ts.suites.iter() {
+            self.suite(nested)?;
+        }
+        self.text_element("system-out", ts.system_out.as_deref())?;
+        self.text_element("system-err", ts.system_err.as_deref())?;
+        self.end("testsuite")
+    }
+
+    /// Write the element `testsuites`
+    fn suites(&mut self, suites: &TestSuites) -> Result<(), Error> {
+        self.writer
+            .write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+        let counts = [
+            suites.tests.to_string(),
+            suites.failures.to_string(),
+            suites.errors.to_string(),
+            suites.skipped.to_string(),
+            suites.time.to_string(),
+        ];
+        let timestamp = suites.timestamp.as_ref().map(timestamp);
+        let mut attributes = Vec::new();
+        if !suites.name.is_empty() {
+            attributes.push(("name", suites.name.as_str()));
+        }
+        attributes.extend([
+            ("tests", counts[0].as_str()),
+            ("failures", &counts[1]),
+            ("errors", &counts[2]),
+            ("skipped", &counts[3]),
+            ("time", &counts[4]),
+        ]);
+        if let Some(timestamp) = &timestamp {
+            attributes.push(("timestamp", timestamp));
+        }
+        self.start("testsuites", &attributes, false)?;
+        for ts in suites.suites.iter() {
+            self.suite(ts)?;
+        }
+        self.end("testsuites")
+    }
+}
+
+/// Write `suites` as JUnit XML to `writer`, normalized for the profile of
+/// `options`
+pub fn to_writer<W: Write>(
+    suites: &TestSuites,
+    writer: W,
+    options: &WriteOptions,
+) -> Result<(), Error> {
+    let writer = match options.indent {
+        Some(indent) => Writer::new_with_indent(writer, b' ', indent),
+        None => Writer::new(writer),
+    };
+    let mut writer = XmlWriter { writer };
+    if options.profile == Profile::Canonical {
+        writer.suites(suites)
+    } else {
+        let mut suites = suites.clone();
+        normalize(&mut suites, options.profile);
+        writer.suites(&suites)
+    }
+}
+
+/// `suites` as JUnit XML, normalized for the profile of `options`
+pub fn to_string(suites: &TestSuites, options: &WriteOptions) -> Result<String, Error> {
+    let mut out = Vec::new();
+    to_writer(suites, &mut out, options)?;
+    Ok(String::from_utf8(out).map_err(|e| e.utf8_error())?)
+}
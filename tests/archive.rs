@@ -0,0 +1,123 @@
+//! Test parsing the reports found in zip and tar archives
+#![cfg(any(feature = "zip", feature = "tar"))]
+
+use junit_parser::archive::ArchiveReport;
+use junit_parser::ParseOptions;
+#[cfg(feature = "tar")]
+use std::io::Cursor;
+
+/// Check the reports found in the fixtures, which all hold the same files
+fn check_reports(reports: Vec<ArchiveReport>) {
+    let paths: Vec<&str> = reports.iter().map(|(p, _)| p.as_str()).collect();
+    assert_eq!(
+        paths,
+        vec![
+            "results/TEST-a.xml",
+            "results/nested/TEST-b.xml",
+            "results/broken.xml"
+        ]
+    );
+    let a = reports[0].1.as_ref().unwrap();
+    assert_eq!(a.suites[0].name, "a");
+    let b = reports[1].1.as_ref().unwrap();
+    assert_eq!(b.suites[0].name, "b");
+    assert!(reports[2].1.is_err());
+}
+
+#[test]
+#[cfg(feature = "zip")]
+/// Test parsing the reports of a zip archive
+fn test_zip_reports() {
+    let file = std::fs::File::open("tests/fixtures/archive/reports.zip").unwrap();
+    let r = junit_parser::archive::zip_reports(file, "**/*.xml", &ParseOptions::default());
+    assert!(r.is_ok());
+    check_reports(r.unwrap());
+}
+
+#[test]
+#[cfg(feature = "zip")]
+/// Test that `*` does not match path separators
+fn test_zip_reports_pattern() {
+    let file = std::fs::File::open("tests/fixtures/archive/reports.zip").unwrap();
+    let r =
+        junit_parser::archive::zip_reports(file, "results/TEST-*.xml", &ParseOptions::default());
+    assert!(r.is_ok());
+    let reports = r.unwrap();
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].0, "results/TEST-a.xml");
+}
+
+#[test]
+#[cfg(feature = "zip")]
+/// Test an invalid glob pattern
+fn test_zip_reports_invalid_pattern() {
+    let file = std::fs::File::open("tests/fixtures/archive/reports.zip").unwrap();
+    let r = junit_parser::archive::zip_reports(file, "[", &ParseOptions::default());
+    assert!(matches!(r, Err(junit_parser::Error::PatternError(_))));
+}
+
+#[test]
+#[cfg(feature = "zip")]
+/// Test reading something which is not a zip archive
+fn test_zip_reports_invalid_archive() {
+    let file = std::fs::File::open("tests/fixtures/archive/reports.tar").unwrap();
+    let r = junit_parser::archive::zip_reports(file, "**/*.xml", &ParseOptions::default());
+    assert!(matches!(r, Err(junit_parser::Error::ZipError(_))));
+}
+
+#[test]
+#[cfg(feature = "tar")]
+/// Test parsing the reports of a tar archive
+fn test_tar_reports() {
+    let tar = include_bytes!("fixtures/archive/reports.tar");
+    let r = junit_parser::archive::tar_reports(
+        Cursor::new(&tar[..]),
+        "**/*.xml",
+        &ParseOptions::default(),
+    );
+    assert!(r.is_ok());
+    check_reports(r.unwrap());
+}
+
+#[test]
+#[cfg(all(feature = "tar", feature = "gzip"))]
+/// Test parsing the reports of a gzip-compressed tar archive
+fn test_tar_gz_reports() {
+    let tar = include_bytes!("fixtures/archive/reports.tar.gz");
+    let r = junit_parser::archive::tar_reports(
+        Cursor::new(&tar[..]),
+        "**/*.xml",
+        &ParseOptions::default(),
+    );
+    assert!(r.is_ok());
+    check_reports(r.unwrap());
+}
+
+#[test]
+#[cfg(feature = "zip")]
+/// Test merging the reports of an archive
+fn test_merge_reports() {
+    let file = std::fs::File::open("tests/fixtures/archive/reports.zip").unwrap();
+    let options = ParseOptions::default();
+    let reports = junit_parser::archive::zip_reports(file, "**/TEST-*.xml", &options).unwrap();
+    let r = junit_parser::archive::merge_reports(reports);
+    assert!(r.is_ok());
+    let t = r.unwrap();
+    assert_eq!(t.suites.len(), 2);
+    assert_eq!(t.tests, 3);
+    assert_eq!(t.failures, 1);
+    assert_eq!(t.skipped, 1);
+    assert_eq!(t.time, 2.0);
+    assert_eq!(
+        t.suites[0].source_file,
+        Some("results/TEST-a.xml".to_string())
+    );
+    assert_eq!(
+        t.suites[1].source_file,
+        Some("results/nested/TEST-b.xml".to_string())
+    );
+
+    let file = std::fs::File::open("tests/fixtures/archive/reports.zip").unwrap();
+    let reports = junit_parser::archive::zip_reports(file, "**/*.xml", &options).unwrap();
+    assert!(junit_parser::archive::merge_reports(reports).is_err());
+}
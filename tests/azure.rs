@@ -0,0 +1,58 @@
+//! Test replaying reports as Azure Pipelines logging commands
+
+use junit_parser::azure::{complete, issues, publish, LoggingCommand};
+use std::io::Cursor;
+
+#[test]
+/// Test the issues of failures, errors and flaky test cases
+fn test_issues() {
+    let xml = r#"<testsuite>
+  <testcase name="pass"/>
+  <testcase name="fail" file="src/a;b.py" line="3"><failure type="AssertionError" message="100% off
+really"/></testcase>
+  <testcase name="error" file="src/c.py"><error/></testcase>
+  <testcase name="flaky"><flakyFailure type="Timeout"/></testcase>
+</testsuite>"#;
+    let report = junit_parser::from_reader(Cursor::new(xml)).unwrap();
+    let lines: Vec<String> = issues(&report)
+        .iter()
+        .map(LoggingCommand::to_string)
+        .collect();
+    assert_eq!(
+        lines,
+        vec![
+            "##vso[task.logissue type=error;sourcepath=src/a%3Bb.py;linenumber=3;code=AssertionError]fail: 100%AZP25 off%0Areally",
+            "##vso[task.logissue type=error;sourcepath=src/c.py]error",
+            "##vso[task.logissue type=warning;code=Timeout]flaky: passed after 1 failed attempts",
+        ]
+    );
+    assert_eq!(
+        complete(&report).to_string(),
+        "##vso[task.complete result=Failed]2 test cases failed"
+    );
+}
+
+#[test]
+/// Test the commands publishing reports and completing the task
+fn test_publish_complete() {
+    assert_eq!(
+        publish(&["a.xml", "b.xml"], Some("Unit tests")).to_string(),
+        "##vso[results.publish type=JUnit;runTitle=Unit tests]a.xml,b.xml"
+    );
+    let flaky = junit_parser::from_reader(Cursor::new(
+        r#"<testsuite><testcase name="a"><rerunFailure/></testcase><testcase name="b"/></testsuite>"#,
+    ))
+    .unwrap();
+    assert_eq!(
+        complete(&flaky).to_string(),
+        "##vso[task.complete result=SucceededWithIssues]1 test cases passed after reruns"
+    );
+    let passing = junit_parser::from_reader(Cursor::new(
+        r#"<testsuite><testcase name="a"/></testsuite>"#,
+    ))
+    .unwrap();
+    assert_eq!(
+        complete(&passing).to_string(),
+        "##vso[task.complete result=Succeeded]All test cases passed"
+    );
+}
@@ -0,0 +1,124 @@
+//! Test grouping failures and errors by their root cause
+#![cfg(feature = "cluster")]
+
+use junit_parser::cluster::{cluster_failures, normalize_message, ClusterOptions, FailureKind};
+use std::io::Cursor;
+
+#[test]
+/// Test masking the parts of messages varying from a run to another
+fn test_normalize_message() {
+    assert_eq!(
+        normalize_message("expected:<42> but was:<43>"),
+        "expected:<<n>> but was:<<n>>"
+    );
+    assert_eq!(
+        normalize_message("user 123e4567-e89b-12d3-a456-426614174000 not found"),
+        "user <uuid> not found"
+    );
+    assert_eq!(
+        normalize_message("object at 0x7f3a2c0b1d90, commit 3f2a9c1d8e7b"),
+        "object at <hex>, commit <hex>"
+    );
+    assert_eq!(
+        normalize_message(r"cannot read C:\Users\ci\AppData\tmp12.txt."),
+        "cannot read <path>."
+    );
+    assert_eq!(
+        normalize_message("GET https://example.com/api/v1 failed after 1.5s"),
+        "GET <path> failed after <n>.<n>s"
+    );
+    assert_eq!(
+        normalize_message("worker-3 timed out (tmp42)"),
+        "worker-<n> timed out (tmp<n>)"
+    );
+    assert_eq!(
+        normalize_message("a/b is not c and decade is not hex"),
+        "a/b is not c and decade is not hex"
+    );
+    assert_eq!(
+        normalize_message("expected -1 but was -2"),
+        "expected -<n> but was -<n>"
+    );
+    assert_eq!(
+        normalize_message("date été 2024 invalid"),
+        "date été <n> invalid"
+    );
+}
+
+#[test]
+/// Test grouping failures of a broken fixture together
+fn test_cluster_failures() {
+    let xml = r#"
+<testsuites>
+  <testsuite name="db">
+    <testcase name="t1"><error type="ConnectionError" message="connect to db-1:5432 failed">java.sql.SQLException: connect to db-1:5432 failed
+	at com.example.Db.connect(Db.java:10)
+	at com.example.DbTest.setUp(DbTest.java:20)</error></testcase>
+    <testcase name="t2"><error type="ConnectionError" message="connect to db-2:5432 failed">java.sql.SQLException: connect to db-2:5432 failed
+	at com.example.Db.connect(Db.java:11)
+	at com.example.DbTest.setUp(DbTest.java:20)</error></testcase>
+    <testcase name="ok"/>
+    <testsuite name="nested">
+      <testcase name="t3"><error type="ConnectionError" message="connect to db-3:5432 failed">java.sql.SQLException: connect to db-3:5432 failed
+	at com.example.Db.connect(Db.java:12)
+	at com.example.DbTest.setUp(DbTest.java:20)</error></testcase>
+    </testsuite>
+  </testsuite>
+  <testsuite name="math">
+    <testcase name="add"><failure type="AssertionError" message="expected 2 but was 3"/></testcase>
+    <testcase name="skip"><skipped/></testcase>
+    <testcase name="other"><error type="ConnectionError" message="connect to db-4:5432 failed">java.sql.SQLException: connect to db-4:5432 failed
+	at com.example.Cache.connect(Cache.java:5)</error></testcase>
+  </testsuite>
+</testsuites>"#;
+    let report = junit_parser::from_reader(Cursor::new(xml)).unwrap();
+    let options = ClusterOptions {
+        max_examples: 2,
+        ..Default::default()
+    };
+    let clusters = cluster_failures(&report, &options);
+    assert_eq!(clusters.len(), 3);
+
+    let c = &clusters[0];
+    assert_eq!(c.count, 3);
+    assert_eq!(c.signature.kind, FailureKind::Error);
+    assert_eq!(c.signature.failure_type, "ConnectionError");
+    assert_eq!(c.signature.message, "connect to db-<n>:<n> failed");
+    assert_eq!(c.signature.frames.len(), 2);
+    assert_eq!(c.signature.frames[0].function.as_deref(), Some("connect"));
+    assert_eq!(c.signature.frames[0].line, None);
+    assert_eq!(c.examples.len(), 2);
+    assert_eq!(c.examples[0].suite, "db");
+    assert_eq!(c.examples[0].name, "t1");
+    assert_eq!(c.examples[1].message, "connect to db-2:5432 failed");
+
+    assert_eq!(clusters[1].count, 1);
+    assert_eq!(clusters[1].signature.kind, FailureKind::Failure);
+    assert_eq!(clusters[1].signature.message, "expected <n> but was <n>");
+    assert!(clusters[1].signature.frames.is_empty());
+    assert_eq!(clusters[2].count, 1);
+    assert_eq!(
+        clusters[2].signature.frames[0].module.as_deref(),
+        Some("com.example.Cache")
+    );
+}
+
+#[test]
+/// Test that the message is taken from the body when there is no `message`
+/// attribute
+fn test_cluster_message_from_body() {
+    let xml = r#"
+<testsuite name="s">
+  <testcase name="a"><failure>
+
+  timeout after 30s
+  </failure></testcase>
+  <testcase name="b"><failure>timeout after 31s</failure></testcase>
+</testsuite>"#;
+    let report = junit_parser::from_reader(Cursor::new(xml)).unwrap();
+    let clusters = cluster_failures(&report, &ClusterOptions::default());
+    assert_eq!(clusters.len(), 1);
+    assert_eq!(clusters[0].count, 2);
+    assert_eq!(clusters[0].signature.message, "timeout after <n>s");
+    assert_eq!(clusters[0].examples[0].message, "timeout after 30s");
+}
@@ -0,0 +1,9 @@
+//! Helpers shared by the tests
+
+use junit_parser::TestSuites;
+use std::io::Cursor;
+
+/// Parse the report `xml`, which must be valid
+pub fn parse(xml: &str) -> TestSuites {
+    junit_parser::from_reader(Cursor::new(xml)).unwrap()
+}
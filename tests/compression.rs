@@ -0,0 +1,94 @@
+//! Test parsing compressed reports and reading reports from a path
+
+use std::io::Cursor;
+
+/// Check the content of the fixtures, which all hold the same report
+fn check_report(r: Result<junit_parser::TestSuites, junit_parser::Error>) {
+    assert!(r.is_ok(), "{:?}", r.err());
+    let t = r.unwrap();
+    assert_eq!(t.name, "compressed");
+    assert_eq!(t.suites.len(), 1);
+    let ts = &t.suites[0];
+    assert_eq!(ts.cases.len(), 2);
+    assert!(ts.cases[0].status.is_success());
+    assert!(ts.cases[1].status.is_failure());
+}
+
+#[test]
+/// Test reading an uncompressed report from a path
+fn test_from_path() {
+    check_report(junit_parser::from_path(
+        "tests/fixtures/compression/report.xml",
+    ));
+}
+
+#[test]
+/// Test reading a report from a path that does not exist
+fn test_from_path_missing() {
+    let r = junit_parser::from_path("tests/fixtures/compression/missing.xml");
+    assert!(matches!(r, Err(junit_parser::Error::IOError(_))));
+}
+
+#[test]
+#[cfg(feature = "gzip")]
+/// Test reading a gzip-compressed report
+fn test_gzip() {
+    let xml = include_bytes!("fixtures/compression/report.xml.gz");
+    check_report(junit_parser::from_reader(Cursor::new(xml)));
+    check_report(junit_parser::from_path(
+        "tests/fixtures/compression/report.xml.gz",
+    ));
+}
+
+#[test]
+#[cfg(feature = "zstd")]
+/// Test reading a zstd-compressed report
+fn test_zstd() {
+    let xml = include_bytes!("fixtures/compression/report.xml.zst");
+    check_report(junit_parser::from_reader(Cursor::new(xml)));
+    check_report(junit_parser::from_path(
+        "tests/fixtures/compression/report.xml.zst",
+    ));
+}
+
+#[test]
+#[cfg(feature = "xz")]
+/// Test reading a xz-compressed report
+fn test_xz() {
+    let xml = include_bytes!("fixtures/compression/report.xml.xz");
+    check_report(junit_parser::from_reader(Cursor::new(xml)));
+    check_report(junit_parser::from_path(
+        "tests/fixtures/compression/report.xml.xz",
+    ));
+}
+
+#[test]
+#[cfg(all(feature = "gzip", feature = "zstd", feature = "xz"))]
+/// Test detecting the compression from a reader returning a byte at a time
+fn test_detect_byte_by_byte() {
+    let gz: &[u8] = include_bytes!("fixtures/compression/report.xml.gz");
+    let zst: &[u8] = include_bytes!("fixtures/compression/report.xml.zst");
+    let xz: &[u8] = include_bytes!("fixtures/compression/report.xml.xz");
+    let xml: &[u8] = include_bytes!("fixtures/compression/report.xml");
+    for data in [gz, zst, xz, xml] {
+        check_report(junit_parser::from_reader(
+            std::io::BufReader::with_capacity(1, Cursor::new(data)),
+        ));
+    }
+}
+
+#[test]
+#[cfg(feature = "gzip")]
+/// Test that a truncated gzip stream is reported as an error
+fn test_gzip_truncated() {
+    let xml = include_bytes!("fixtures/compression/report.xml.gz");
+    let r = junit_parser::from_reader(Cursor::new(&xml[..xml.len() / 2]));
+    assert!(r.is_err());
+}
+
+#[test]
+/// Test that an uncompressed report is still parsed
+fn test_plain() {
+    let xml = include_bytes!("fixtures/compression/report.xml");
+    check_report(junit_parser::from_reader(Cursor::new(xml)));
+}
@@ -0,0 +1,103 @@
+//! Test analyzing the durations of test cases and test suites
+
+use junit_parser::durations::{
+    regressions, slowest_cases, slowest_suites, suite_percentiles, RegressionThresholds,
+};
+use std::io::Cursor;
+
+const REPORT: &str = r#"
+<testsuites>
+  <testsuite name="fast" time="0.6">
+    <testcase name="f1" time="0.1"/>
+    <testcase name="f2" time="0.2"/>
+    <testcase name="f3" time="0.3"/>
+  </testsuite>
+  <testsuite name="slow" time="12">
+    <testcase name="s1" time="5"/>
+    <testcase name="s2" time="1"/>
+    <testsuite name="nested" time="6">
+      <testcase name="n1" time="6"/>
+    </testsuite>
+  </testsuite>
+</testsuites>"#;
+
+#[test]
+/// Test getting the slowest test cases and test suites
+fn test_slowest() {
+    let report = junit_parser::from_reader(Cursor::new(REPORT)).unwrap();
+    let cases = slowest_cases(&report, 3);
+    let names: Vec<_> = cases.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, vec!["n1", "s1", "s2"]);
+    assert_eq!(cases[0].suite, "nested");
+    assert_eq!(cases[0].time, 6.);
+    assert_eq!(slowest_cases(&report, 100).len(), 6);
+
+    let suites = slowest_suites(&report, 2);
+    let names: Vec<_> = suites.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, vec!["slow", "nested"]);
+    assert_eq!(slowest_suites(&report, 100).len(), 3);
+}
+
+#[test]
+/// Test the percentiles of the durations per test suite
+fn test_suite_percentiles() {
+    let mut xml = String::from(r#"<testsuite name="s">"#);
+    for i in 1..=100 {
+        xml.push_str(&format!(r#"<testcase name="t{}" time="{}"/>"#, i, i));
+    }
+    xml.push_str("</testsuite>");
+    let report = junit_parser::from_reader(Cursor::new(xml)).unwrap();
+    let p = &suite_percentiles(&report)[0];
+    assert_eq!(p.suite, "s");
+    assert_eq!(p.cases, 100);
+    assert_eq!(p.p50, 50.);
+    assert_eq!(p.p90, 90.);
+    assert_eq!(p.p95, 95.);
+    assert_eq!(p.p99, 99.);
+    assert_eq!(p.max, 100.);
+
+    let report = junit_parser::from_reader(Cursor::new(REPORT)).unwrap();
+    let percentiles = suite_percentiles(&report);
+    assert_eq!(percentiles.len(), 3);
+    assert_eq!(percentiles[0].suite, "fast");
+    assert_eq!(percentiles[0].p50, 0.2);
+    assert_eq!(percentiles[0].p90, 0.3);
+    assert_eq!(percentiles[1].suite, "slow");
+    assert_eq!(percentiles[1].cases, 2);
+    assert_eq!(percentiles[1].p50, 1.);
+    assert_eq!(percentiles[2].suite, "nested");
+}
+
+#[test]
+/// Test finding the test cases slower than in a baseline report
+fn test_regressions() {
+    let baseline = junit_parser::from_reader(Cursor::new(REPORT)).unwrap();
+    let current = junit_parser::from_reader(Cursor::new(
+        r#"
+<testsuite name="all">
+  <testcase name="f1" time="0.15"/>
+  <testcase name="f2" time="0.5"/>
+  <testcase name="s1" time="5.5"/>
+  <testcase name="n1" time="9"/>
+  <testcase name="new" time="100"/>
+</testsuite>"#,
+    ))
+    .unwrap();
+
+    let r = regressions(&baseline, &current, &RegressionThresholds::default());
+    let names: Vec<_> = r.iter().map(|r| r.name.as_str()).collect();
+    // f1 grew by 50% but only by 0.05s, s1 by 0.5s but only by 10%
+    assert_eq!(names, vec!["n1", "f2"]);
+    assert_eq!(r[0].baseline, 6.);
+    assert_eq!(r[0].current, 9.);
+    assert_eq!(r[0].increase(), 3.);
+    assert_eq!(r[0].relative_increase(), 0.5);
+
+    let thresholds = RegressionThresholds {
+        relative: 0.,
+        absolute: 0.,
+    };
+    let r = regressions(&baseline, &current, &thresholds);
+    let names: Vec<_> = r.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, vec!["n1", "s1", "f2", "f1"]);
+}
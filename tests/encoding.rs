@@ -0,0 +1,74 @@
+//! Test parsing reports which are not encoded in UTF-8
+#![cfg(feature = "encoding")]
+
+use std::io::Cursor;
+
+/// Check the content of the fixtures, which all hold the same report
+fn check_report(xml: &[u8]) {
+    let cursor = Cursor::new(xml);
+    let r = junit_parser::from_reader(cursor);
+    assert!(r.is_ok(), "{:?}", r.err());
+    let t = r.unwrap();
+    assert_eq!(t.suites.len(), 1);
+    let ts = &t.suites[0];
+    assert_eq!(ts.name, "Café");
+    assert_eq!(ts.tests, 2);
+    assert_eq!(ts.failures, 1);
+    assert_eq!(ts.cases.len(), 2);
+    let tc = &ts.cases[0];
+    assert_eq!(tc.name, "Naïve::résumé");
+    assert!(tc.status.is_success());
+    let tc = &ts.cases[1];
+    assert_eq!(tc.name, "Naïve::façade");
+    let tf = tc.status.failure_as_ref();
+    assert_eq!(tf.message, "attendu « 1 »");
+    assert!(tf.text.ends_with("détails à voir"));
+}
+
+#[test]
+/// Test a UTF-16 little-endian report with a BOM
+fn test_utf16le() {
+    check_report(include_bytes!("fixtures/encoding/utf16le.xml"));
+}
+
+#[test]
+/// Test a UTF-16 big-endian report with a BOM
+fn test_utf16be() {
+    check_report(include_bytes!("fixtures/encoding/utf16be.xml"));
+}
+
+#[test]
+/// Test a report declared as `encoding="ISO-8859-1"`
+fn test_iso_8859_1() {
+    check_report(include_bytes!("fixtures/encoding/iso-8859-1.xml"));
+}
+
+#[test]
+/// Test a report declared as `encoding="windows-1252"`
+fn test_windows_1252() {
+    let xml = include_bytes!("fixtures/encoding/windows-1252.xml");
+    check_report(xml);
+    let t = junit_parser::from_reader(Cursor::new(xml)).unwrap();
+    let tf = t.suites[0].cases[1].status.failure_as_ref();
+    assert_eq!(tf.text, "€ détails à voir");
+}
+
+#[test]
+/// Test that a UTF-8 report with a BOM is still parsed
+fn test_utf8_bom() {
+    let xml = "\u{FEFF}<?xml version=\"1.0\" encoding=\"UTF-8\"?><testsuite name=\"Café\"/>";
+    let cursor = Cursor::new(xml);
+    let r = junit_parser::from_reader(cursor);
+    assert!(r.is_ok());
+    assert_eq!(r.unwrap().suites[0].name, "Café");
+}
+
+#[test]
+/// Test that an unknown declared encoding falls back to UTF-8
+fn test_unknown_encoding() {
+    let xml = r#"<?xml version="1.0" encoding="foo"?><testsuite name="Café"/>"#;
+    let cursor = Cursor::new(xml);
+    let r = junit_parser::from_reader(cursor);
+    assert!(r.is_ok());
+    assert_eq!(r.unwrap().suites[0].name, "Café");
+}
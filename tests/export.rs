@@ -0,0 +1,111 @@
+//! Test exporting test cases as CSV and JSON Lines
+#![cfg(feature = "export")]
+
+use junit_parser::export::{export, ExportOptions, Exporter, Format};
+use junit_parser::Error;
+
+mod common;
+use common::parse;
+
+/// Report with nested test suites, properties and values to escape
+const REPORT: &str = r#"<testsuites>
+  <testsuite name="cart">
+    <properties><property name="os" value="linux"/></properties>
+    <testcase name="total" classname="Cart" time="0.5" file="src/cart.py" line="12">
+      <failure type="AssertionError" message="expected &quot;10&quot;, got 11"/>
+    </testcase>
+    <testsuite name="db">
+      <testcase name="connect" time="1">
+        <properties><property name="os" value="windows"/></properties>
+        <flakyError/>
+      </testcase>
+      <testcase name="close"><skipped message="later"/></testcase>
+    </testsuite>
+  </testsuite>
+</testsuites>"#;
+
+/// `os` column, empty without properties
+fn os(value: &str) -> &str {
+    if cfg!(any(
+        feature = "properties_as_hashmap",
+        feature = "properties_as_vector"
+    )) {
+        value
+    } else {
+        ""
+    }
+}
+
+#[test]
+/// Test the rows in CSV
+fn test_csv() {
+    let options = ExportOptions {
+        properties: vec!["os".to_string()],
+        ..Default::default()
+    };
+    let mut out = Vec::new();
+    export(&parse(REPORT), &mut out, &options).unwrap();
+    let expected = format!(
+        "suite,classname,name,status,time,timestamp,file,line,failure_type,failure_message,reruns,os
+cart,Cart,total,failure,0.5,,src/cart.py,12,AssertionError,\"expected \"\"10\"\", got 11\",0,{}
+cart/db,,connect,success,1,,,,,,1,{}
+cart/db,,close,skipped,0,,,,,later,0,{}
+",
+        os("linux"),
+        os("windows"),
+        os("linux")
+    );
+    assert_eq!(String::from_utf8(out).unwrap(), expected);
+}
+
+#[test]
+/// Test the rows in JSON Lines, of several reports
+fn test_json_lines() {
+    let options = ExportOptions {
+        format: Format::JsonLines,
+        ..Default::default()
+    };
+    let mut exporter = Exporter::new(Vec::new(), options).unwrap();
+    exporter.write_suites(&parse(REPORT)).unwrap();
+    exporter
+        .write_suite(&parse(REPORT).suites[0].suites[0])
+        .unwrap();
+    let out = String::from_utf8(exporter.into_inner().unwrap()).unwrap();
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines.len(), 5);
+    assert_eq!(
+        lines[0],
+        r#"{"suite":"cart","classname":"Cart","name":"total","status":"failure","time":0.5,"timestamp":null,"file":"src/cart.py","line":12,"failure_type":"AssertionError","failure_message":"expected \"10\", got 11","reruns":0}"#
+    );
+    let row: serde_json::Value = serde_json::from_str(lines[3]).unwrap();
+    assert_eq!(row["suite"], "db");
+    assert_eq!(row["name"], "connect");
+    assert_eq!(row["reruns"], 1);
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+/// Test timestamps in RFC 3339 format
+fn test_timestamp() {
+    let report = parse(
+        r#"<testsuite name="s"><testcase name="a" timestamp="2024-05-01T10:00:00Z"/></testsuite>"#,
+    );
+    let mut out = Vec::new();
+    export(&report, &mut out, &ExportOptions::default()).unwrap();
+    assert!(String::from_utf8(out)
+        .unwrap()
+        .contains("s,,a,success,0,2024-05-01T10:00:00+00:00,"));
+}
+
+#[test]
+/// Test that properties named like another column are rejected
+fn test_duplicate_columns() {
+    for properties in [vec!["status"], vec!["os", "name"], vec!["os", "os"]] {
+        let options = ExportOptions {
+            format: Format::JsonLines,
+            properties: properties.iter().map(|p| p.to_string()).collect(),
+        };
+        let res = Exporter::new(Vec::new(), options);
+        assert!(matches!(res, Err(Error::DuplicateColumn(_))));
+    }
+}
@@ -0,0 +1,110 @@
+//! Test detecting flaky tests across a history of reports
+
+use junit_parser::flaky::flakiness_report;
+use junit_parser::TestSuites;
+use std::io::Cursor;
+
+/// Parse the reports of successive runs
+fn history(runs: &[&str]) -> Vec<TestSuites> {
+    runs.iter()
+        .map(|xml| junit_parser::from_reader(Cursor::new(xml)).unwrap())
+        .collect()
+}
+
+#[test]
+/// Test computing flip and failure rates, and ranking the tests
+fn test_flakiness_report() {
+    let history = history(&[
+        r#"<testsuite>
+  <testcase classname="c" name="stable"/>
+  <testcase classname="c" name="flaky"/>
+  <testcase classname="c" name="broken"><failure/></testcase>
+  <testcase classname="c" name="sometimes"/>
+</testsuite>"#,
+        r#"<testsuite>
+  <testcase classname="c" name="stable"/>
+  <testcase classname="c" name="flaky"><error/></testcase>
+  <testcase classname="c" name="broken"><failure/></testcase>
+  <testcase classname="c" name="sometimes"><skipped/></testcase>
+</testsuite>"#,
+        r#"<testsuite>
+  <testcase classname="c" name="stable"/>
+  <testcase classname="c" name="flaky"/>
+  <testcase classname="c" name="broken"><failure/></testcase>
+  <testcase classname="c" name="sometimes"><failure/></testcase>
+</testsuite>"#,
+    ]);
+    let report = flakiness_report(&history);
+    let names: Vec<_> = report.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec!["c::sometimes", "c::flaky", "c::broken", "c::stable"]
+    );
+
+    // The skipped run is not taken into account, and the failure rate breaks
+    // the tie on the flip rate
+    let t = &report[0];
+    assert_eq!(t.runs, 2);
+    assert_eq!(t.skipped, 1);
+    assert_eq!(t.flips, 1);
+    assert_eq!(t.flip_rate, 1.0);
+    assert_eq!(t.failure_rate, 0.5);
+    assert_eq!(t.last_failure, Some(2));
+
+    let t = &report[1];
+    assert_eq!(t.runs, 3);
+    assert_eq!(t.passes, 2);
+    assert_eq!(t.failures, 1);
+    assert_eq!(t.flips, 2);
+    assert_eq!(t.flip_rate, 1.0);
+    assert!((t.failure_rate - 1.0 / 3.0).abs() < 1e-9);
+    assert_eq!(t.last_failure, Some(1));
+    assert!(t.is_flaky());
+
+    let t = &report[2];
+    assert_eq!(t.flips, 0);
+    assert_eq!(t.failure_rate, 1.0);
+    assert_eq!(t.last_failure, Some(2));
+    assert!(!t.is_flaky());
+
+    let t = &report[3];
+    assert_eq!(t.failure_rate, 0.0);
+    assert_eq!(t.last_failure, None);
+    assert!(!t.is_flaky());
+}
+
+#[test]
+/// Test that tests needing reruns are reported as flaky
+fn test_flakiness_reruns() {
+    let history = history(&[
+        r#"<testsuite><testcase name="retried"><flakyFailure/></testcase><testcase name="ok"/></testsuite>"#,
+        r#"<testsuite><testcase name="retried"/><testcase name="ok"/></testsuite>"#,
+    ]);
+    let report = flakiness_report(&history);
+    assert_eq!(report[0].name, "retried");
+    assert_eq!(report[0].rerun_runs, 1);
+    assert_eq!(report[0].flips, 0);
+    assert!(report[0].is_flaky());
+    assert!(!report[1].is_flaky());
+}
+
+#[test]
+/// Test that a test case appearing several times in a run, or in nested
+/// suites, fails if any of its occurrences fails
+fn test_flakiness_duplicates() {
+    let history = history(&[
+        r#"<testsuites><testsuite><testsuite><testcase name="dup"/></testsuite><testcase name="dup"><failure/></testcase></testsuite></testsuites>"#,
+        r#"<testsuites><testsuite><testcase name="dup"/><testcase name="dup"/></testsuite></testsuites>"#,
+    ]);
+    let report = flakiness_report(&history);
+    assert_eq!(report.len(), 1);
+    assert_eq!(report[0].runs, 2);
+    assert_eq!(report[0].failures, 1);
+    assert_eq!(report[0].flips, 1);
+}
+
+#[test]
+/// Test an empty history
+fn test_flakiness_empty() {
+    assert!(flakiness_report(&[]).is_empty());
+}
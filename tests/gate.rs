@@ -0,0 +1,174 @@
+//! Test evaluating quality gate rules against reports
+#![cfg(feature = "gate")]
+
+use junit_parser::gate::{Rule, Rules};
+
+mod common;
+use common::parse;
+
+/// Report with 3 passing, 2 failing and 1 skipped test cases
+const REPORT: &str = r#"<testsuites time="12.5">
+  <testsuite name="s1" time="8">
+    <testcase name="a"/>
+    <testcase name="b"><failure message="boom"/></testcase>
+    <testcase name="c"><skipped/></testcase>
+  </testsuite>
+  <testsuite name="s2" time="4.5">
+    <testcase name="d"/>
+    <testcase name="e"/>
+    <testcase name="f"><error/></testcase>
+  </testsuite>
+</testsuites>"#;
+
+#[test]
+/// Test that empty rules always pass
+fn test_no_rules() {
+    let verdict = Rules::default().evaluate(&parse(REPORT), None);
+    assert!(verdict.passed);
+    assert!(verdict.violations.is_empty());
+    assert!(verdict.quarantined.is_empty());
+}
+
+#[test]
+/// Test the maximum number of failures and the minimum pass rate
+fn test_failures_and_pass_rate() {
+    let rules = Rules {
+        max_failures: Some(1),
+        min_pass_rate: Some(0.8),
+        ..Default::default()
+    };
+    let verdict = rules.evaluate(&parse(REPORT), None);
+    assert!(!verdict.passed);
+    assert_eq!(verdict.violations.len(), 2);
+    assert_eq!(verdict.violations[0].rule, Rule::MaxFailures);
+    assert_eq!(verdict.violations[0].tests, vec!["b", "f"]);
+    assert_eq!(verdict.violations[1].rule, Rule::MinPassRate);
+    assert_eq!(
+        verdict.violations[1].reason,
+        "pass rate of 60.00% below 80.00%"
+    );
+
+    let rules = Rules {
+        max_failures: Some(2),
+        min_pass_rate: Some(0.6),
+        ..Default::default()
+    };
+    assert!(rules.evaluate(&parse(REPORT), None).passed);
+}
+
+#[test]
+/// Test that quarantined test cases are ignored
+fn test_quarantine() {
+    let rules = Rules {
+        max_failures: Some(1),
+        min_pass_rate: Some(0.75),
+        quarantine: vec!["f".parse().unwrap()],
+        ..Default::default()
+    };
+    let verdict = rules.evaluate(&parse(REPORT), None);
+    assert!(verdict.passed);
+    assert_eq!(verdict.quarantined, vec!["f"]);
+
+    // Glob patterns, as in quarantine files
+    let rules = Rules::from_toml(
+        r#"
+max_failures = 0
+quarantine = ["glob:*"]
+"#,
+    )
+    .unwrap();
+    let verdict = rules.evaluate(&parse(REPORT), None);
+    assert!(verdict.passed);
+    assert_eq!(verdict.quarantined, vec!["b", "f"]);
+    assert!(Rules::from_toml(r#"quarantine = ["glob:["]"#).is_err());
+}
+
+#[test]
+/// Test failures compared to a baseline
+fn test_no_new_failures() {
+    let baseline = parse(
+        r#"<testsuite name="s1">
+  <testcase name="a"/>
+  <testcase name="b"><failure/></testcase>
+</testsuite>"#,
+    );
+    let rules = Rules {
+        no_new_failures: true,
+        ..Default::default()
+    };
+    let verdict = rules.evaluate(&parse(REPORT), Some(&baseline));
+    assert!(!verdict.passed);
+    assert_eq!(verdict.violations[0].rule, Rule::NoNewFailures);
+    assert_eq!(verdict.violations[0].tests, vec!["f"]);
+
+    // Ignored without baseline
+    assert!(rules.evaluate(&parse(REPORT), None).passed);
+}
+
+#[test]
+/// Test the maximum duration and the suites where skipping is not allowed
+fn test_duration_and_skipped() {
+    let rules = Rules {
+        max_duration: Some(10.),
+        no_skipped_in: vec!["s1".to_string()],
+        ..Default::default()
+    };
+    let verdict = rules.evaluate(&parse(REPORT), None);
+    assert_eq!(verdict.violations.len(), 2);
+    assert_eq!(verdict.violations[0].rule, Rule::MaxDuration);
+    assert_eq!(verdict.violations[1].rule, Rule::NoSkippedIn);
+    assert_eq!(verdict.violations[1].tests, vec!["c"]);
+
+    let rules = Rules {
+        max_duration: Some(20.),
+        no_skipped_in: vec!["s2".to_string()],
+        ..Default::default()
+    };
+    assert!(rules.evaluate(&parse(REPORT), None).passed);
+}
+
+#[test]
+/// Test reading the same rules from TOML, YAML and JSON
+fn test_formats() {
+    let expected = Rules {
+        max_failures: Some(0),
+        min_pass_rate: Some(0.95),
+        no_new_failures: true,
+        no_skipped_in: vec!["s1".to_string()],
+        ..Default::default()
+    };
+    let toml = r#"
+max_failures = 0
+min_pass_rate = 0.95
+no_new_failures = true
+no_skipped_in = ["s1"]
+"#;
+    let yaml = r#"
+max_failures: 0
+min_pass_rate: 0.95
+no_new_failures: true
+no_skipped_in: [s1]
+"#;
+    let json = r#"{"max_failures": 0, "min_pass_rate": 0.95, "no_new_failures": true, "no_skipped_in": ["s1"]}"#;
+    assert_eq!(Rules::from_toml(toml).unwrap(), expected);
+    assert_eq!(Rules::from_yaml(yaml).unwrap(), expected);
+    assert_eq!(Rules::from_json(json).unwrap(), expected);
+
+    assert!(Rules::from_toml("max_failure = 0").is_err());
+}
+
+#[test]
+/// Test reading rules from a file whose format is given by its extension
+fn test_from_path() {
+    let dir = std::env::temp_dir().join(format!("junit-parser-gate-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("rules.yml");
+    std::fs::write(&path, "max_failures: 3\n").unwrap();
+    let rules = Rules::from_path(&path).unwrap();
+    assert_eq!(rules.max_failures, Some(3));
+
+    let path = dir.join("rules.txt");
+    std::fs::write(&path, "max_failures: 3\n").unwrap();
+    assert!(Rules::from_path(&path).is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
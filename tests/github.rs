@@ -0,0 +1,138 @@
+//! Test annotating failures with GitHub Actions workflow commands
+
+use junit_parser::github::{annotations, Annotation, AnnotationOptions, Level};
+use junit_parser::TestSuites;
+
+mod common;
+use common::parse;
+
+/// Lines printed for the annotations of `suites`
+fn lines(suites: &TestSuites, options: &AnnotationOptions) -> Vec<String> {
+    annotations(suites, options)
+        .iter()
+        .map(Annotation::to_string)
+        .collect()
+}
+
+#[test]
+/// Test the annotations of failures, errors and flaky test cases
+fn test_annotations() {
+    let report = parse(
+        r#"<testsuite>
+  <testcase name="pass"/>
+  <testcase name="fail" file="/ci/work/src/a.py" line="3"><failure message="50% off: a, b"/></testcase>
+  <testcase name="error" file="src/b.py"><error>first line
+second line</error></testcase>
+  <testcase name="bare"><failure/></testcase>
+  <testcase name="flaky" file="/ci/work/src/c.py" line="7">
+    <flakyFailure message="timeout"/>
+    <flakyFailure message="reset"/>
+  </testcase>
+  <testcase name="skip"><skipped/></testcase>
+</testsuite>"#,
+    );
+    let options = AnnotationOptions {
+        rewrites: vec![
+            ("/ci/work/".to_string(), String::new()),
+            ("/ci/".to_string(), "root/".to_string()),
+        ],
+        ..Default::default()
+    };
+    assert_eq!(
+        lines(&report, &options),
+        vec![
+            "::error file=src/a.py,line=3,title=fail::50%25 off: a, b",
+            "::error file=src/b.py,title=error::first line",
+            "::error title=bare::failed",
+            "::warning file=src/c.py,line=7,title=flaky::passed after 2 failed attempts, first one: timeout",
+        ]
+    );
+
+    let options = AnnotationOptions {
+        flaky_warnings: false,
+        ..Default::default()
+    };
+    let annotations = annotations(&report, &options);
+    assert_eq!(annotations.len(), 3);
+    assert_eq!(annotations[0].file.as_deref(), Some("/ci/work/src/a.py"));
+}
+
+#[test]
+/// Test escaping the properties and the message
+fn test_escape() {
+    let annotation = Annotation {
+        level: Level::Error,
+        file: Some("a,b:c.py".to_string()),
+        line: Some(1),
+        title: Some("test::name".to_string()),
+        message: "line 1\r\nline 2".to_string(),
+    };
+    assert_eq!(
+        annotation.to_string(),
+        "::error file=a%2Cb%3Ac.py,line=1,title=test%3A%3Aname::line 1%0D%0Aline 2"
+    );
+}
+
+#[test]
+/// Test the maximum number of annotations
+fn test_max_annotations() {
+    let mut xml = String::from("<testsuite>");
+    for i in 0..10 {
+        xml.push_str(&format!("<testcase name=\"t{}\"><failure/></testcase>", i));
+    }
+    xml.push_str("</testsuite>");
+    let options = AnnotationOptions {
+        max_annotations: Some(4),
+        ..Default::default()
+    };
+    let lines = lines(&parse(&xml), &options);
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[2], "::error title=t2::failed");
+    assert_eq!(lines[3], "::notice::7 more test cases not annotated");
+
+    let options = AnnotationOptions {
+        max_annotations: Some(0),
+        ..Default::default()
+    };
+    assert!(annotations(&parse(&xml), &options).is_empty());
+
+    let options = AnnotationOptions {
+        max_annotations: None,
+        ..Default::default()
+    };
+    assert_eq!(annotations(&parse(&xml), &options).len(), 10);
+}
+
+#[cfg(feature = "stacktrace")]
+#[test]
+/// Test locating failures from their stack traces
+fn test_stack_trace_location() {
+    let report = parse(
+        r#"<testsuite>
+  <testcase name="test_connect" file="tests/test_db.py">
+    <failure message="unavailable">Traceback (most recent call last):
+  File "/ci/work/tests/test_db.py", line 4, in test_connect
+    db.connect()
+  File "/ci/work/app/db.py", line 12, in connect
+    raise DatabaseError("unavailable")
+db.DatabaseError: unavailable</failure>
+  </testcase>
+  <testcase name="test_other">
+    <error>Traceback (most recent call last):
+  File "/ci/work/app/other.py", line 8, in test_other
+KeyError: 'x'</error>
+  </testcase>
+</testsuite>"#,
+    );
+    let options = AnnotationOptions {
+        rewrites: vec![("/ci/work/".to_string(), String::new())],
+        ..Default::default()
+    };
+    assert_eq!(
+        lines(&report, &options),
+        vec![
+            "::error file=tests/test_db.py,line=4,title=test_connect::unavailable",
+            "::error file=app/other.py,line=8,title=test_other::KeyError: 'x'",
+        ]
+    );
+}
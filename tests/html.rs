@@ -0,0 +1,71 @@
+//! Test rendering reports as HTML pages
+#![cfg(feature = "html")]
+
+use junit_parser::html::{render, HtmlOptions};
+
+mod common;
+use common::parse;
+
+#[test]
+/// Test the content of the page
+fn test_render() {
+    let report = parse(
+        r#"<testsuites time="4">
+  <testsuite name="payments" time="3">
+    <properties><property name="os" value="linux &amp; co"/></properties>
+    <testcase name="pay" classname="Cart" time="2">
+      <failure message="expected &lt;10&gt;">at cart.py:12</failure>
+      <system-out>paying</system-out>
+    </testcase>
+    <testcase name="refund" time="1">
+      <flakyFailure message="timeout" type="Timeout">first try</flakyFailure>
+    </testcase>
+    <testsuite name="nested">
+      <testcase name="cancel"><skipped message="later"/></testcase>
+    </testsuite>
+  </testsuite>
+  <testsuite name="login" time="1">
+    <testcase name="login" time="1"/>
+  </testsuite>
+</testsuites>"#,
+    );
+    let options = HtmlOptions {
+        title: "Nightly <run>".to_string(),
+    };
+    let html = render(&report, &options);
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<title>Nightly &lt;run&gt;</title>"));
+    assert!(html.contains("<span>4 tests</span><span class=\"success\">2 passed</span>"));
+    // Everything is inlined
+    assert!(!html.contains("src="));
+    assert!(!html.contains("href="));
+
+    assert!(html.contains("data-name=\"Cart::pay\" data-status=\"failure\" data-time=\"2\""));
+    assert!(html.contains("expected &lt;10&gt;"));
+    assert!(html.contains("<pre>at cart.py:12</pre>"));
+    assert!(html.contains("<summary>Standard output</summary><pre>paying</pre>"));
+    assert!(html.contains("<summary>Reruns (1)</summary>"));
+    assert!(html.contains("<td>flaky failure</td><td>Timeout</td><td>timeout</td>"));
+    assert!(html.contains("<pre>first try</pre>"));
+    assert!(html.contains("data-status=\"skipped\""));
+    // Suites with failures are open, the other ones are collapsed
+    assert!(html.contains("<details class=\"suite\" open><summary>payments"));
+    assert!(html.contains("<details class=\"suite\"><summary>login"));
+    assert!(html.contains("<details class=\"suite\"><summary>nested"));
+    #[cfg(any(feature = "properties_as_hashmap", feature = "properties_as_vector"))]
+    assert!(html.contains("<tr><th>os</th><td>linux &amp; co</td></tr>"));
+}
+
+#[test]
+/// Test escaping the content of the report
+fn test_escape() {
+    let report = parse(
+        r#"<testsuite name="&lt;script&gt;">
+  <testcase name="a&quot;b"><error><![CDATA[</pre><script>alert(1)</script>]]></error></testcase>
+</testsuite>"#,
+    );
+    let html = render(&report, &HtmlOptions::default());
+    assert_eq!(html.matches("<script>").count(), 1);
+    assert!(html.contains("data-name=\"a&quot;b\""));
+    assert!(html.contains("&lt;/pre&gt;&lt;script&gt;alert(1)&lt;/script&gt;"));
+}
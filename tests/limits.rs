@@ -0,0 +1,224 @@
+//! Test the limits of `ParseOptions` on untrusted reports
+
+use junit_parser::{Error, ParseOptions, TRUNCATION_MARKER};
+use std::io::Cursor;
+
+/// Options with every limit set, as used to parse untrusted reports
+fn limited() -> ParseOptions {
+    ParseOptions {
+        max_depth: Some(32),
+        max_cases: Some(1000),
+        max_text_size: Some(1024),
+        max_total_size: Some(1 << 20),
+        ..Default::default()
+    }
+}
+
+const REPORT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="root" tests="3" failures="1">
+  <testsuite name="suite" tests="3" failures="1">
+    <properties><property name="env" value="ci"/></properties>
+    <testcase name="ok" classname="a.b" time="0.1"/>
+    <testcase name="ko" classname="a.b" time="0.2">
+      <failure message="boom" type="AssertionError">expected &lt;1&gt;<![CDATA[ but was <2>]]></failure>
+      <system-out>out</system-out>
+    </testcase>
+    <testcase name="flaky">
+      <flakyFailure type="E"><system-err>err</system-err></flakyFailure>
+    </testcase>
+    <testsuite name="nested"><testcase name="inner"/></testsuite>
+  </testsuite>
+</testsuites>"#;
+
+#[test]
+/// Test that a deeply nested report is rejected without overflowing the stack
+fn test_max_depth() {
+    let depth = 100_000;
+    let xml = "<testsuite>".repeat(depth) + &"</testsuite>".repeat(depth);
+    let r = junit_parser::from_reader_with_options(Cursor::new(xml), &limited());
+    assert!(matches!(r, Err(Error::DepthLimitExceeded(32))), "{:?}", r);
+}
+
+#[test]
+/// Test that nesting up to the limit is accepted
+fn test_max_depth_reached() {
+    let options = ParseOptions {
+        max_depth: Some(3),
+        ..Default::default()
+    };
+    let xml = "<testsuite>".repeat(3) + &"</testsuite>".repeat(3);
+    let r = junit_parser::from_reader_with_options(Cursor::new(&xml), &options);
+    assert!(r.is_ok(), "{:?}", r.err());
+    let xml = "<testsuite>".repeat(4) + &"</testsuite>".repeat(4);
+    let r = junit_parser::from_reader_with_options(Cursor::new(xml), &options);
+    assert!(matches!(r, Err(Error::DepthLimitExceeded(3))));
+}
+
+#[test]
+/// Test that sibling test suites do not add up in the depth
+fn test_max_depth_siblings() {
+    let options = ParseOptions {
+        max_depth: Some(2),
+        ..Default::default()
+    };
+    let xml = format!(
+        "<testsuites>{}</testsuites>",
+        "<testsuite><testsuite></testsuite></testsuite>".repeat(10)
+    );
+    let r = junit_parser::from_reader_with_options(Cursor::new(xml), &options);
+    assert!(r.is_ok(), "{:?}", r.err());
+}
+
+#[test]
+/// Test that the number of test cases is limited, including empty ones
+fn test_max_cases() {
+    let options = ParseOptions {
+        max_cases: Some(2),
+        ..Default::default()
+    };
+    let xml = r#"<testsuite><testcase name="a"/><testcase name="b"></testcase></testsuite>"#;
+    let r = junit_parser::from_reader_with_options(Cursor::new(xml), &options);
+    assert!(r.is_ok(), "{:?}", r.err());
+    let xml = r#"<testsuite><testcase/><testcase></testcase><testcase/></testsuite>"#;
+    let r = junit_parser::from_reader_with_options(Cursor::new(xml), &options);
+    assert!(matches!(r, Err(Error::CasesLimitExceeded(2))), "{:?}", r);
+}
+
+#[test]
+/// Test that long texts are truncated and end with the truncation marker
+fn test_max_text_size() {
+    let options = ParseOptions {
+        max_text_size: Some(10),
+        ..Default::default()
+    };
+    let xml = format!(
+        r#"<testsuite><testcase>
+  <failure>{0}</failure>
+  <system-out>{0}</system-out>
+  <system-err>short</system-err>
+</testcase></testsuite>"#,
+        "x".repeat(100_000)
+    );
+    let r = junit_parser::from_reader_with_options(Cursor::new(xml), &options);
+    assert!(r.is_ok(), "{:?}", r.err());
+    let tc = &r.unwrap().suites[0].cases[0];
+    let expected = format!("xxxxxxxxxx{}", TRUNCATION_MARKER);
+    assert_eq!(tc.status.failure_as_ref().text, expected);
+    assert_eq!(tc.system_out, Some(expected));
+    assert_eq!(tc.system_err, Some("short".to_string()));
+}
+
+#[test]
+#[cfg(feature = "properties_as_vector")]
+/// Test that long property values are truncated, from the attribute or the
+/// text of the property
+fn test_max_text_size_properties() {
+    let options = ParseOptions {
+        max_text_size: Some(10),
+        ..Default::default()
+    };
+    let xml = format!(
+        r#"<testsuite><properties>
+  <property name="attribute" value="{0}"/>
+  <property name="text">{0}</property>
+  <property name="short">value</property>
+</properties><testcase/></testsuite>"#,
+        "x".repeat(100_000)
+    );
+    let r = junit_parser::from_reader_with_options(Cursor::new(xml), &options);
+    assert!(r.is_ok(), "{:?}", r.err());
+    let expected = format!("xxxxxxxxxx{}", TRUNCATION_MARKER);
+    let properties = &r.unwrap().suites[0].properties.vec;
+    assert_eq!(properties[0], ("attribute".to_string(), expected.clone()));
+    assert_eq!(properties[1], ("text".to_string(), expected));
+    assert_eq!(properties[2], ("short".to_string(), "value".to_string()));
+}
+
+#[test]
+/// Test that texts made of several chunks are truncated once, on a char
+/// boundary
+fn test_max_text_size_chunks() {
+    let options = ParseOptions {
+        max_text_size: Some(6),
+        ..Default::default()
+    };
+    let xml = r#"<testsuite><testcase>
+  <error>abcd</error>
+  <system-out>aé<![CDATA[éé]]>more<!-- c -->text</system-out>
+</testcase></testsuite>"#;
+    let r = junit_parser::from_reader_with_options(Cursor::new(xml), &options);
+    assert!(r.is_ok(), "{:?}", r.err());
+    let tc = &r.unwrap().suites[0].cases[0];
+    assert_eq!(tc.status.error_as_ref().text, "abcd");
+    assert_eq!(tc.system_out, Some(format!("aéé{}", TRUNCATION_MARKER)));
+}
+
+#[test]
+/// Test that the total size of the report is limited
+fn test_max_total_size() {
+    let len = REPORT.len() as u64;
+    let options = ParseOptions {
+        max_total_size: Some(len),
+        ..Default::default()
+    };
+    let r = junit_parser::from_reader_with_options(Cursor::new(REPORT), &options);
+    assert!(r.is_ok(), "{:?}", r.err());
+    let options = ParseOptions {
+        max_total_size: Some(len - 1),
+        ..Default::default()
+    };
+    let r = junit_parser::from_reader_with_options(Cursor::new(REPORT), &options);
+    assert!(matches!(r, Err(Error::SizeLimitExceeded(_))), "{:?}", r);
+}
+
+#[test]
+/// Test that a large report is rejected before being fully read
+fn test_max_total_size_large() {
+    let xml = format!(
+        "<testsuite><testcase><system-out>{}</system-out></testcase></testsuite>",
+        "x".repeat(10 << 20)
+    );
+    let r = junit_parser::from_reader_with_options(Cursor::new(xml), &limited());
+    assert!(matches!(r, Err(Error::SizeLimitExceeded(_))), "{:?}", r);
+}
+
+#[test]
+/// Test that every prefix of a report is parsed without panicking
+fn test_truncated_inputs() {
+    for end in 0..REPORT.len() {
+        let _ = junit_parser::from_reader_with_options(
+            Cursor::new(&REPORT.as_bytes()[..end]),
+            &limited(),
+        );
+    }
+}
+
+#[test]
+/// Test that randomly mutated reports are parsed without panicking
+fn test_mutated_inputs() {
+    // xorshift, to get reproducible pseudo-random mutations
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    let alphabet = b"<>/=\"'&;![]-? \nxa";
+    for _ in 0..2000 {
+        let mut xml = REPORT.as_bytes().to_vec();
+        for _ in 0..(next() % 8 + 1) {
+            let i = (next() % xml.len() as u64) as usize;
+            let byte = match next() % 3 {
+                0 => alphabet[(next() % alphabet.len() as u64) as usize],
+                1 => next() as u8,
+                _ => {
+                    xml.remove(i);
+                    continue;
+                }
+            };
+            xml[i] = byte;
+        }
+        let _ = junit_parser::from_reader_with_options(Cursor::new(xml), &limited());
+    }
+}
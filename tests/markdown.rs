@@ -0,0 +1,108 @@
+//! Test rendering reports as Markdown
+
+use junit_parser::markdown::{render, MarkdownOptions};
+
+mod common;
+use common::parse;
+
+/// Report with failures, errors, skipped and flaky test cases
+const REPORT: &str = r#"<testsuites time="7.5">
+  <testsuite name="payments" time="5">
+    <testcase name="pay" time="2">
+      <failure message="expected &lt;10&gt; | got 11">line 1
+line 2
+line 3
+line 4</failure>
+    </testcase>
+    <testcase name="refund" time="1">
+      <flakyFailure message="timeout">first try</flakyFailure>
+    </testcase>
+    <testcase name="cancel" time="2"><skipped/></testcase>
+  </testsuite>
+  <testsuite name="login" time="2.5">
+    <testcase name="login" time="2.5"><error message="reset">```code```</error></testcase>
+  </testsuite>
+</testsuites>"#;
+
+#[test]
+/// Test the sections of the summary
+fn test_render() {
+    let md = render(&parse(REPORT), None, &MarkdownOptions::default());
+    assert!(md.starts_with("## ❌ Test results\n"));
+    assert!(md.contains("| 4 | 1 | 1 | 1 | 1 | 7.500s |"));
+    assert!(md.contains("### Failures (2)"));
+    // Pipes are only escaped in table cells
+    assert!(md.contains("<summary>❌ pay: expected &lt;10&gt; | got 11</summary>"));
+    assert!(md.contains("```\nline 1\nline 2\nline 3\nline 4\n```\n"));
+    // The fence is longer than the backticks of the body
+    assert!(md.contains("<summary>💥 login: reset</summary>"));
+    assert!(md.contains("````\n```code```\n````\n"));
+    assert!(md.contains("### Flaky tests (1)"));
+    assert!(md.contains("| refund | passed | 1 | timeout |"));
+    assert!(md.contains("| payments | 3 | 1 | 1 | 0 | 1 | 5.000s |"));
+    assert!(md.contains("| login | 1 | 0 | 0 | 1 | 0 | 2.500s |"));
+    assert!(!md.contains("not shown"));
+
+    let passing = parse(r#"<testsuite name="s"><testcase name="a"/></testsuite>"#);
+    let md = render(&passing, None, &MarkdownOptions::default());
+    assert!(md.starts_with("## ✅ Test results\n"));
+    assert!(!md.contains("### Failures"));
+    assert!(!md.contains("### Flaky"));
+}
+
+#[test]
+/// Test comparing to a baseline report
+fn test_baseline() {
+    let baseline = parse(
+        r#"<testsuite name="payments">
+  <testcase name="pay"/>
+  <testcase name="refund"><failure/></testcase>
+</testsuite>"#,
+    );
+    let md = render(&parse(REPORT), Some(&baseline), &MarkdownOptions::default());
+    assert!(md.contains("| 4 (+2) | 1 | 1 | 1 (+1) | 1 (+1) |"));
+    assert!(md.contains("### New failures (2)\n\n- pay\n- login\n"));
+    assert!(md.contains("### Fixed (1)\n\n- refund\n"));
+}
+
+#[test]
+/// Test truncating bodies and keeping the summary under the size budget
+fn test_limits() {
+    let options = MarkdownOptions {
+        max_body_lines: 2,
+        ..Default::default()
+    };
+    let md = render(&parse(REPORT), None, &options);
+    assert!(md.contains("```\nline 1\nline 2\n[...truncated]\n```\n"));
+
+    // A line longer than the room left is cut on a char boundary
+    let options = MarkdownOptions {
+        max_body_size: 10,
+        ..Default::default()
+    };
+    let long = parse(&format!(
+        r#"<testsuite name="s"><testcase name="a"><failure>{}</failure></testcase></testsuite>"#,
+        "é".repeat(100)
+    ));
+    let md = render(&long, None, &options);
+    assert!(md.contains("```\néééé[...truncated]\n```\n"));
+
+    let mut xml = String::from("<testsuite name=\"big\">");
+    for i in 0..500 {
+        xml.push_str(&format!(
+            "<testcase name=\"test{}\"><failure message=\"failed\">{}</failure></testcase>",
+            i,
+            "x".repeat(200)
+        ));
+    }
+    xml.push_str("</testsuite>");
+    let options = MarkdownOptions {
+        max_size: 10_000,
+        ..Default::default()
+    };
+    let md = render(&parse(&xml), None, &options);
+    assert!(md.len() <= 10_000);
+    assert!(md.contains("### Failures (500)"));
+    assert!(md.contains("<summary>❌ test0: failed</summary>"));
+    assert!(md.contains("failures not shown to keep the summary short"));
+}
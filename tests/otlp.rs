@@ -0,0 +1,185 @@
+//! Test exporting reports as OpenTelemetry traces
+#![cfg(feature = "otlp")]
+
+use chrono::{TimeZone, Utc};
+use junit_parser::otlp::{to_json, to_value, OtlpOptions};
+use serde_json::Value;
+use std::io::Cursor;
+
+/// Value of the attribute `key` of `span`
+fn attribute<'a>(span: &'a Value, key: &str) -> Option<&'a Value> {
+    span["attributes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|a| a["key"] == key)
+        .map(|a| &a["value"])
+}
+
+#[test]
+/// Test the spans of a report without timestamps
+fn test_spans() {
+    let xml = r#"<testsuites name="nightly" time="4">
+  <testsuite name="cart" time="3" hostname="ci-1">
+    <properties><property name="os" value="linux"/></properties>
+    <testcase name="total" classname="Cart" time="1" file="src/cart.py" line="12">
+      <failure type="AssertionError" message="expected 10"/>
+    </testcase>
+    <testcase name="empty" time="0.5"><skipped/></testcase>
+    <testsuite name="db" time="1">
+      <testcase name="connect" time="1"><rerunFailure/></testcase>
+    </testsuite>
+  </testsuite>
+  <testsuite name="login" time="0.25">
+    <testcase name="login" time="0.25"/>
+  </testsuite>
+</testsuites>"#;
+    let report = junit_parser::from_reader(Cursor::new(xml)).unwrap();
+    let options = OtlpOptions {
+        service_name: "shop".to_string(),
+        start: Some(Utc.timestamp_opt(1_000, 0).unwrap()),
+    };
+    let traces = to_value(&report, &options);
+    let resource = &traces["resourceSpans"][0];
+    assert_eq!(
+        resource["resource"]["attributes"][0]["value"]["stringValue"],
+        "shop"
+    );
+    let spans = resource["scopeSpans"][0]["spans"].as_array().unwrap();
+    let names: Vec<&str> = spans.iter().map(|s| s["name"].as_str().unwrap()).collect();
+    assert_eq!(
+        names,
+        vec!["nightly", "cart", "total", "empty", "db", "connect", "login", "login"]
+    );
+    let trace_id = spans[0]["traceId"].as_str().unwrap();
+    assert_eq!(trace_id.len(), 32);
+    assert!(spans.iter().all(|s| s["traceId"] == trace_id));
+    assert!(spans[0].get("parentSpanId").is_none());
+    assert_eq!(spans[1]["parentSpanId"], spans[0]["spanId"]);
+    assert_eq!(spans[2]["parentSpanId"], spans[1]["spanId"]);
+    assert_eq!(spans[5]["parentSpanId"], spans[4]["spanId"]);
+    assert_eq!(spans[6]["parentSpanId"], spans[0]["spanId"]);
+
+    // Test cases and test suites run one after the other
+    let times: Vec<(&str, &str)> = spans
+        .iter()
+        .map(|s| {
+            (
+                s["startTimeUnixNano"].as_str().unwrap(),
+                s["endTimeUnixNano"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(times[0], ("1000000000000", "1004000000000"));
+    assert_eq!(times[1], ("1000000000000", "1003000000000"));
+    assert_eq!(times[2], ("1000000000000", "1001000000000"));
+    assert_eq!(times[3], ("1001000000000", "1001500000000"));
+    assert_eq!(times[4], ("1001500000000", "1002500000000"));
+    assert_eq!(times[6], ("1003000000000", "1003250000000"));
+
+    assert_eq!(spans[0]["status"]["code"], 2);
+    assert_eq!(spans[6]["status"]["code"], 1);
+    let total = &spans[2];
+    assert_eq!(total["status"]["code"], 2);
+    assert_eq!(total["status"]["message"], "expected 10");
+    assert_eq!(
+        attribute(total, "test.case.name").unwrap()["stringValue"],
+        "Cart::total"
+    );
+    assert_eq!(
+        attribute(total, "test.case.result.status").unwrap()["stringValue"],
+        "fail"
+    );
+    assert_eq!(
+        attribute(total, "junit.failure.type").unwrap()["stringValue"],
+        "AssertionError"
+    );
+    assert_eq!(attribute(total, "code.lineno").unwrap()["intValue"], "12");
+    let empty = &spans[3];
+    assert!(empty["status"].get("code").is_none());
+    assert!(attribute(empty, "test.case.result.status").is_none());
+    assert_eq!(
+        attribute(&spans[5], "junit.reruns").unwrap()["intValue"],
+        "1"
+    );
+    assert_eq!(
+        attribute(&spans[1], "host.name").unwrap()["stringValue"],
+        "ci-1"
+    );
+    #[cfg(any(feature = "properties_as_hashmap", feature = "properties_as_vector"))]
+    assert_eq!(
+        attribute(&spans[1], "junit.property.os").unwrap()["stringValue"],
+        "linux"
+    );
+
+    let json = to_json(&report, &options).unwrap();
+    assert!(json.starts_with("{\"resourceSpans\":"));
+}
+
+#[test]
+/// Test the timestamps of test suites and test cases
+fn test_timestamps() {
+    let xml = r#"<testsuites>
+  <testsuite name="b" timestamp="2024-05-01T10:00:10Z" time="1">
+    <testcase name="b1" time="0.5" timestamp="2024-05-01T10:00:10.25Z"/>
+    <testcase name="b2" time="2"/>
+  </testsuite>
+  <testsuite name="a" timestamp="2024-05-01T10:00:00Z" time="1"/>
+</testsuites>"#;
+    let report = junit_parser::from_reader(Cursor::new(xml)).unwrap();
+    let traces = to_value(&report, &OtlpOptions::default());
+    let spans = &traces["resourceSpans"][0]["scopeSpans"][0]["spans"];
+    // The report starts with the earliest test suite
+    assert_eq!(spans[0]["startTimeUnixNano"], "1714557600000000000");
+    assert_eq!(spans[0]["endTimeUnixNano"], "1714557612750000000");
+    assert_eq!(spans[2]["startTimeUnixNano"], "1714557610250000000");
+    // The following test case starts at the end of the previous one, and
+    // the test suite lasts until its end
+    assert_eq!(spans[3]["startTimeUnixNano"], "1714557610750000000");
+    assert_eq!(spans[1]["endTimeUnixNano"], "1714557612750000000");
+    assert_eq!(spans[4]["startTimeUnixNano"], "1714557600000000000");
+}
+
+#[test]
+/// Test timestamps and durations that do not fit in nanoseconds
+fn test_out_of_range() {
+    let xml = r#"<testsuites>
+  <testsuite name="a" timestamp="1600-01-01T00:00:00Z" time="1">
+    <testcase name="a1" time="1e300"/>
+    <testcase name="a2" time="1"/>
+  </testsuite>
+</testsuites>"#;
+    let report = junit_parser::from_reader(Cursor::new(xml)).unwrap();
+    let options = OtlpOptions {
+        start: Some(Utc.timestamp_opt(1_000, 0).unwrap()),
+        ..Default::default()
+    };
+    let traces = to_value(&report, &options);
+    let spans = &traces["resourceSpans"][0]["scopeSpans"][0]["spans"];
+    // Timestamps before 1677 are ignored
+    assert_eq!(spans[0]["startTimeUnixNano"], "1000000000000");
+    assert_eq!(spans[1]["startTimeUnixNano"], "1000000000000");
+    // Durations saturate
+    assert_eq!(spans[2]["endTimeUnixNano"], i64::MAX.to_string());
+    assert_eq!(spans[3]["startTimeUnixNano"], i64::MAX.to_string());
+    assert_eq!(spans[3]["endTimeUnixNano"], i64::MAX.to_string());
+    assert_eq!(spans[0]["endTimeUnixNano"], i64::MAX.to_string());
+}
+
+#[test]
+#[cfg(not(feature = "chrono"))]
+/// Test timestamps without offset, or invalid, when they are not parsed as
+/// dates with the report
+fn test_timestamps_without_offset() {
+    let xml = r#"<testsuites>
+  <testsuite name="a" timestamp="2024-05-01T10:00:00" time="1"/>
+  <testsuite name="b" timestamp="2024-05-01T12:00:00+02:00" time="1"/>
+  <testsuite name="c" timestamp="yesterday" time="1"/>
+</testsuites>"#;
+    let report = junit_parser::from_reader(Cursor::new(xml)).unwrap();
+    let traces = to_value(&report, &OtlpOptions::default());
+    let spans = &traces["resourceSpans"][0]["scopeSpans"][0]["spans"];
+    assert_eq!(spans[1]["startTimeUnixNano"], "1714557600000000000");
+    assert_eq!(spans[2]["startTimeUnixNano"], "1714557600000000000");
+    assert_eq!(spans[3]["startTimeUnixNano"], "1714557601000000000");
+}
@@ -0,0 +1,107 @@
+//! Test recording the position of test suites and test cases
+
+use junit_parser::{ParseOptions, Position};
+use std::io::Cursor;
+
+fn tracking() -> ParseOptions {
+    ParseOptions {
+        track_positions: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+/// Test that positions are not recorded by default
+fn test_no_positions() {
+    let xml = r#"<testsuite><testcase name="ATest"/></testsuite>"#;
+    let cursor = Cursor::new(xml);
+    let r = junit_parser::from_reader(cursor);
+    assert!(r.is_ok());
+    let ts = &r.unwrap().suites[0];
+    assert_eq!(ts.position, None);
+    assert_eq!(ts.cases[0].position, None);
+}
+
+#[test]
+/// Test the positions of nested test suites and test cases
+fn test_positions() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites>
+  <testsuite name="outer">
+    <testsuite name="inner"></testsuite>
+    <testcase name="AnEmptyTest"/>
+    <testcase
+        classname="foo"
+        name="AFailingTest">
+      <failure>details</failure>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#;
+    let cursor = Cursor::new(xml);
+    let r = junit_parser::from_reader_with_options(cursor, &tracking());
+    assert!(r.is_ok());
+    let t = r.unwrap();
+    let check = |p: &Option<Position>, text: &str, line: u64| {
+        let p = p.as_ref().unwrap();
+        let range = p.byte_range.start as usize..p.byte_range.end as usize;
+        assert_eq!(&xml[range], text);
+        assert_eq!(p.line, line);
+    };
+    let outer = &t.suites[0];
+    assert!(
+        xml[outer.position.as_ref().unwrap().byte_range.start as usize..]
+            .starts_with(r#"<testsuite name="outer">"#)
+    );
+    assert!(
+        xml[..outer.position.as_ref().unwrap().byte_range.end as usize].ends_with("  </testsuite>")
+    );
+    assert_eq!(outer.position.as_ref().unwrap().line, 3);
+    check(
+        &outer.suites[0].position,
+        r#"<testsuite name="inner"></testsuite>"#,
+        4,
+    );
+    check(
+        &outer.cases[0].position,
+        r#"<testcase name="AnEmptyTest"/>"#,
+        5,
+    );
+    check(
+        &outer.cases[1].position,
+        r#"<testcase
+        classname="foo"
+        name="AFailingTest">
+      <failure>details</failure>
+    </testcase>"#,
+        6,
+    );
+}
+
+#[test]
+/// Test the position of a root `testsuite` element
+fn test_position_root_suite() {
+    let xml = "\n\n<testsuite name=\"root\">\n</testsuite>";
+    let cursor = Cursor::new(xml);
+    let r = junit_parser::from_reader_with_options(cursor, &tracking());
+    assert!(r.is_ok());
+    let ts = &r.unwrap().suites[0];
+    let p = ts.position.as_ref().unwrap();
+    assert_eq!(p.byte_range, 2..xml.len() as u64);
+    assert_eq!(p.line, 3);
+}
+
+#[test]
+/// Test that the source file is set when reading from a path
+fn test_source_file() {
+    let path = "tests/fixtures/compression/report.xml";
+    let r = junit_parser::from_path_with_options(path, &tracking());
+    assert!(r.is_ok());
+    let t = r.unwrap();
+    let ts = &t.suites[0];
+    assert_eq!(ts.source_file, Some(path.to_string()));
+    assert_eq!(ts.position.as_ref().unwrap().line, 3);
+    let tc = &ts.cases[1];
+    assert_eq!(tc.source_file, Some(path.to_string()));
+    assert_eq!(tc.position.as_ref().unwrap().line, 5);
+}
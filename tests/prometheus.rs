@@ -0,0 +1,86 @@
+//! Test exporting metrics in the Prometheus text format
+
+use junit_parser::prometheus::{render, Format, PrometheusOptions};
+
+mod common;
+use common::parse;
+
+/// Report with nested test suites, flaky test cases and duplicated names
+const REPORT: &str = r#"<testsuites>
+  <testsuite name="cart" time="2.5">
+    <testcase name="total" time="1"><failure/></testcase>
+    <testcase name="total" time="0.5"/>
+    <testcase name="refund" time="1">
+      <flakyFailure/>
+      <rerunError/>
+    </testcase>
+    <testsuite name="db &quot;main&quot;" time="1">
+      <testcase name="connect" time="1"><skipped/></testcase>
+    </testsuite>
+  </testsuite>
+  <testsuite name="cart" time="0.5">
+    <testcase name="empty" time="0.5"><error/></testcase>
+  </testsuite>
+</testsuites>"#;
+
+#[test]
+/// Test the metrics and their labels
+fn test_render() {
+    let options = PrometheusOptions {
+        labels: vec![("ci-job".to_string(), "unit\\tests".to_string())],
+        ..Default::default()
+    };
+    let metrics = render(&parse(REPORT), &options);
+    let expected = r#"# HELP junit_tests_total Number of test cases per status
+# TYPE junit_tests_total counter
+junit_tests_total{ci_job="unit\\tests",suite="cart",status="success"} 2
+junit_tests_total{ci_job="unit\\tests",suite="cart",status="failure"} 1
+junit_tests_total{ci_job="unit\\tests",suite="cart",status="error"} 1
+junit_tests_total{ci_job="unit\\tests",suite="cart",status="skipped"} 0
+junit_tests_total{ci_job="unit\\tests",suite="db \"main\"",status="success"} 0
+junit_tests_total{ci_job="unit\\tests",suite="db \"main\"",status="failure"} 0
+junit_tests_total{ci_job="unit\\tests",suite="db \"main\"",status="error"} 0
+junit_tests_total{ci_job="unit\\tests",suite="db \"main\"",status="skipped"} 1
+# HELP junit_suite_duration_seconds Duration of the test suite
+# TYPE junit_suite_duration_seconds gauge
+junit_suite_duration_seconds{ci_job="unit\\tests",suite="cart"} 3
+junit_suite_duration_seconds{ci_job="unit\\tests",suite="db \"main\""} 1
+# HELP junit_flaky_tests_total Number of test cases passing after reruns
+# TYPE junit_flaky_tests_total counter
+junit_flaky_tests_total{ci_job="unit\\tests",suite="cart"} 1
+junit_flaky_tests_total{ci_job="unit\\tests",suite="db \"main\""} 0
+# HELP junit_reruns_total Number of rerun and flaky entries per kind
+# TYPE junit_reruns_total counter
+junit_reruns_total{ci_job="unit\\tests",suite="cart",kind="flaky_failure"} 1
+junit_reruns_total{ci_job="unit\\tests",suite="cart",kind="flaky_error"} 0
+junit_reruns_total{ci_job="unit\\tests",suite="cart",kind="rerun_failure"} 0
+junit_reruns_total{ci_job="unit\\tests",suite="cart",kind="rerun_error"} 1
+junit_reruns_total{ci_job="unit\\tests",suite="db \"main\"",kind="flaky_failure"} 0
+junit_reruns_total{ci_job="unit\\tests",suite="db \"main\"",kind="flaky_error"} 0
+junit_reruns_total{ci_job="unit\\tests",suite="db \"main\"",kind="rerun_failure"} 0
+junit_reruns_total{ci_job="unit\\tests",suite="db \"main\"",kind="rerun_error"} 0
+"#;
+    assert_eq!(metrics, expected);
+}
+
+#[test]
+/// Test the durations of test cases and the OpenMetrics format
+fn test_options() {
+    let options = PrometheusOptions {
+        format: Format::OpenMetrics,
+        prefix: "ci.tests".to_string(),
+        case_durations: true,
+        ..Default::default()
+    };
+    let metrics = render(&parse(REPORT), &options);
+    assert!(metrics.contains("# TYPE ci_tests_tests counter\n"));
+    assert!(metrics.contains("ci_tests_tests_total{suite=\"cart\",status=\"success\"} 2\n"));
+    assert!(metrics.contains("# TYPE ci_tests_case_duration_seconds gauge\n"));
+    assert!(metrics.contains("ci_tests_case_duration_seconds{suite=\"cart\",case=\"total\"} 1.5\n"));
+    assert!(metrics.contains("ci_tests_case_duration_seconds{suite=\"cart\",case=\"empty\"} 0.5\n"));
+    assert!(metrics.ends_with("# EOF\n"));
+
+    let metrics = render(&parse(REPORT), &PrometheusOptions::default());
+    assert!(!metrics.contains("case_duration_seconds"));
+    assert!(!metrics.contains("# EOF"));
+}
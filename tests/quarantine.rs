@@ -0,0 +1,153 @@
+//! Test applying a list of known failures to reports
+#![cfg(feature = "quarantine")]
+
+use junit_parser::quarantine::{Action, Entry, NaiveDate, Pattern, Quarantine};
+use junit_parser::TestStatus;
+
+mod common;
+use common::parse;
+
+/// Report with failures and errors in nested test suites
+const REPORT: &str = r#"<testsuites tests="6" failures="2" errors="1" skipped="1">
+  <testsuite name="outer" tests="6" failures="2" errors="1" skipped="1">
+    <testcase name="com.acme.LoginTest.login"><failure message="boom">trace</failure></testcase>
+    <testcase name="com.acme.LoginTest.logout"/>
+    <testsuite name="inner" tests="4" failures="1" errors="1" skipped="1">
+      <testcase name="com.acme.CartTest.add"><failure/></testcase>
+      <testcase name="com.acme.CartTest.remove"><error/></testcase>
+      <testcase name="com.acme.CartTest.empty"><skipped/></testcase>
+      <testcase name="pay"/>
+    </testsuite>
+  </testsuite>
+</testsuites>"#;
+
+/// 18 October 2026
+fn today() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2026, 10, 18).unwrap()
+}
+
+/// Entry matching `pattern`
+fn entry(pattern: &str) -> Entry {
+    Entry::new(pattern.parse().unwrap())
+}
+
+#[test]
+/// Test parsing and matching patterns
+fn test_patterns() {
+    let exact: Pattern = "com.acme.CartTest.add".parse().unwrap();
+    assert!(exact.matches("com.acme.CartTest.add"));
+    assert!(!exact.matches("com.acme.CartTest.add2"));
+
+    let glob: Pattern = "glob:com.acme.Cart*".parse().unwrap();
+    assert!(glob.matches("com.acme.CartTest.add"));
+    assert!(!glob.matches("com.acme.LoginTest.login"));
+    assert_eq!(glob.to_string(), "glob:com.acme.Cart*");
+
+    let regex: Pattern = "regex:Test\\.(add|login)$".parse().unwrap();
+    assert!(regex.matches("com.acme.CartTest.add"));
+    assert!(regex.matches("com.acme.LoginTest.login"));
+    assert!(!regex.matches("com.acme.LoginTest.logout"));
+
+    assert!("glob:[".parse::<Pattern>().is_err());
+    assert!("regex:(".parse::<Pattern>().is_err());
+}
+
+#[test]
+/// Test rewriting quarantined failures as skipped, updating the counters
+fn test_skip() {
+    let mut report = parse(REPORT);
+    let mut cart = entry("glob:com.acme.CartTest.*");
+    cart.ticket = Some("ACME-42".to_string());
+    cart.reason = Some("flaky database".to_string());
+    let quarantine = Quarantine {
+        entries: vec![cart],
+    };
+    let applied = quarantine.apply(&mut report, today(), Action::Skip);
+    let names: Vec<_> = applied
+        .quarantined
+        .iter()
+        .map(|q| q.name.as_str())
+        .collect();
+    assert_eq!(
+        names,
+        vec!["com.acme.CartTest.add", "com.acme.CartTest.remove"]
+    );
+    assert_eq!(applied.quarantined[0].suite, "inner");
+    assert_eq!(applied.quarantined[0].ticket.as_deref(), Some("ACME-42"));
+    assert!(applied.expired.is_empty());
+    assert!(applied.unused.is_empty());
+
+    let inner = &report.suites[0].suites[0];
+    match &inner.cases[0].status {
+        TestStatus::Skipped(s) => {
+            assert_eq!(s.skipped_type, "quarantined");
+            assert_eq!(s.message, "quarantined (ACME-42): flaky database");
+        }
+        status => panic!("unexpected status {:?}", status),
+    }
+    assert!(inner.cases[1].status.is_skipped());
+    assert_eq!((inner.failures, inner.errors, inner.skipped), (0, 0, 3));
+    let outer = &report.suites[0];
+    assert_eq!((outer.failures, outer.errors, outer.skipped), (1, 0, 3));
+    assert_eq!((report.failures, report.errors, report.skipped), (1, 0, 3));
+    assert!(outer.cases[0].status.is_failure());
+}
+
+#[test]
+/// Test only listing the quarantined failures
+fn test_report_only() {
+    let mut report = parse(REPORT);
+    let quarantine = Quarantine {
+        entries: vec![entry("com.acme.LoginTest.login")],
+    };
+    let applied = quarantine.apply(&mut report, today(), Action::Report);
+    assert_eq!(applied.quarantined.len(), 1);
+    assert_eq!(applied.quarantined[0].suite, "outer");
+    assert!(report.suites[0].cases[0].status.is_failure());
+    assert_eq!(report.failures, 2);
+}
+
+#[test]
+/// Test expired and unused entries
+fn test_expired_and_unused() {
+    let mut report = parse(REPORT);
+    let mut expired = entry("com.acme.LoginTest.login");
+    expired.expires = NaiveDate::from_ymd_opt(2026, 10, 17);
+    let mut last_day = entry("com.acme.CartTest.add");
+    last_day.expires = Some(today());
+    let passing = entry("com.acme.LoginTest.logout");
+    let quarantine = Quarantine {
+        entries: vec![expired, last_day, passing, entry("regex:^nothing$")],
+    };
+    let applied = quarantine.apply(&mut report, today(), Action::Skip);
+    let names: Vec<_> = applied
+        .quarantined
+        .iter()
+        .map(|q| q.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["com.acme.CartTest.add"]);
+    assert_eq!(applied.expired, vec![0]);
+    assert_eq!(applied.unused, vec![2, 3]);
+    assert!(report.suites[0].cases[0].status.is_failure());
+}
+
+#[cfg(all(feature = "serde", feature = "gate"))]
+#[test]
+/// Test deserializing a list
+fn test_deserialize() {
+    let json = r#"[
+  {"test": "glob:com.acme.Cart*", "expires": "2026-12-31", "ticket": "ACME-42"},
+  {"test": "com.acme.LoginTest.login", "reason": "known"}
+]"#;
+    let quarantine: Quarantine = serde_json::from_str(json).unwrap();
+    assert_eq!(quarantine.entries.len(), 2);
+    assert_eq!(
+        quarantine.entries[0].expires,
+        NaiveDate::from_ymd_opt(2026, 12, 31)
+    );
+    assert_eq!(quarantine.entries[1].reason.as_deref(), Some("known"));
+    assert!(matches!(quarantine.entries[1].pattern, Pattern::Exact(_)));
+
+    assert!(serde_json::from_str::<Quarantine>(r#"[{"test": "regex:("}]"#).is_err());
+    assert!(serde_json::from_str::<Quarantine>(r#"[{"test": "a", "expires": "soon"}]"#).is_err());
+}
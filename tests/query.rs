@@ -0,0 +1,207 @@
+//! Test selecting test cases with filters
+#![cfg(feature = "query")]
+
+use junit_parser::pattern::Pattern;
+use junit_parser::query::{prune, select, Comparison, Filter, Status};
+use junit_parser::TestSuites;
+
+mod common;
+use common::parse;
+
+/// Report with nested test suites and properties
+const REPORT: &str = r#"<testsuites>
+  <testsuite name="com.acme.payments" file="payments.py">
+    <properties><property name="team" value="core"/></properties>
+    <testcase name="pay" classname="Checkout" time="3.5">
+      <failure type="TimeoutError" message="timed out after 3s"/>
+    </testcase>
+    <testcase name="refund" classname="Checkout" time="0.5">
+      <failure type="AssertionError" message="expected 10"/>
+    </testcase>
+    <testcase name="cancel" classname="Checkout" time="2.5"/>
+    <testsuite name="com.acme.payments.cards">
+      <testcase name="visa" time="4" file="cards.py">
+        <properties><property name="team" value="cards"/></properties>
+        <error type="IOError" message="connection reset"/>
+      </testcase>
+      <testcase name="amex" time="1"><skipped message="not supported"/></testcase>
+    </testsuite>
+  </testsuite>
+  <testsuite name="com.acme.login">
+    <testcase name="login" group="auth" time="5"><failure/></testcase>
+  </testsuite>
+</testsuites>"#;
+
+/// Original names of the test cases of `report` selected by `expression`
+fn names(report: &TestSuites, expression: &str) -> Vec<String> {
+    let filter: Filter = expression.parse().unwrap();
+    select(report, &filter)
+        .into_iter()
+        .map(|(_, tc)| tc.original_name.clone())
+        .collect()
+}
+
+#[test]
+/// Test filters on text fields
+fn test_text_fields() {
+    let report = parse(REPORT);
+    assert_eq!(names(&report, "name = Checkout::pay"), vec!["pay"]);
+    assert_eq!(names(&report, "name != Checkout::pay").len(), 5);
+    assert_eq!(names(&report, "name = visa"), vec!["visa"]);
+    assert_eq!(names(&report, "classname ~ Check*").len(), 3);
+    assert_eq!(names(&report, "group = auth"), vec!["login"]);
+    assert_eq!(
+        names(&report, r#"suite ~ "com.acme.payments.*""#),
+        vec!["visa", "amex"]
+    );
+    assert_eq!(names(&report, "suite =~ ^com\\.acme\\.payments").len(), 5);
+    assert_eq!(names(&report, "file = cards.py"), vec!["visa"]);
+    assert_eq!(names(&report, "file = payments.py").len(), 3);
+    assert_eq!(names(&report, "type =~ Timeout|IO"), vec!["pay", "visa"]);
+    assert_eq!(names(&report, r#"message ~ "*after 3s""#), vec!["pay"]);
+    assert_eq!(names(&report, "message !=~ ^expected").len(), 5);
+}
+
+#[test]
+/// Test filters on status and time
+fn test_status_and_time() {
+    let report = parse(REPORT);
+    assert_eq!(
+        names(&report, "status = failure"),
+        vec!["pay", "refund", "login"]
+    );
+    assert_eq!(names(&report, "status = error"), vec!["visa"]);
+    assert_eq!(names(&report, "status = skipped"), vec!["amex"]);
+    assert_eq!(names(&report, "status = success"), vec!["cancel"]);
+    assert_eq!(names(&report, "status != success").len(), 5);
+    assert_eq!(names(&report, "time > 3.5"), vec!["visa", "login"]);
+    assert_eq!(names(&report, "time >= 3.5").len(), 3);
+    assert_eq!(names(&report, "time <= 1"), vec!["refund", "amex"]);
+    assert_eq!(names(&report, "time = 2.5"), vec!["cancel"]);
+}
+
+#[test]
+/// Test combining filters
+fn test_combinations() {
+    let report = parse(REPORT);
+    assert_eq!(
+        names(
+            &report,
+            r#"status = failure and suite ~ "com.acme.payments*" and time > 2"#
+        ),
+        vec!["pay"]
+    );
+    assert_eq!(
+        names(&report, "status = error or status = skipped"),
+        vec!["visa", "amex"]
+    );
+    assert_eq!(
+        names(&report, "not (status = failure or status = error)"),
+        vec!["cancel", "amex"]
+    );
+    // `and` binds tighter than `or`
+    assert_eq!(
+        names(
+            &report,
+            "name = auth::login or status = failure and time < 1"
+        ),
+        vec!["refund", "login"]
+    );
+    assert_eq!(
+        names(&report, "NOT status = success AND time > 4"),
+        vec!["login"]
+    );
+}
+
+#[cfg(any(feature = "properties_as_hashmap", feature = "properties_as_vector"))]
+#[test]
+/// Test filters on properties of test cases and test suites
+fn test_properties() {
+    let report = parse(REPORT);
+    assert_eq!(
+        names(&report, "property.team = core"),
+        vec!["pay", "refund", "cancel"]
+    );
+    assert_eq!(names(&report, "property.team = cards"), vec!["visa"]);
+    assert!(names(&report, "property.owner ~ *").is_empty());
+}
+
+#[test]
+/// Test building filters programmatically
+fn test_build() {
+    let report = parse(REPORT);
+    let filter = Filter::And(vec![
+        Filter::Status(Status::Failure),
+        Filter::Time(Comparison::Greater, 3.),
+        Filter::Not(Box::new(Filter::Name(Pattern::Exact(
+            "auth::login".to_string(),
+        )))),
+    ]);
+    let selected = select(&report, &filter);
+    assert_eq!(selected.len(), 1);
+    assert_eq!(selected[0].0.name, "com.acme.payments");
+    assert_eq!(selected[0].1.name, "Checkout::pay");
+}
+
+#[test]
+/// Test pruning a report, recomputing its counters
+fn test_prune() {
+    let filter: Filter = "status != success and time >= 1".parse().unwrap();
+    let pruned = prune(parse(REPORT), &filter);
+    assert_eq!(pruned.suites.len(), 2);
+    let payments = &pruned.suites[0];
+    assert_eq!(payments.cases.len(), 1);
+    assert_eq!(payments.cases[0].original_name, "pay");
+    assert_eq!(payments.suites[0].cases.len(), 2);
+    assert_eq!(
+        (
+            payments.tests,
+            payments.failures,
+            payments.errors,
+            payments.skipped
+        ),
+        (3, 1, 1, 1)
+    );
+    assert_eq!(
+        (pruned.tests, pruned.failures, pruned.errors, pruned.skipped),
+        (4, 2, 1, 1)
+    );
+
+    let pruned = prune(parse(REPORT), &"name = visa".parse().unwrap());
+    assert!(pruned.suites[0].cases.is_empty());
+    assert_eq!(pruned.suites[0].suites[0].cases.len(), 1);
+    assert_eq!(pruned.tests, 1);
+
+    let pruned = prune(parse(REPORT), &"name = nothing".parse().unwrap());
+    assert!(pruned.suites.is_empty());
+    assert_eq!(pruned.tests, 0);
+}
+
+#[test]
+/// Test invalid expressions
+fn test_invalid() {
+    for expression in [
+        "",
+        "name",
+        "name =",
+        "name < a",
+        "unknown = a",
+        "property. = a",
+        "status = broken",
+        "status ~ failure",
+        "time ~ 1",
+        "time > soon",
+        "(name = a",
+        "name = a)",
+        "name = a and",
+        r#"name = "a"#,
+        "name ~ [",
+        "name =~ (",
+    ] {
+        assert!(
+            expression.parse::<Filter>().is_err(),
+            "{} should be invalid",
+            expression
+        );
+    }
+}
@@ -0,0 +1,70 @@
+//! Test exporting failures as SARIF logs
+#![cfg(feature = "sarif")]
+
+use junit_parser::sarif::{to_json, to_value, SarifOptions};
+use std::io::Cursor;
+
+#[test]
+/// Test the results and rules of the log
+fn test_sarif() {
+    let xml = r#"<testsuites>
+  <testsuite name="cart">
+    <testcase name="total" classname="Cart" file="src\cart.py" line="12">
+      <failure type="AssertionError" message="expected 10">at cart.py:12</failure>
+    </testcase>
+    <testcase name="pass"/>
+    <testcase name="bare"><failure>first line
+second line</failure></testcase>
+    <testcase name="skip"><skipped/></testcase>
+  </testsuite>
+  <testsuite name="db">
+    <testcase name="connect" file="src/db.py"><error type="IOError"/></testcase>
+    <testcase name="close"><failure type="AssertionError" message="closed"/></testcase>
+  </testsuite>
+</testsuites>"#;
+    let report = junit_parser::from_reader(Cursor::new(xml)).unwrap();
+    let options = SarifOptions {
+        tool_name: "tests".to_string(),
+        tool_version: None,
+    };
+    let log = to_value(&report, &options);
+    assert_eq!(log["version"], "2.1.0");
+    let run = &log["runs"][0];
+    assert_eq!(run["tool"]["driver"]["name"], "tests");
+    assert!(run["tool"]["driver"].get("version").is_none());
+    let rules: Vec<&str> = run["tool"]["driver"]["rules"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(rules, vec!["AssertionError", "test-failure", "IOError"]);
+
+    let results = run["results"].as_array().unwrap();
+    assert_eq!(results.len(), 4);
+    assert_eq!(results[0]["message"]["text"], "expected 10");
+    assert_eq!(results[0]["properties"]["suite"], "cart");
+    assert_eq!(results[0]["properties"]["details"], "at cart.py:12");
+    let location = &results[0]["locations"][0];
+    assert_eq!(
+        location["physicalLocation"]["artifactLocation"]["uri"],
+        "src/cart.py"
+    );
+    assert_eq!(location["physicalLocation"]["region"]["startLine"], 12);
+    assert_eq!(
+        location["logicalLocations"][0]["fullyQualifiedName"],
+        "Cart::total"
+    );
+    assert_eq!(results[1]["ruleIndex"], 1);
+    assert_eq!(results[1]["message"]["text"], "first line");
+    assert!(results[1].get("locations").is_none());
+    assert_eq!(results[2]["ruleId"], "IOError");
+    assert_eq!(results[2]["message"]["text"], "Test failed");
+    assert!(results[2]["locations"][0]["physicalLocation"]
+        .get("region")
+        .is_none());
+    assert_eq!(results[3]["ruleIndex"], 0);
+
+    let json = to_json(&report, &options).unwrap();
+    assert!(json.contains("\"$schema\""));
+}
@@ -0,0 +1,134 @@
+//! Test splitting tests across shards balanced by their past durations
+#![cfg(feature = "shard")]
+
+use junit_parser::shard::{plan_shards, test_ids, Granularity, Plan, ShardOptions};
+use junit_parser::TestSuites;
+use std::io::Cursor;
+
+/// Reports of two past runs
+fn history() -> Vec<TestSuites> {
+    [
+        r#"<testsuites>
+  <testsuite name="s1">
+    <testcase classname="A" name="a1" time="10"/>
+    <testcase classname="A" name="a2" time="2"/>
+    <testcase classname="B" name="b1" time="6"/>
+  </testsuite>
+  <testsuite name="s2">
+    <testcase classname="C" name="c1" time="4"/>
+    <testcase name="d1" time="3"/>
+  </testsuite>
+</testsuites>"#,
+        r#"<testsuites>
+  <testsuite name="s1">
+    <testcase classname="A" name="a1" time="6"/>
+    <testcase classname="A" name="a2" time="2"/>
+    <testcase classname="B" name="b1" time="6"/>
+  </testsuite>
+</testsuites>"#,
+    ]
+    .iter()
+    .map(|xml| junit_parser::from_reader(Cursor::new(xml)).unwrap())
+    .collect()
+}
+
+#[test]
+/// Test assigning test cases, averaging their durations over the reports
+fn test_plan_cases() {
+    let history = history();
+    let options = ShardOptions {
+        shards: 2,
+        default_time: Some(5.),
+        ..Default::default()
+    };
+    let tests = test_ids(&history[0], Granularity::Case);
+    assert_eq!(tests, vec!["A::a1", "A::a2", "B::b1", "C::c1", "d1"]);
+    let plan = plan_shards(&history, tests.iter().chain(&["new".to_string()]), &options);
+    assert_eq!(plan.granularity, Granularity::Case);
+    assert_eq!(plan.unknown, vec!["new"]);
+    // a1: 8, b1: 6, new: 5, c1: 4, d1: 3, a2: 2
+    assert_eq!(plan.shards.len(), 2);
+    assert_eq!(plan.shards[0].index, 0);
+    assert_eq!(plan.shards[0].tests, vec!["A::a1", "C::c1", "A::a2"]);
+    assert_eq!(plan.shards[0].estimated_time, 14.);
+    assert_eq!(plan.shards[1].tests, vec!["B::b1", "new", "d1"]);
+    assert_eq!(plan.shards[1].estimated_time, 14.);
+    assert_eq!(plan.longest(), 14.);
+}
+
+#[test]
+/// Test assigning classes and suites
+fn test_plan_classes_and_suites() {
+    let history = history();
+    let options = ShardOptions {
+        shards: 2,
+        granularity: Granularity::Class,
+        ..Default::default()
+    };
+    let tests = test_ids(&history[0], Granularity::Class);
+    assert_eq!(tests, vec!["A", "B", "C", "s2"]);
+    let plan = plan_shards(&history, &tests, &options);
+    // A: 10, B: 6, C: 4, s2: 3
+    assert_eq!(plan.shards[0].tests, vec!["A", "s2"]);
+    assert_eq!(plan.shards[1].tests, vec!["B", "C"]);
+    assert!(plan.unknown.is_empty());
+
+    let options = ShardOptions {
+        shards: 3,
+        granularity: Granularity::Suite,
+        ..Default::default()
+    };
+    let plan = plan_shards(&history, ["s1", "s2"], &options);
+    assert_eq!(plan.shards[0].tests, vec!["s1"]);
+    assert_eq!(plan.shards[0].estimated_time, 16.);
+    assert_eq!(plan.shards[1].tests, vec!["s2"]);
+    assert!(plan.shards[2].tests.is_empty());
+}
+
+#[test]
+/// Test that unknown tests get the average duration when no default is set,
+/// and that tests listed twice are assigned once
+fn test_plan_unknown_average() {
+    let report = junit_parser::from_reader(Cursor::new(
+        r#"<testsuite><testcase name="a" time="2"/><testcase name="b" time="4"/></testsuite>"#,
+    ))
+    .unwrap();
+    let plan = plan_shards([&report], ["x", "x", "a"], &ShardOptions::default());
+    assert_eq!(plan.shards.len(), 1);
+    assert_eq!(plan.shards[0].tests, vec!["x", "a"]);
+    assert_eq!(plan.shards[0].estimated_time, 5.);
+
+    // Without history, and with at least one shard
+    let options = ShardOptions {
+        shards: 0,
+        ..Default::default()
+    };
+    let plan = plan_shards(&[], ["x", "y"], &options);
+    assert_eq!(plan.shards.len(), 1);
+    assert_eq!(plan.shards[0].estimated_time, 2.);
+}
+
+#[test]
+/// Test writing the plan as JSON and as one file per shard
+fn test_plan_output() {
+    let history = history();
+    let options = ShardOptions {
+        shards: 2,
+        granularity: Granularity::Class,
+        ..Default::default()
+    };
+    let plan = plan_shards(&history, ["A", "B", "C"], &options);
+    let json = plan.to_json().unwrap();
+    assert!(json.contains(r#""granularity": "class""#), "{}", json);
+    let parsed: Plan = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, plan);
+
+    let dir = std::env::temp_dir().join(format!("junit-parser-shards-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let paths = plan.write_shard_files(&dir).unwrap();
+    assert_eq!(paths.len(), 2);
+    assert_eq!(paths[0], dir.join("shard-0.txt"));
+    assert_eq!(std::fs::read_to_string(&paths[0]).unwrap(), "A\n");
+    assert_eq!(std::fs::read_to_string(&paths[1]).unwrap(), "B\nC\n");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
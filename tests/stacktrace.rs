@@ -0,0 +1,323 @@
+//! Test parsing the stack traces of failures and errors
+#![cfg(feature = "stacktrace")]
+
+use junit_parser::stacktrace::{Frame, Language, StackTrace};
+use std::io::Cursor;
+
+/// Frame with a function, a module, a file and a line
+fn frame(module: &str, function: &str, file: &str, line: u64) -> Frame {
+    Frame {
+        function: Some(function.to_string()),
+        module: Some(module.to_string()),
+        file: Some(file.to_string()),
+        line: Some(line),
+        column: None,
+    }
+}
+
+#[test]
+/// Test a Java stack trace with a chain of causes
+fn test_java() {
+    let text = r#"java.lang.RuntimeException: wrapper
+	at com.example.Service.call(Service.java:42)
+	at java.base/java.lang.Thread.run(Thread.java:833)
+	at jdk.internal.reflect.NativeMethodAccessorImpl.invoke0(Native Method)
+	Suppressed: java.io.IOException: close failed
+		at com.example.Resource.close(Resource.java:9)
+Caused by: java.lang.IllegalStateException: boom
+	at com.example.Repo$Inner.load(Repo.kt:7)
+	... 3 more"#;
+    let trace = StackTrace::parse(text).unwrap();
+    assert_eq!(trace.language, Language::Java);
+    assert_eq!(
+        trace.message.as_deref(),
+        Some("java.lang.RuntimeException: wrapper")
+    );
+    assert_eq!(trace.frames.len(), 3);
+    assert_eq!(
+        trace.frames[0],
+        frame("com.example.Service", "call", "Service.java", 42)
+    );
+    assert_eq!(
+        trace.frames[1],
+        frame("java.lang.Thread", "run", "Thread.java", 833)
+    );
+    assert_eq!(trace.frames[2].file, None);
+    let cause = trace.cause.as_ref().unwrap();
+    assert_eq!(
+        cause.message.as_deref(),
+        Some("java.lang.IllegalStateException: boom")
+    );
+    assert_eq!(
+        cause.frames,
+        vec![frame("com.example.Repo$Inner", "load", "Repo.kt", 7)]
+    );
+    assert_eq!(trace.root_cause(), &**cause);
+}
+
+#[test]
+/// Test a chained Python traceback
+fn test_python() {
+    let text = r#"Traceback (most recent call last):
+  File "/app/db.py", line 10, in connect
+    raise OSError("refused")
+OSError: refused
+
+The above exception was the direct cause of the following exception:
+
+Traceback (most recent call last):
+  File "/app/test_db.py", line 4, in test_connect
+    db.connect()
+  File "/app/db.py", line 12, in connect
+    raise DatabaseError("unavailable") from e
+db.DatabaseError: unavailable"#;
+    let trace = StackTrace::parse(text).unwrap();
+    assert_eq!(trace.language, Language::Python);
+    assert_eq!(
+        trace.message.as_deref(),
+        Some("db.DatabaseError: unavailable")
+    );
+    assert_eq!(trace.frames.len(), 2);
+    let top = trace.top_frame().unwrap();
+    assert_eq!(top.function.as_deref(), Some("connect"));
+    assert_eq!(top.file.as_deref(), Some("/app/db.py"));
+    assert_eq!(top.line, Some(12));
+    assert_eq!(trace.frames[1].function.as_deref(), Some("test_connect"));
+    let cause = trace.cause.as_ref().unwrap();
+    assert_eq!(cause.message.as_deref(), Some("OSError: refused"));
+    assert_eq!(cause.frames[0].line, Some(10));
+    assert!(cause.cause.is_none());
+}
+
+#[test]
+/// Test a failure in pytest's long format
+fn test_pytest() {
+    let text = r#"def test_foo():
+>       assert 1 == 2
+E       assert 1 == 2
+
+tests/test_foo.py:3: AssertionError"#;
+    let trace = StackTrace::parse(text).unwrap();
+    assert_eq!(trace.language, Language::Python);
+    assert_eq!(trace.message.as_deref(), Some("assert 1 == 2"));
+    assert_eq!(trace.frames.len(), 1);
+    assert_eq!(trace.frames[0].file.as_deref(), Some("tests/test_foo.py"));
+    assert_eq!(trace.frames[0].line, Some(3));
+}
+
+#[test]
+/// Test a Rust panic with its backtrace
+fn test_rust() {
+    let text = r#"thread 'tests::it_fails' panicked at src/lib.rs:10:5:
+assertion `left == right` failed
+  left: 1
+ right: 2
+stack backtrace:
+   0: rust_begin_unwind
+             at /rustc/abc/library/std/src/panicking.rs:645:5
+   1: mycrate::tests::it_fails::h0123456789abcdef
+             at ./src/lib.rs:10:5
+note: Some details are omitted, run with `RUST_BACKTRACE=full` for a verbose backtrace."#;
+    let trace = StackTrace::parse(text).unwrap();
+    assert_eq!(trace.language, Language::Rust);
+    assert_eq!(
+        trace.message.as_deref(),
+        Some("assertion `left == right` failed")
+    );
+    assert_eq!(trace.frames.len(), 3);
+    let top = trace.top_frame().unwrap();
+    assert_eq!(top.file.as_deref(), Some("src/lib.rs"));
+    assert_eq!(top.line, Some(10));
+    assert_eq!(top.column, Some(5));
+    assert_eq!(
+        trace.frames[1].function.as_deref(),
+        Some("rust_begin_unwind")
+    );
+    assert_eq!(trace.frames[2].module.as_deref(), Some("mycrate::tests"));
+    assert_eq!(trace.frames[2].function.as_deref(), Some("it_fails"));
+    assert_eq!(trace.frames[2].file.as_deref(), Some("./src/lib.rs"));
+}
+
+#[test]
+/// Test a Rust panic in the format used before Rust 1.73
+fn test_rust_old_format() {
+    let text =
+        "thread 'main' panicked at 'called `Option::unwrap()` on a `None` value', src/main.rs:2:5";
+    let trace = StackTrace::parse(text).unwrap();
+    assert_eq!(
+        trace.message.as_deref(),
+        Some("called `Option::unwrap()` on a `None` value")
+    );
+    assert_eq!(trace.frames[0].file.as_deref(), Some("src/main.rs"));
+    assert_eq!(trace.frames[0].line, Some(2));
+}
+
+#[test]
+/// Test a Go panic, with several goroutines
+fn test_go() {
+    let text = "panic: runtime error: index out of range [3] with length 3
+
+goroutine 7 [running]:
+github.com/acme/app/store.(*Store).Get(0xc000010000, 0x3)
+\t/src/app/store/store.go:42 +0x1d
+github.com/acme/app/store.TestGet(0xc000082340)
+\t/src/app/store/store_test.go:12 +0x45
+created by testing.(*T).Run in goroutine 1
+\t/usr/local/go/src/testing/testing.go:1648 +0x3ad
+
+goroutine 1 [chan receive]:
+main.main()
+\t/src/main.go:3 +0x1
+exit status 2";
+    let trace = StackTrace::parse(text).unwrap();
+    assert_eq!(trace.language, Language::Go);
+    assert_eq!(
+        trace.message.as_deref(),
+        Some("runtime error: index out of range [3] with length 3")
+    );
+    assert_eq!(trace.frames.len(), 3);
+    assert_eq!(
+        trace.frames[0],
+        frame(
+            "github.com/acme/app/store",
+            "(*Store).Get",
+            "/src/app/store/store.go",
+            42
+        )
+    );
+    assert_eq!(trace.frames[2].module.as_deref(), Some("testing"));
+    assert_eq!(trace.frames[2].function.as_deref(), Some("(*T).Run"));
+}
+
+#[test]
+/// Test a failure printed by `go test`
+fn test_go_test() {
+    let text = "    store_test.go:21: expected 3, got 4";
+    let trace = StackTrace::parse(text).unwrap();
+    assert_eq!(trace.language, Language::Go);
+    assert_eq!(trace.message.as_deref(), Some("expected 3, got 4"));
+    assert_eq!(trace.frames[0].file.as_deref(), Some("store_test.go"));
+    assert_eq!(trace.frames[0].line, Some(21));
+}
+
+#[test]
+/// Test a .NET stack trace with an inner exception
+fn test_dotnet() {
+    let text = r#"System.InvalidOperationException: outer ---> System.ArgumentException: inner
+   at Acme.Parser.Parse(String s) in C:\src\Parser.cs:line 12
+   --- End of inner exception stack trace ---
+   at Acme.Service.Run() in C:\src\Service.cs:line 30
+   at Acme.Tests.ServiceTests.Runs()"#;
+    let trace = StackTrace::parse(text).unwrap();
+    assert_eq!(trace.language, Language::DotNet);
+    assert_eq!(
+        trace.message.as_deref(),
+        Some("System.InvalidOperationException: outer")
+    );
+    assert_eq!(trace.frames.len(), 2);
+    assert_eq!(
+        trace.frames[0],
+        frame("Acme.Service", "Run", r"C:\src\Service.cs", 30)
+    );
+    assert_eq!(trace.frames[1].function.as_deref(), Some("Runs"));
+    assert_eq!(trace.frames[1].file, None);
+    let cause = trace.cause.as_ref().unwrap();
+    assert_eq!(
+        cause.message.as_deref(),
+        Some("System.ArgumentException: inner")
+    );
+    assert_eq!(
+        cause.frames,
+        vec![frame("Acme.Parser", "Parse", r"C:\src\Parser.cs", 12)]
+    );
+}
+
+#[test]
+/// Test a V8 stack trace with a cause
+fn test_javascript_v8() {
+    let text = "Error: request failed
+    at Client.fetch (/app/src/client.js:10:11)
+    at async Context.<anonymous> (/app/test/client.test.js:5:3)
+    at /app/node_modules/mocha/lib/runner.js:1:2
+  [cause]: TypeError: fetch failed
+      at new Request (node:internal/deps/undici:3:4)";
+    let trace = StackTrace::parse(text).unwrap();
+    assert_eq!(trace.language, Language::JavaScript);
+    assert_eq!(trace.message.as_deref(), Some("Error: request failed"));
+    assert_eq!(trace.frames.len(), 3);
+    let top = trace.top_frame().unwrap();
+    assert_eq!(top.module.as_deref(), Some("Client"));
+    assert_eq!(top.function.as_deref(), Some("fetch"));
+    assert_eq!(top.file.as_deref(), Some("/app/src/client.js"));
+    assert_eq!(top.line, Some(10));
+    assert_eq!(top.column, Some(11));
+    assert_eq!(trace.frames[1].function.as_deref(), Some("<anonymous>"));
+    assert_eq!(trace.frames[2].function, None);
+    let cause = trace.cause.as_ref().unwrap();
+    assert_eq!(cause.message.as_deref(), Some("TypeError: fetch failed"));
+    assert_eq!(cause.frames[0].function.as_deref(), Some("Request"));
+    assert_eq!(cause.frames[0].line, Some(3));
+}
+
+#[test]
+/// Test a Firefox stack trace
+fn test_javascript_firefox() {
+    let text = "render@http://localhost/app.js:20:7\n@http://localhost/app.js:1:1";
+    let trace = StackTrace::parse(text).unwrap();
+    assert_eq!(trace.language, Language::JavaScript);
+    assert_eq!(trace.frames.len(), 2);
+    assert_eq!(trace.frames[0].function.as_deref(), Some("render"));
+    assert_eq!(
+        trace.frames[0].file.as_deref(),
+        Some("http://localhost/app.js")
+    );
+    assert_eq!(trace.frames[0].line, Some(20));
+    assert_eq!(trace.frames[1].function, None);
+}
+
+#[test]
+/// Test that texts without a stack trace are not parsed
+fn test_no_stack_trace() {
+    assert!(StackTrace::parse("").is_none());
+    assert!(StackTrace::parse("expected 1 but was 2").is_none());
+    assert!(StackTrace::parse("see https://example.com at noon").is_none());
+}
+
+#[test]
+/// Test parsing the stack traces of a parsed report
+fn test_from_report() {
+    let xml = r#"
+<testsuite>
+  <testcase name="AFailingTest">
+    <failure>java.lang.AssertionError: expected
+	at com.example.FooTest.fails(FooTest.java:12)</failure>
+  </testcase>
+  <testcase name="AnErrorTest">
+    <error>Traceback (most recent call last):
+  File "test_foo.py", line 3, in test_error
+KeyError: 'x'</error>
+  </testcase>
+  <testcase name="AFlakyTest">
+    <flakyFailure>
+      <stackTrace>Error: flaky
+    at test (/app/flaky.test.js:2:3)</stackTrace>
+    </flakyFailure>
+  </testcase>
+</testsuite>"#;
+    let r = junit_parser::from_reader(Cursor::new(xml));
+    assert!(r.is_ok());
+    let cases = &r.unwrap().suites[0].cases;
+    let trace = cases[0]
+        .status
+        .failure_as_ref()
+        .parse_stack_trace()
+        .unwrap();
+    assert_eq!(trace.language, Language::Java);
+    assert_eq!(trace.frames[0].line, Some(12));
+    let trace = cases[1].status.error_as_ref().parse_stack_trace().unwrap();
+    assert_eq!(trace.language, Language::Python);
+    assert_eq!(trace.message.as_deref(), Some("KeyError: 'x'"));
+    let trace = cases[2].reruns[0].parse_stack_trace().unwrap();
+    assert_eq!(trace.language, Language::JavaScript);
+    assert_eq!(trace.frames[0].function.as_deref(), Some("test"));
+}
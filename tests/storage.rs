@@ -0,0 +1,163 @@
+//! Test storing the history of reports in an SQLite database
+#![cfg(feature = "sqlite")]
+
+use junit_parser::storage::{Status, Store};
+use junit_parser::Error;
+use std::io::Cursor;
+
+/// Reports of three successive runs
+const RUNS: [(&str, &str); 3] = [
+    (
+        "abc123",
+        r#"<testsuites name="ci" tests="2">
+  <testsuite name="s" tests="2">
+    <properties><property name="os" value="linux"/></properties>
+    <testcase classname="c" name="fast" time="0.1"/>
+    <testcase classname="c" name="slow" time="1.0"><flakyFailure type="E" message="retry"/></testcase>
+  </testsuite>
+</testsuites>"#,
+    ),
+    (
+        "def456",
+        r#"<testsuites name="ci" tests="2">
+  <testsuite name="s" tests="2">
+    <testcase classname="c" name="fast" time="0.2"/>
+    <testcase classname="c" name="slow" time="2.0"><failure message="timeout"/></testcase>
+    <testsuite name="nested"><testcase classname="c" name="slow" time="0.5"/></testsuite>
+  </testsuite>
+</testsuites>"#,
+    ),
+    (
+        "789fed",
+        r#"<testsuites name="ci" tests="2">
+  <testsuite name="s" tests="2">
+    <testcase classname="c" name="fast"><skipped message="disabled"/></testcase>
+    <testcase classname="c" name="slow" time="3.0"/>
+  </testsuite>
+</testsuites>"#,
+    ),
+];
+
+/// Store with the three runs ingested
+fn store() -> Store {
+    let mut store = Store::open_in_memory().unwrap();
+    for (label, xml) in RUNS.iter() {
+        let report = junit_parser::from_reader(Cursor::new(xml)).unwrap();
+        store.ingest(&report, label).unwrap();
+    }
+    store
+}
+
+#[test]
+/// Test listing the ingested runs
+fn test_runs() {
+    let runs = store().runs().unwrap();
+    assert_eq!(runs.len(), 3);
+    let labels: Vec<_> = runs.iter().map(|r| r.label.as_str()).collect();
+    assert_eq!(labels, vec!["abc123", "def456", "789fed"]);
+    assert_eq!(runs[0].name, "ci");
+    assert_eq!(runs[0].tests, 2);
+    assert!(runs[0].id < runs[1].id);
+}
+
+#[test]
+/// Test the history of a test case
+fn test_test_history() {
+    let store = store();
+    let history = store.test_history("c::fast").unwrap();
+    assert_eq!(history.len(), 3);
+    assert_eq!(history[0].status, Status::Success);
+    assert_eq!(history[0].message, None);
+    assert_eq!(history[1].time, 0.2);
+    assert_eq!(history[2].status, Status::Skipped);
+    assert_eq!(history[2].message.as_deref(), Some("disabled"));
+
+    let history = store.test_history("c::slow").unwrap();
+    assert_eq!(history.len(), 4);
+    assert_eq!(history[1].label, "def456");
+    assert_eq!(history[1].status, Status::Failure);
+    assert_eq!(history[1].message.as_deref(), Some("timeout"));
+    assert_eq!(history[2].label, "def456");
+    assert_eq!(history[2].status, Status::Success);
+
+    assert!(store.test_history("c::missing").unwrap().is_empty());
+}
+
+#[test]
+/// Test the duration trend of a test case
+fn test_duration_trend() {
+    let store = store();
+    let runs = store.runs().unwrap();
+    let trend = store.duration_trend("c::slow", None).unwrap();
+    assert_eq!(
+        trend,
+        vec![(runs[0].id, 1.0), (runs[1].id, 2.5), (runs[2].id, 3.0)]
+    );
+    let trend = store.duration_trend("c::slow", Some(2)).unwrap();
+    assert_eq!(trend, vec![(runs[1].id, 2.5), (runs[2].id, 3.0)]);
+    // Skipped runs are ignored
+    let trend = store.duration_trend("c::fast", None).unwrap();
+    assert_eq!(trend.len(), 2);
+}
+
+#[test]
+/// Test the failure rate of a test case
+fn test_failure_rate() {
+    let store = store();
+    let rate = store.failure_rate("c::slow", None).unwrap().unwrap();
+    assert!((rate - 1.0 / 3.0).abs() < 1e-9);
+    assert_eq!(store.failure_rate("c::slow", Some(1)).unwrap(), Some(0.0));
+    assert_eq!(store.failure_rate("c::fast", None).unwrap(), Some(0.0));
+    assert_eq!(store.failure_rate("c::missing", None).unwrap(), None);
+}
+
+#[test]
+/// Test that the history is kept in the database file
+fn test_open_file() {
+    let path = std::env::temp_dir().join(format!("junit-parser-{}.sqlite", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    {
+        let mut store = Store::open(&path).unwrap();
+        let report = junit_parser::from_reader(Cursor::new(RUNS[0].1)).unwrap();
+        store.ingest(&report, RUNS[0].0).unwrap();
+    }
+    let mut store = Store::open(&path).unwrap();
+    let report = junit_parser::from_reader(Cursor::new(RUNS[1].1)).unwrap();
+    store.ingest(&report, RUNS[1].0).unwrap();
+    assert_eq!(store.runs().unwrap().len(), 2);
+    assert_eq!(store.test_history("c::fast").unwrap().len(), 2);
+    drop(store);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+/// Test rejecting a database with a newer schema, or an unknown status
+fn test_invalid_database() {
+    let path = std::env::temp_dir().join(format!(
+        "junit-parser-invalid-{}.sqlite",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    {
+        let mut store = Store::open(&path).unwrap();
+        let report = junit_parser::from_reader(Cursor::new(RUNS[0].1)).unwrap();
+        store.ingest(&report, RUNS[0].0).unwrap();
+    }
+    let conn = rusqlite::Connection::open(&path).unwrap();
+    conn.execute("UPDATE cases SET status = 'passed'", [])
+        .unwrap();
+    let store = Store::open(&path).unwrap();
+    assert!(matches!(
+        store.test_history("c::fast"),
+        Err(Error::SqliteError(_))
+    ));
+
+    conn.pragma_update(None, "user_version", 2).unwrap();
+    assert!(matches!(
+        Store::open(&path),
+        Err(Error::UnsupportedSchemaVersion(2))
+    ));
+    drop(store);
+    drop(conn);
+    std::fs::remove_file(&path).unwrap();
+}
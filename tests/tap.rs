@@ -0,0 +1,64 @@
+//! Test rendering reports as TAP
+
+use junit_parser::tap::{render, TapOptions, TapVersion};
+use std::io::Cursor;
+
+#[test]
+/// Test the test points, subtests and diagnostics
+fn test_render() {
+    let xml = r#"<testsuites>
+  <testsuite name="cart">
+    <testcase name="total" classname="Cart" time="0.5" file="src/cart.py" line="12">
+      <failure type="AssertionError" message="expected &quot;10&quot;">line 1
+line 2</failure>
+    </testcase>
+    <testcase name="issue #2"><skipped message="see #2"/></testcase>
+    <testsuite name="nested">
+      <testcase name="connect"><error/></testcase>
+    </testsuite>
+  </testsuite>
+  <testsuite name="login">
+    <testcase name="login"/>
+    <testcase name="logout"><skipped/></testcase>
+  </testsuite>
+</testsuites>"#;
+    let report = junit_parser::from_reader(Cursor::new(xml)).unwrap();
+    assert_eq!(
+        render(&report, &TapOptions::default()),
+        r#"TAP version 14
+1..2
+# Subtest: cart
+    1..3
+    not ok 1 - Cart::total
+      ---
+      message: "expected \"10\""
+      severity: fail
+      type: "AssertionError"
+      duration_ms: 500
+      file: "src/cart.py"
+      line: 12
+      stack: "line 1\nline 2"
+      ...
+    ok 2 - issue \#2 # SKIP see \#2
+    # Subtest: nested
+        1..1
+        not ok 1 - connect
+          ---
+          severity: error
+          duration_ms: 0
+          ...
+    not ok 3 - nested
+not ok 1 - cart
+# Subtest: login
+    1..2
+    ok 1 - login
+    ok 2 - logout # SKIP
+ok 2 - login
+"#
+    );
+
+    let options = TapOptions {
+        version: TapVersion::Tap13,
+    };
+    assert!(render(&report, &options).starts_with("TAP version 13\n1..2\n"));
+}
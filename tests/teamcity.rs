@@ -0,0 +1,55 @@
+//! Test replaying reports as TeamCity service messages
+
+use junit_parser::teamcity::{messages, ServiceMessage, TeamCityOptions};
+use std::io::Cursor;
+
+#[test]
+/// Test the messages of test suites and test cases
+fn test_messages() {
+    let xml = r#"<testsuite name="cart">
+  <testcase name="total" classname="Cart" time="0.0125">
+    <failure message="expected '10' [got 11]">line 1|
+line 2</failure>
+    <system-out>computing</system-out>
+  </testcase>
+  <testcase name="skip"><skipped message="later"/></testcase>
+  <testsuite name="db">
+    <testcase name="connect"><error message="reset"/></testcase>
+  </testsuite>
+</testsuite>"#;
+    let report = junit_parser::from_reader(Cursor::new(xml)).unwrap();
+    let options = TeamCityOptions {
+        flow_id: Some("1".to_string()),
+        ..Default::default()
+    };
+    let lines: Vec<String> = messages(&report, &options)
+        .iter()
+        .map(ServiceMessage::to_string)
+        .collect();
+    assert_eq!(
+        lines,
+        vec![
+            "##teamcity[testSuiteStarted name='cart' flowId='1']",
+            "##teamcity[testStarted name='Cart::total' captureStandardOutput='false' flowId='1']",
+            "##teamcity[testStdOut name='Cart::total' out='computing' flowId='1']",
+            "##teamcity[testFailed name='Cart::total' message='expected |'10|' |[got 11|]' details='line 1|||nline 2' flowId='1']",
+            "##teamcity[testFinished name='Cart::total' duration='13' flowId='1']",
+            "##teamcity[testStarted name='skip' captureStandardOutput='false' flowId='1']",
+            "##teamcity[testIgnored name='skip' message='later' flowId='1']",
+            "##teamcity[testFinished name='skip' duration='0' flowId='1']",
+            "##teamcity[testSuiteStarted name='db' flowId='1']",
+            "##teamcity[testStarted name='connect' captureStandardOutput='false' flowId='1']",
+            "##teamcity[testFailed name='connect' message='reset' details='' flowId='1']",
+            "##teamcity[testFinished name='connect' duration='0' flowId='1']",
+            "##teamcity[testSuiteFinished name='db' flowId='1']",
+            "##teamcity[testSuiteFinished name='cart' flowId='1']",
+        ]
+    );
+
+    let options = TeamCityOptions {
+        outputs: false,
+        ..Default::default()
+    };
+    let messages = messages(&report, &options);
+    assert!(messages.iter().all(|m| m.name != "testStdOut"));
+}
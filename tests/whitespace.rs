@@ -0,0 +1,133 @@
+//! Test preserving whitespace in bodies with `ParseOptions::preserve_whitespace`
+
+use junit_parser::ParseOptions;
+use std::io::Cursor;
+
+fn preserving() -> ParseOptions {
+    ParseOptions {
+        preserve_whitespace: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+/// Test that the default behavior trims text chunks and joins them with newlines
+fn test_default_trims_failure() {
+    let xml = r#"
+<testsuite>
+  <testcase name="AFailingTest">
+    <failure>
+      first<!-- comment -->  second
+    </failure>
+  </testcase>
+</testsuite>"#;
+    let cursor = Cursor::new(xml);
+    let r = junit_parser::from_reader(cursor);
+    assert!(r.is_ok());
+    let suite = &r.unwrap().suites[0];
+    let tf = suite.cases[0].status.failure_as_ref();
+    assert_eq!(tf.text, "first\nsecond");
+}
+
+#[test]
+/// Test that a python traceback keeps its indentation in a `<failure>`
+fn test_preserve_failure() {
+    let xml = r#"
+<testsuite>
+  <testcase name="AFailingTest">
+    <failure message="assert 1 == 2">Traceback (most recent call last):
+  File "test_foo.py", line 3, in test_foo
+    assert 1 == 2
+AssertionError</failure>
+  </testcase>
+</testsuite>"#;
+    let cursor = Cursor::new(xml);
+    let r = junit_parser::from_reader_with_options(cursor, &preserving());
+    assert!(r.is_ok());
+    let suite = &r.unwrap().suites[0];
+    let tf = suite.cases[0].status.failure_as_ref();
+    assert_eq!(
+        tf.text,
+        r#"Traceback (most recent call last):
+  File "test_foo.py", line 3, in test_foo
+    assert 1 == 2
+AssertionError"#
+    );
+}
+
+#[test]
+/// Test that chunks split around entities and comments are not separated by
+/// newlines in an `<error>`
+fn test_preserve_error_entities_and_comments() {
+    let xml = r#"
+<testsuite>
+  <testcase name="AnErrorTest">
+    <error>  if a &lt; b &amp;&amp; c &#x3E; d<!-- comment -->  {
+    }</error>
+  </testcase>
+</testsuite>"#;
+    let cursor = Cursor::new(xml);
+    let r = junit_parser::from_reader_with_options(cursor, &preserving());
+    assert!(r.is_ok());
+    let suite = &r.unwrap().suites[0];
+    let te = suite.cases[0].status.error_as_ref();
+    assert_eq!(te.text, "  if a < b && c > d  {\n    }");
+}
+
+#[test]
+/// Test mixing text and CDATA sections in a `<skipped>`
+fn test_preserve_skipped_cdata() {
+    let xml = r#"
+<testsuite>
+  <testcase name="ASkippedTest">
+    <skipped>
+  reason: <![CDATA[<not ready>]]>
+</skipped>
+  </testcase>
+</testsuite>"#;
+    let cursor = Cursor::new(xml);
+    let r = junit_parser::from_reader_with_options(cursor, &preserving());
+    assert!(r.is_ok());
+    let suite = &r.unwrap().suites[0];
+    let ts = suite.cases[0].status.skipped_as_ref();
+    assert_eq!(ts.text, "\n  reason: <not ready>\n");
+}
+
+#[test]
+/// Test preserving the body of a rerun element
+fn test_preserve_rerun() {
+    let xml = r#"
+<testsuite>
+  <testcase name="AFlakyTest">
+    <flakyFailure type="AssertionError">  expected: 1
+  actual: 2<system-out>out</system-out>
+  done</flakyFailure>
+  </testcase>
+</testsuite>"#;
+    let cursor = Cursor::new(xml);
+    let r = junit_parser::from_reader_with_options(cursor, &preserving());
+    assert!(r.is_ok());
+    let suite = &r.unwrap().suites[0];
+    let rerun = &suite.cases[0].reruns[0];
+    assert_eq!(rerun.text, "  expected: 1\n  actual: 2\n  done");
+    assert_eq!(rerun.system_out, Some("out".to_string()));
+}
+
+#[test]
+/// Test that entities are kept in `system-out` and `system-err`
+fn test_preserve_system_entities() {
+    let xml = r#"
+<testsuite>
+  <testcase name="ATest">
+    <system-out>a &amp; b</system-out>
+    <system-err>  &lt;err&gt;  </system-err>
+  </testcase>
+</testsuite>"#;
+    let cursor = Cursor::new(xml);
+    let r = junit_parser::from_reader_with_options(cursor, &preserving());
+    assert!(r.is_ok());
+    let suite = &r.unwrap().suites[0];
+    let tc = &suite.cases[0];
+    assert_eq!(tc.system_out, Some("a & b".to_string()));
+    assert_eq!(tc.system_err, Some("  <err>  ".to_string()));
+}
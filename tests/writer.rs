@@ -0,0 +1,134 @@
+//! Test writing reports as JUnit XML
+
+use junit_parser::writer::{normalize, to_string, Profile, WriteOptions};
+use junit_parser::TestStatus;
+
+mod common;
+use common::parse;
+
+/// Report with nested test suites, outputs and attachments
+const REPORT: &str = r#"<testsuites name="nightly" tests="9" time="3">
+  <testsuite name="shop" tests="9">
+    <testsuite name="cart" package="com.shop" time="2" hostname="ci-1">
+      <properties><property name="os" value="linux"/></properties>
+      <testcase name="total" classname="Cart" time="1.5" file="src/cart.py" line="12">
+        <failure type="AssertionError" message="expected &lt;10&gt;">at cart.py:12</failure>
+        <system-out>computing</system-out>
+      </testcase>
+      <testcase name="refund" classname="com.shop.Refund" time="0.5">
+        <flakyFailure message="timeout" type="Timeout" time="0.25">first try<stackTrace>at refund.py:3</stackTrace></flakyFailure>
+      </testcase>
+      <system-out>[[ATTACHMENT|/tmp/cart.png]]
+other output</system-out>
+    </testsuite>
+    <testsuite name="login">
+      <testcase name="login"><skipped message="later"/></testcase>
+      <testcase name="logout"><error/></testcase>
+    </testsuite>
+  </testsuite>
+</testsuites>"#;
+
+#[test]
+/// Test writing a report and parsing it back
+fn test_round_trip() {
+    let report = parse(REPORT);
+    let xml = to_string(&report, &WriteOptions::default()).unwrap();
+    assert!(xml.starts_with(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites name=\"nightly\" tests=\"9\""
+    ));
+    assert!(xml.contains(
+        "\n      <testcase name=\"refund\" classname=\"com.shop.Refund\" time=\"0.5\">\n"
+    ));
+
+    let parsed = parse(&xml);
+    assert_eq!(parsed.name, "nightly");
+    assert_eq!(parsed.tests, 9);
+    let shop = &parsed.suites[0];
+    assert_eq!(shop.suites.len(), 2);
+    let cart = &shop.suites[0];
+    assert_eq!(cart.package.as_deref(), Some("com.shop"));
+    assert_eq!(cart.hostname.as_deref(), Some("ci-1"));
+    assert_eq!(cart.time, 2.);
+    assert!(cart.system_out.as_deref().unwrap().contains("other output"));
+    let total = &cart.cases[0];
+    assert_eq!(total.name, "Cart::total");
+    assert_eq!(total.file.as_deref(), Some("src/cart.py"));
+    assert_eq!(total.line, Some(12));
+    assert_eq!(total.system_out.as_deref(), Some("computing"));
+    match &total.status {
+        TestStatus::Failure(f) => {
+            assert_eq!(f.message, "expected <10>");
+            assert_eq!(f.failure_type, "AssertionError");
+            assert_eq!(f.text, "at cart.py:12");
+        }
+        status => panic!("unexpected status {:?}", status),
+    }
+    let rerun = &cart.cases[1].reruns[0];
+    assert_eq!(rerun.message, "timeout");
+    assert_eq!(rerun.rerun_type, "Timeout");
+    assert_eq!(rerun.time, 0.25);
+    assert_eq!(rerun.text, "first try");
+    assert_eq!(rerun.stack_trace.as_deref(), Some("at refund.py:3"));
+    assert!(shop.suites[1].cases[0].status.is_skipped());
+    assert!(shop.suites[1].cases[1].status.is_error());
+    #[cfg(any(feature = "properties_as_hashmap", feature = "properties_as_vector"))]
+    assert!(xml.contains("<property name=\"os\" value=\"linux\"/>"));
+}
+
+#[test]
+/// Test normalizing a report for GitLab
+fn test_gitlab() {
+    let mut report = parse(REPORT);
+    normalize(&mut report, Profile::GitLab);
+    let names: Vec<&str> = report.suites.iter().map(|ts| ts.name.as_str()).collect();
+    assert_eq!(names, vec!["shop.cart", "shop.login"]);
+    assert!(report.suites.iter().all(|ts| ts.suites.is_empty()));
+    assert_eq!(
+        (report.tests, report.failures, report.errors, report.skipped),
+        (4, 1, 1, 1)
+    );
+    let cart = &report.suites[0];
+    assert_eq!((cart.tests, cart.failures, cart.errors), (2, 1, 0));
+    assert_eq!(
+        cart.cases[0].system_out.as_deref(),
+        Some("computing\n[[ATTACHMENT|/tmp/cart.png]]")
+    );
+    assert_eq!(
+        cart.cases[1].system_out.as_deref(),
+        Some("[[ATTACHMENT|/tmp/cart.png]]")
+    );
+    assert_eq!(cart.cases[0].classname.as_deref(), Some("Cart"));
+    let login = &report.suites[1];
+    assert_eq!(login.time, 0.);
+    assert_eq!((login.tests, login.errors, login.skipped), (2, 1, 1));
+
+    let xml = to_string(
+        &report,
+        &WriteOptions {
+            profile: Profile::GitLab,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(xml.matches("<testsuite ").count(), 2);
+}
+
+#[test]
+/// Test normalizing a report for Jenkins
+fn test_jenkins() {
+    let mut report = parse(REPORT);
+    normalize(&mut report, Profile::Jenkins);
+    let cart = &report.suites[0];
+    assert_eq!(cart.cases[0].classname.as_deref(), Some("com.shop.Cart"));
+    assert_eq!(cart.cases[0].name, "com.shop.Cart::total");
+    assert_eq!(cart.cases[1].classname.as_deref(), Some("com.shop.Refund"));
+    assert_eq!(cart.cases[0].system_out.as_deref(), Some("computing"));
+    let login = &report.suites[1];
+    assert_eq!(login.cases[0].classname.as_deref(), Some("shop.login"));
+    assert_eq!(report.tests, 4);
+
+    let mut canonical = parse(REPORT);
+    normalize(&mut canonical, Profile::Canonical);
+    assert_eq!(canonical.suites.len(), 1);
+    assert_eq!(canonical.tests, 9);
+}